@@ -1,6 +1,9 @@
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use tic_tac_toe_mcts::bitboard::BitBoard;
 use tic_tac_toe_mcts::interfaces::{GameState, PointPlacement, TicTacToeReferee};
-use tic_tac_toe_mcts::referee::{FasterRefereeV1, NaiveReferee};
+use tic_tac_toe_mcts::referee::{
+    BitBoardReferee, FasterRefereeV1, IncrementalReferee, NaiveReferee,
+};
 
 fn bench_fibs(c: &mut Criterion) {
     let board = [[None; 7]; 7];
@@ -13,6 +16,9 @@ fn bench_fibs(c: &mut Criterion) {
 
     let naive_referee = NaiveReferee::new(winning_length);
     let faster_referee = FasterRefereeV1::new(winning_length);
+    let incremental_referee = IncrementalReferee::new(winning_length);
+    let bitboard_referee = BitBoardReferee::new(winning_length);
+    let mut bitboard = BitBoard::from_game_state(&board, [0, 1]).unwrap();
 
     let mut group = c.benchmark_group("TicTacToe Referee (empty board)");
     for cur in &placements {
@@ -32,9 +38,97 @@ fn bench_fibs(c: &mut Criterion) {
                 });
             },
         );
+        group.bench_with_input(
+            BenchmarkId::new("IncrementalReferee", cur),
+            cur,
+            |b, &pp| {
+                b.iter(|| {
+                    incremental_referee.receive_move(&mut board, pp, 0);
+                    board[pp] = None.into();
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("BitBoardReferee", cur),
+            cur,
+            |b, &pp| {
+                b.iter(|| {
+                    bitboard_referee.receive_move(&mut bitboard, pp, 0);
+                    bitboard.remove(pp);
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Lets the last free cell of a board without lines be taken, which ends in a draw.
+///
+/// Referees looking for free cells have to scan the whole board here, unless they are
+/// told the number of free cells.
+fn bench_nearly_full_board(c: &mut Criterion) {
+    const N: u16 = 7;
+    let winning_length = 4;
+    let naive_referee = NaiveReferee::new(winning_length);
+    let faster_referee = FasterRefereeV1::new(winning_length);
+    let incremental_referee = IncrementalReferee::new(winning_length);
+
+    let mut group = c.benchmark_group("TicTacToe Referee (nearly full board)");
+    for pp in [
+        PointPlacement { row: 3, column: 3 },
+        PointPlacement { row: 0, column: 0 },
+    ] {
+        // Pairs of stones alternate, shifted by one cell per row, so that no line of
+        // four stones exists.
+        let mut board = GameState::new(N, N, None);
+        for row in 0..N {
+            for column in 0..N {
+                let player = (column / 2 + row) % 2;
+                board[PointPlacement { row, column }] = Some(player).into();
+            }
+        }
+        let player = (pp.column / 2 + pp.row) % 2;
+        board[pp] = None.into();
+
+        group.bench_with_input(BenchmarkId::new("NaiveReferee", pp), &pp, |b, &pp| {
+            b.iter(|| {
+                naive_referee.receive_move(&mut board, pp, player);
+                board[pp] = None.into();
+            });
+        });
+        group.bench_with_input(
+            BenchmarkId::new("FasterRefereeV1", pp),
+            &pp,
+            |b, &pp| {
+                b.iter(|| {
+                    faster_referee.receive_move(&mut board, pp, player);
+                    board[pp] = None.into();
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("IncrementalReferee", pp),
+            &pp,
+            |b, &pp| {
+                b.iter(|| {
+                    incremental_referee.receive_move(&mut board, pp, player);
+                    board[pp] = None.into();
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("IncrementalReferee (counted)", pp),
+            &pp,
+            |b, &pp| {
+                b.iter(|| {
+                    incremental_referee.receive_counted_move(&mut board, pp, player, 1);
+                    board[pp] = None.into();
+                });
+            },
+        );
     }
     group.finish();
 }
 
-criterion_group!(benches, bench_fibs);
+criterion_group!(benches, bench_fibs, bench_nearly_full_board);
 criterion_main!(benches);
@@ -6,15 +6,22 @@ use crate::interfaces::{
 pub struct ExploitingArena<'arena> {
     active_player: usize,
     board: GameState,
-    players: [&'arena mut (dyn Player); 2],
-    referee: &'arena mut (dyn TicTacToeReferee),
+    players: Vec<&'arena mut dyn Player>,
+    referee: &'arena mut dyn TicTacToeReferee,
 }
 
 impl<'arena> ExploitingArena<'arena> {
+    /// Creates a new arena in which `starting_player` makes the first move.
+    ///
+    /// The players take turns in the given order, starting over after the last one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not exactly one of `players` has the ID `starting_player`.
     pub fn new(
         starting_player: PlayerID,
         board: GameState,
-        players: [&'arena mut dyn Player; 2],
+        players: Vec<&'arena mut dyn Player>,
         referee: &'arena mut dyn TicTacToeReferee,
     ) -> Self {
         let matching_players: Vec<_> = players
@@ -42,16 +49,16 @@ impl<'arena> ExploitingArena<'arena> {
     }
 
     fn get_first_maximum_point_placement(
-        board: &GameState,
+        legal_moves: &[PointPlacement],
         placement: &Placement,
     ) -> Option<PointPlacement> {
-        board
-            .joint_iter_2d(placement)
-            .filter(|(_, cell, _)| cell.is_free())
-            .filter(|(_, _, weight)| weight > &&0.0)
-            .fold((None, 0.0), |(result_pp, max_weight), (pp, _, weight)| {
-                if weight > &max_weight {
-                    (Some(pp), *weight)
+        legal_moves
+            .iter()
+            .map(|&pp| (pp, placement[pp]))
+            .filter(|(_, weight)| weight > &0.0)
+            .fold((None, 0.0), |(result_pp, max_weight), (pp, weight)| {
+                if weight > max_weight {
+                    (Some(pp), weight)
                 } else {
                     (result_pp, max_weight)
                 }
@@ -62,22 +69,25 @@ impl<'arena> ExploitingArena<'arena> {
 
 impl TicTacToeArena for ExploitingArena<'_> {
     fn do_next_move(&mut self) -> (GameResult, PlayerID, Option<PointPlacement>) {
-        let cur_player = &mut self.players[self.active_player % 2];
-        self.active_player += 1;
+        let nplayers = self.players.len();
+        let cur_player = &mut self.players[self.active_player % nplayers];
+        let cur_id = cur_player.get_id();
         let placements = cur_player.do_move(&self.board);
         let maybe_pp = ExploitingArena::get_first_maximum_point_placement(
-            &self.board,
+            &self.referee.get_legal_moves(&self.board),
             &placements,
         );
 
         match maybe_pp {
             Some(pp) => {
-                let result =
-                    self.referee
-                        .receive_move(&mut self.board, pp, cur_player.get_id());
-                (result, cur_player.get_id(), Some(pp))
+                let result = self.referee.receive_move(&mut self.board, pp, cur_id);
+                // Turns of several stones are completed by the same player.
+                if self.referee.is_turn_over(&self.board) {
+                    self.active_player += 1;
+                }
+                (result, cur_id, Some(pp))
             }
-            None => (GameResult::Defeat, cur_player.get_id(), None),
+            None => (GameResult::Defeat, cur_id, None),
         }
     }
 
@@ -4,21 +4,50 @@ use crate::interfaces::{
 };
 use rand::distr::weighted::WeightedIndex;
 use rand::prelude::*;
-use rand::rng;
+use rand::rngs::StdRng;
 
 pub struct ExploringTicTacToeArena<'arena> {
     active_player: usize,
     board: GameState,
-    players: [&'arena mut (dyn Player); 2],
-    referee: &'arena mut (dyn TicTacToeReferee),
+    players: Vec<&'arena mut dyn Player>,
+    referee: &'arena mut dyn TicTacToeReferee,
+    rng: StdRng,
 }
 
 impl<'arena> ExploringTicTacToeArena<'arena> {
+    /// Creates a new arena in which `starting_player` makes the first move.
+    ///
+    /// The players take turns in the given order, starting over after the last one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not exactly one of `players` has the ID `starting_player`.
     pub fn new(
         board: GameState,
-        players: [&'arena mut dyn Player; 2],
+        players: Vec<&'arena mut dyn Player>,
         starting_player: PlayerID,
         referee: &'arena mut dyn TicTacToeReferee,
+    ) -> Self {
+        Self::new_with_rng(
+            board,
+            players,
+            starting_player,
+            referee,
+            StdRng::from_os_rng(),
+        )
+    }
+
+    /// Creates a new arena sampling the moves with `rng`, see `new`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not exactly one of `players` has the ID `starting_player`.
+    pub fn new_with_rng(
+        board: GameState,
+        players: Vec<&'arena mut dyn Player>,
+        starting_player: PlayerID,
+        referee: &'arena mut dyn TicTacToeReferee,
+        rng: StdRng,
     ) -> Self {
         let matching_players: Vec<_> = players
             .iter()
@@ -39,24 +68,23 @@ impl<'arena> ExploringTicTacToeArena<'arena> {
                 players,
                 active_player: n,
                 referee,
+                rng,
             },
             _ => panic!("Multiple matching player found for ID {starting_player}"),
         }
     }
 
     fn sample_point_placement(
-        board: &GameState,
-        placement: Placement,
+        legal_moves: &[PointPlacement],
+        placement: &Placement,
+        rng: &mut StdRng,
     ) -> Option<PointPlacement> {
         let mut pps = Vec::<PointPlacement>::new();
         let mut weights = Vec::<f32>::new();
 
-        // Get point placement candidates with weights
-        for (pp, weight) in placement.into_iter_2d() {
-            if board[pp].is_taken() {
-                // Skip already occupied cells
-                continue;
-            }
+        // Get point placement candidates with weights, skipping illegal cells
+        for &pp in legal_moves {
+            let weight = placement[pp];
             if weight == 0.0 {
                 continue;
             }
@@ -69,29 +97,34 @@ impl<'arena> ExploringTicTacToeArena<'arena> {
         }
 
         // Sample candidate from eligble options
-        let mut rng = rng();
         let dist = WeightedIndex::new(weights).unwrap();
-        let sampled_idx = dist.sample(&mut rng);
+        let sampled_idx = dist.sample(rng);
         Some(pps[sampled_idx])
     }
 }
 
 impl TicTacToeArena for ExploringTicTacToeArena<'_> {
     fn do_next_move(&mut self) -> (GameResult, PlayerID, Option<PointPlacement>) {
-        let cur_player = &mut self.players[self.active_player % 2];
-        self.active_player += 1;
+        let nplayers = self.players.len();
+        let cur_player = &mut self.players[self.active_player % nplayers];
+        let cur_id = cur_player.get_id();
         let placements = cur_player.do_move(&self.board);
-        let maybe_pp =
-            ExploringTicTacToeArena::sample_point_placement(&self.board, placements);
+        let maybe_pp = ExploringTicTacToeArena::sample_point_placement(
+            &self.referee.get_legal_moves(&self.board),
+            &placements,
+            &mut self.rng,
+        );
 
         match maybe_pp {
             Some(pp) => {
-                let result =
-                    self.referee
-                        .receive_move(&mut self.board, pp, cur_player.get_id());
-                (result, cur_player.get_id(), Some(pp))
+                let result = self.referee.receive_move(&mut self.board, pp, cur_id);
+                // Turns of several stones are completed by the same player.
+                if self.referee.is_turn_over(&self.board) {
+                    self.active_player += 1;
+                }
+                (result, cur_id, Some(pp))
             }
-            None => (GameResult::Defeat, cur_player.get_id(), None),
+            None => (GameResult::Defeat, cur_id, None),
         }
     }
 
@@ -0,0 +1,286 @@
+use crate::interfaces::{
+    BoardSizeT, BoardStateEntry, GameState, PlayerID, PointPlacement, WinLengthT,
+};
+
+type BitSet = u128;
+
+/// Compact, allocation-free representation of a two-player `GameState`.
+///
+/// The stones of each player are stored in one bitset. Cells are laid out row by row,
+/// with one unused padding bit after every row. This padding separates the rows, so
+/// that lines can be detected by shifting a bitset onto itself without wrapping into
+/// the next row.
+///
+/// Since the type is `Copy` and cheap to hash, it is well suited as key of a
+/// `GameStateStorage` and for deep searches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BitBoard {
+    ncolumns: BoardSizeT,
+    nrows: BoardSizeT,
+    players: [PlayerID; 2],
+    stones: [BitSet; 2],
+}
+
+impl BitBoard {
+    /// Creates an empty `BitBoard` for the two given players.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the board does not fit into the underlying bitsets or if both
+    /// players have the same ID.
+    pub fn new(
+        nrows: BoardSizeT,
+        ncolumns: BoardSizeT,
+        players: [PlayerID; 2],
+    ) -> anyhow::Result<Self> {
+        let nbits = usize::from(nrows) * (usize::from(ncolumns) + 1);
+        if nbits > BitSet::BITS as usize {
+            anyhow::bail!(
+                "Board of size {nrows}x{ncolumns} needs {nbits} bits, but only {} are available!",
+                BitSet::BITS
+            );
+        }
+        if players[0] == players[1] {
+            anyhow::bail!("Players must have different IDs!");
+        }
+        Ok(Self {
+            ncolumns,
+            nrows,
+            players,
+            stones: [0, 0],
+        })
+    }
+
+    /// Converts a `GameState` into a `BitBoard`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the board is too big or if it contains stones of players not
+    /// listed in `players`.
+    pub fn from_game_state(
+        board: &GameState,
+        players: [PlayerID; 2],
+    ) -> anyhow::Result<Self> {
+        let mut bitboard = Self::new(
+            board.get_number_of_rows(),
+            board.get_number_of_columns(),
+            players,
+        )?;
+        for (pp, cell) in board.iter_2d() {
+            if cell.is_free() {
+                continue;
+            }
+            let Some(player) = players.iter().find(|&&id| *cell == Some(id).into())
+            else {
+                anyhow::bail!("Cell {pp} is taken by an unknown player: {cell}");
+            };
+            bitboard.place(pp, *player);
+        }
+        Ok(bitboard)
+    }
+
+    #[must_use]
+    pub fn to_game_state(&self) -> GameState {
+        let mut board = GameState::new(self.nrows, self.ncolumns, None);
+        for row in 0..self.nrows {
+            for column in 0..self.ncolumns {
+                let pp = PointPlacement { row, column };
+                board[pp] = self.get(pp);
+            }
+        }
+        board
+    }
+
+    #[must_use]
+    pub fn get_number_of_rows(&self) -> BoardSizeT {
+        self.nrows
+    }
+
+    #[must_use]
+    pub fn get_number_of_columns(&self) -> BoardSizeT {
+        self.ncolumns
+    }
+
+    #[must_use]
+    pub fn get_players(&self) -> [PlayerID; 2] {
+        self.players
+    }
+
+    #[must_use]
+    pub fn get(&self, pp: PointPlacement) -> BoardStateEntry {
+        let mask = self.get_cell_mask(pp);
+        self.players
+            .iter()
+            .zip(self.stones)
+            .find(|(_, stones)| stones & mask != 0)
+            .map(|(player, _)| *player)
+            .into()
+    }
+
+    #[must_use]
+    pub fn is_free(&self, pp: PointPlacement) -> bool {
+        (self.stones[0] | self.stones[1]) & self.get_cell_mask(pp) == 0
+    }
+
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        (self.stones[0] | self.stones[1]) == self.get_cells_mask()
+    }
+
+    /// Puts a stone of `player` on the cell `pp`, replacing what was there before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `player` is not one of the board's players.
+    pub fn place(&mut self, pp: PointPlacement, player: PlayerID) {
+        let mask = self.get_cell_mask(pp);
+        let index = self.get_player_index(player);
+        self.stones[index] |= mask;
+        self.stones[1 - index] &= !mask;
+    }
+
+    pub fn remove(&mut self, pp: PointPlacement) {
+        let mask = !self.get_cell_mask(pp);
+        self.stones[0] &= mask;
+        self.stones[1] &= mask;
+    }
+
+    pub fn iter_free_cells(&self) -> impl Iterator<Item = PointPlacement> + use<> {
+        let board = *self;
+        (0..self.nrows).flat_map(move |row| {
+            (0..board.ncolumns)
+                .map(move |column| PointPlacement { row, column })
+                .filter(move |&pp| board.is_free(pp))
+        })
+    }
+
+    /// Checks whether `player` has at least `winning_length` stones in a row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `player` is not one of the board's players.
+    #[must_use]
+    pub fn has_line(&self, player: PlayerID, winning_length: WinLengthT) -> bool {
+        let stones = self.stones[self.get_player_index(player)];
+        let row_shift = u32::from(self.ncolumns) + 1;
+        // horizontal, vertical, slash diagonal, backslash diagonal
+        let shifts = [1, row_shift, row_shift + 1, row_shift - 1];
+        shifts.into_iter().any(|shift| {
+            let mut run = stones;
+            for _ in 1..winning_length {
+                // Shifts beyond the bitset only occur on single rows, which have no
+                // vertical or diagonal lines.
+                run &= run.checked_shr(shift).unwrap_or(0);
+                if run == 0 {
+                    return false;
+                }
+            }
+            run != 0
+        })
+    }
+
+    fn get_player_index(&self, player: PlayerID) -> usize {
+        self.players
+            .iter()
+            .position(|&id| id == player)
+            .unwrap_or_else(|| panic!("Player {player} does not play on this board!"))
+    }
+
+    fn get_cells_mask(&self) -> BitSet {
+        let row_shift = u32::from(self.ncolumns) + 1;
+        let row_mask: BitSet = (1 << self.ncolumns) - 1;
+        (0..u32::from(self.nrows))
+            .fold(0, |mask, row| mask | row_mask << (row * row_shift))
+    }
+
+    fn get_cell_mask(&self, pp: PointPlacement) -> BitSet {
+        debug_assert!(pp.row < self.nrows && pp.column < self.ncolumns);
+        let index =
+            u32::from(pp.row) * (u32::from(self.ncolumns) + 1) + u32::from(pp.column);
+        1 << index
+    }
+}
+
+impl From<&BitBoard> for GameState {
+    fn from(value: &BitBoard) -> Self {
+        value.to_game_state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(3, 3)]
+    #[case(7, 7)]
+    #[case(10, 11)]
+    #[case(1, 127)]
+    fn test_roundtrip_conversion(#[case] nrows: u16, #[case] ncolumns: u16) {
+        let mut board = GameState::new(nrows, ncolumns, None);
+        for (n, (pp, _)) in GameState::new(nrows, ncolumns, None).iter_2d().enumerate()
+        {
+            match n % 3 {
+                0 => board[pp] = Some(4).into(),
+                1 => board[pp] = Some(2).into(),
+                _ => {}
+            }
+        }
+        let bitboard = BitBoard::from_game_state(&board, [2, 4]).unwrap();
+        assert_eq!(bitboard.to_game_state(), board);
+    }
+
+    #[rstest]
+    #[case(11, 11)]
+    #[case(1, 128)]
+    fn test_too_big_boards_are_rejected(#[case] nrows: u16, #[case] ncolumns: u16) {
+        assert!(BitBoard::new(nrows, ncolumns, [0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_players_are_rejected() {
+        let board = GameState::new_with_values([[Some(0), Some(2)]]).unwrap();
+        assert!(BitBoard::from_game_state(&board, [0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_place_and_remove() {
+        let mut bitboard = BitBoard::new(3, 4, [0, 1]).unwrap();
+        let pp = PointPlacement { row: 2, column: 3 };
+        assert!(bitboard.is_free(pp));
+        bitboard.place(pp, 1);
+        assert_eq!(bitboard.get(pp), Some(1).into());
+        assert_eq!(bitboard.iter_free_cells().count(), 11);
+        bitboard.remove(pp);
+        assert!(bitboard.is_free(pp));
+        assert_eq!(bitboard.iter_free_cells().count(), 12);
+    }
+
+    #[rstest]
+    // lines must not wrap around from one row into the next
+    #[case([[None, None, Some(0)], [Some(0), None, None], [None, None, None]], false)]
+    #[case([[None, None, None], [None, None, Some(0)], [Some(0), None, None]], false)]
+    #[case([[None, Some(0), None], [Some(0), None, None], [None, None, None]], true)]
+    #[case([[Some(0), None, None], [Some(0), None, None], [None, None, None]], true)]
+    #[case([[None, None, None], [None, None, Some(0)], [None, None, Some(0)]], true)]
+    fn test_has_line(
+        #[case] values: [[Option<PlayerID>; 3]; 3],
+        #[case] expected: bool,
+    ) {
+        let board = GameState::new_with_values(values).unwrap();
+        let bitboard = BitBoard::from_game_state(&board, [0, 1]).unwrap();
+        assert_eq!(bitboard.has_line(0, 2), expected);
+    }
+
+    #[rstest]
+    #[case(&[120, 121, 122, 123], true)]
+    #[case(&[0, 1, 2, 124, 125, 126], false)]
+    fn test_has_line_on_widest_row(#[case] columns: &[u16], #[case] expected: bool) {
+        let mut bitboard = BitBoard::new(1, 127, [0, 1]).unwrap();
+        for &column in columns {
+            bitboard.place(PointPlacement { row: 0, column }, 0);
+        }
+        assert_eq!(bitboard.has_line(0, 4), expected);
+    }
+}
@@ -7,7 +7,7 @@ use std::{
     ops::Index,
 };
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Board<T> {
     nrows: u16,
     ncolumns: u16,
@@ -55,11 +55,13 @@ impl<T: std::marker::Copy> Board<T> {
     /// # Examples
     ///
     /// ```rust
+    /// # use tic_tac_toe_mcts::board::Board;
     /// let values = vec![
     ///     vec![1, 2],
     ///     vec![3, 4],
     /// ];
     /// let board = Board::<i32>::new_with_values(values)?;
+    /// # Ok::<(), anyhow::Error>(())
     /// ```
     #[allow(dead_code)]
     pub fn new_with_values<Matrix, Row, U>(values: Matrix) -> anyhow::Result<Self>
@@ -115,8 +117,10 @@ impl<T: std::marker::Copy> Board<T> {
     /// # Examples
     ///
     /// ```rust
+    /// # use tic_tac_toe_mcts::board::Board;
     /// let board = vec![1, 2, 3, 4];
     /// let board = Board::<i32>::new_with_board(2, 2, board)?;
+    /// # Ok::<(), anyhow::Error>(())
     /// ```
     #[allow(dead_code)]
     pub fn new_with_board(
@@ -220,6 +224,114 @@ impl<T: std::marker::Copy> Board<T> {
     }
 }
 
+/// Rotation or reflection of a board.
+///
+/// Rotations are clockwise. Only the first four symmetries keep the shape of
+/// rectangular boards, the others swap rows and columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate180,
+    /// Mirror at the horizontal axis, i.e. reverse the order of the rows.
+    FlipRows,
+    /// Mirror at the vertical axis, i.e. reverse the order of the columns.
+    FlipColumns,
+    Rotate90,
+    Rotate270,
+    /// Mirror at the main diagonal.
+    Transpose,
+    /// Mirror at the anti-diagonal.
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate180,
+        Symmetry::FlipRows,
+        Symmetry::FlipColumns,
+        Symmetry::Rotate90,
+        Symmetry::Rotate270,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// Returns the symmetries mapping a board of the given shape onto itself.
+    #[must_use]
+    pub fn for_shape(nrows: BoardSizeT, ncolumns: BoardSizeT) -> &'static [Symmetry] {
+        if nrows == ncolumns {
+            &Self::ALL
+        } else {
+            &Self::ALL[..4]
+        }
+    }
+
+    /// Returns the symmetry undoing this one.
+    #[must_use]
+    pub fn inverse(self) -> Self {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            _ => self,
+        }
+    }
+
+    /// Returns where `pp` of a board with the given shape ends up after transforming.
+    #[must_use]
+    pub fn apply(
+        self,
+        pp: PointPlacement,
+        nrows: BoardSizeT,
+        ncolumns: BoardSizeT,
+    ) -> PointPlacement {
+        let PointPlacement { row, column } = pp;
+        let last_row = nrows - 1;
+        let last_column = ncolumns - 1;
+        let (row, column) = match self {
+            Symmetry::Identity => (row, column),
+            Symmetry::Rotate180 => (last_row - row, last_column - column),
+            Symmetry::FlipRows => (last_row - row, column),
+            Symmetry::FlipColumns => (row, last_column - column),
+            Symmetry::Rotate90 => (column, last_row - row),
+            Symmetry::Rotate270 => (last_column - column, row),
+            Symmetry::Transpose => (column, row),
+            Symmetry::AntiTranspose => (last_column - column, last_row - row),
+        };
+        PointPlacement { row, column }
+    }
+
+    fn swaps_dimensions(self) -> bool {
+        matches!(
+            self,
+            Symmetry::Rotate90
+                | Symmetry::Rotate270
+                | Symmetry::Transpose
+                | Symmetry::AntiTranspose
+        )
+    }
+}
+
+impl<T: std::marker::Copy> Board<T> {
+    /// Returns a rotated or reflected copy of the board.
+    #[must_use]
+    pub fn transform(&self, symmetry: Symmetry) -> Self {
+        let (nrows, ncolumns) = if symmetry.swaps_dimensions() {
+            (self.ncolumns, self.nrows)
+        } else {
+            (self.nrows, self.ncolumns)
+        };
+        let mut transformed = Board {
+            nrows,
+            ncolumns,
+            board: self.board.clone(),
+        };
+        for (pp, val) in self.iter_2d() {
+            transformed[symmetry.apply(pp, self.nrows, self.ncolumns)] = *val;
+        }
+        transformed
+    }
+}
+
 impl<T: std::marker::Copy> Index<PointPlacement> for Board<T> {
     type Output = T;
 
@@ -382,4 +494,46 @@ mod tests {
         }
         assert_eq!(result.next(), None);
     }
+
+    #[test]
+    fn test_transform_moves_values() {
+        let board = Board::<i32>::new_with_values([[1, 2, 3], [4, 5, 6]]).unwrap();
+        let expected = [
+            (Symmetry::Identity, vec![vec![1, 2, 3], vec![4, 5, 6]]),
+            (Symmetry::Rotate180, vec![vec![6, 5, 4], vec![3, 2, 1]]),
+            (Symmetry::FlipRows, vec![vec![4, 5, 6], vec![1, 2, 3]]),
+            (Symmetry::FlipColumns, vec![vec![3, 2, 1], vec![6, 5, 4]]),
+            (Symmetry::Rotate90, vec![vec![4, 1], vec![5, 2], vec![6, 3]]),
+            (
+                Symmetry::Rotate270,
+                vec![vec![3, 6], vec![2, 5], vec![1, 4]],
+            ),
+            (
+                Symmetry::Transpose,
+                vec![vec![1, 4], vec![2, 5], vec![3, 6]],
+            ),
+            (
+                Symmetry::AntiTranspose,
+                vec![vec![6, 3], vec![5, 2], vec![4, 1]],
+            ),
+        ];
+        for (symmetry, values) in expected {
+            let expected = Board::<i32>::new_with_values(values).unwrap();
+            assert_eq!(board.transform(symmetry), expected, "{symmetry:?}");
+        }
+    }
+
+    #[rstest]
+    #[case(4, 4)]
+    #[case(3, 5)]
+    fn test_inverse_undoes_transform(#[case] nrows: u16, #[case] ncolumns: u16) {
+        let mut board = Board::<i32>::new(nrows, ncolumns, 0);
+        for (index, pp) in board.clone().iter_2d().map(|(pp, _)| pp).enumerate() {
+            board[pp] = i32::try_from(index).unwrap();
+        }
+        for symmetry in Symmetry::ALL {
+            let transformed = board.transform(symmetry);
+            assert_eq!(transformed.transform(symmetry.inverse()), board);
+        }
+    }
 }
@@ -0,0 +1,380 @@
+use crate::interfaces::{
+    GameResult, GameState, Player, PlayerID, PointPlacement, TicTacToeArena,
+    TicTacToeReferee,
+};
+use crate::layout::parse_mask;
+use anyhow::Context;
+use rand::Rng;
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Weighted moves for the positions of the opening, see `BookPlayer`.
+///
+/// Positions are told apart exactly, i.e. neither symmetries nor the player to move
+/// are taken into account. As text, every position takes one line, starting with its
+/// rows in the format of `parse_mask`, separated by `/`. The moves follow as
+/// `row,column:weight`, e.g.
+///
+/// ```text
+/// .../.0./... 0,0:2.5 0,1:1
+/// ```
+///
+/// Empty lines and lines starting with `;` are ignored, as `#` marks blocked cells.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpeningBook {
+    entries: HashMap<GameState, Vec<(PointPlacement, f32)>>,
+}
+
+impl OpeningBook {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of positions in the book.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds `weight` to the weight of `placement` on `board`.
+    pub fn add_move(
+        &mut self,
+        board: &GameState,
+        placement: PointPlacement,
+        weight: f32,
+    ) {
+        let moves = self.entries.entry(board.clone()).or_default();
+        match moves.iter_mut().find(|(pp, _)| *pp == placement) {
+            Some((_, total)) => *total += weight,
+            None => moves.push((placement, weight)),
+        }
+    }
+
+    /// Returns the weighted moves of `board`, if it is in the book.
+    #[must_use]
+    pub fn get_moves(&self, board: &GameState) -> Option<&[(PointPlacement, f32)]> {
+        self.entries.get(board).map(Vec::as_slice)
+    }
+
+    /// Picks one of the moves of `board` contained in `legal_moves`, with a chance
+    /// proportional to its weight.
+    ///
+    /// Returns `None` if the book has no such move of positive weight.
+    pub fn sample_move<R: Rng + ?Sized>(
+        &self,
+        board: &GameState,
+        legal_moves: &[PointPlacement],
+        rng: &mut R,
+    ) -> Option<PointPlacement> {
+        let moves: Vec<_> = self
+            .get_moves(board)?
+            .iter()
+            .filter(|(pp, weight)| *weight > 0.0 && legal_moves.contains(pp))
+            .collect();
+        let distribution =
+            WeightedIndex::new(moves.iter().map(|(_, weight)| weight)).ok()?;
+        Some(moves[distribution.sample(rng)].0)
+    }
+
+    /// Records the moves `players` choose in all positions up to `nplies` moves after
+    /// `board`.
+    ///
+    /// `players` take turns in the given order, starting with the first one. On every
+    /// position, the `width` moves of highest weight are recorded with their weights
+    /// and followed further.
+    pub fn add_searches(
+        &mut self,
+        board: &GameState,
+        players: &mut [&mut dyn Player],
+        referee: &dyn TicTacToeReferee,
+        nplies: u32,
+        width: usize,
+    ) {
+        self.add_searches_from(board, players, 0, referee, nplies, width);
+    }
+
+    fn add_searches_from(
+        &mut self,
+        board: &GameState,
+        players: &mut [&mut dyn Player],
+        active_player: usize,
+        referee: &dyn TicTacToeReferee,
+        nplies: u32,
+        width: usize,
+    ) {
+        if nplies == 0 {
+            return;
+        }
+        let player = &mut players[active_player % players.len()];
+        let player_id = player.get_id();
+        let placements = player.do_move(board);
+        let mut candidates: Vec<_> = referee
+            .get_legal_moves(board)
+            .into_iter()
+            .map(|pp| (pp, placements[pp]))
+            .filter(|&(_, weight)| weight > 0.0)
+            .collect();
+        candidates.sort_by(|(_, lhs), (_, rhs)| rhs.total_cmp(lhs));
+        candidates.truncate(width);
+        for (pp, weight) in candidates {
+            self.add_move(board, pp, weight);
+            let mut next_board = board.clone();
+            if referee.receive_move(&mut next_board, pp, player_id)
+                != GameResult::Undecided
+            {
+                continue;
+            }
+            let next_player = if referee.is_turn_over(&next_board) {
+                active_player + 1
+            } else {
+                active_player
+            };
+            self.add_searches_from(
+                &next_board,
+                players,
+                next_player,
+                referee,
+                nplies - 1,
+                width,
+            );
+        }
+    }
+
+    /// Plays the game of `arena` to its end and records its first `nplies` moves.
+    ///
+    /// Each move is weighted by the points its player scored in the game, i.e. 1 for
+    /// a win, 0.5 for a draw and 0 for a loss. Returns the final result together with
+    /// the player that caused it.
+    pub fn add_game(
+        &mut self,
+        arena: &mut dyn TicTacToeArena,
+        nplies: u32,
+    ) -> (GameResult, PlayerID) {
+        let mut moves = Vec::new();
+        loop {
+            let board = arena.get_board();
+            let (result, player_id, maybe_pp) = arena.do_next_move();
+            if let Some(pp) = maybe_pp
+                && moves.len() < nplies as usize
+            {
+                moves.push((board, pp, player_id));
+            }
+            if result == GameResult::Undecided {
+                continue;
+            }
+            for (board, pp, mover) in moves {
+                let points = match result {
+                    GameResult::Victory => f32::from(mover == player_id),
+                    GameResult::Defeat | GameResult::IllegalMove => {
+                        f32::from(mover != player_id)
+                    }
+                    GameResult::Draw | GameResult::Undecided => 0.5,
+                };
+                self.add_move(&board, pp, points);
+            }
+            return (result, player_id);
+        }
+    }
+
+    /// Parses a book from its text format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line is not in the format described at `OpeningBook`.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut book = Self::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            Self::parse_line(&mut book, line)
+                .with_context(|| format!("Invalid entry in line {}!", idx + 1))?;
+        }
+        Ok(book)
+    }
+
+    fn parse_line(&mut self, line: &str) -> anyhow::Result<()> {
+        let mut tokens = line.split_whitespace();
+        let mask = tokens.next().context("Position is missing!")?;
+        let board = parse_mask(&mask.replace('/', "\n"))?;
+        for token in tokens {
+            let parse = || {
+                let (pp, weight) = token.split_once(':')?;
+                let (row, column) = pp.split_once(',')?;
+                let pp = PointPlacement {
+                    row: row.parse().ok()?,
+                    column: column.parse().ok()?,
+                };
+                Some((pp, weight.parse::<f32>().ok()?))
+            };
+            let (pp, weight) =
+                parse().with_context(|| format!("Invalid move '{token}'!"))?;
+            if pp.row >= board.get_number_of_rows()
+                || pp.column >= board.get_number_of_columns()
+                || board[pp].is_taken()
+            {
+                anyhow::bail!("Move '{token}' is not on a free cell!");
+            }
+            if !weight.is_finite() || weight < 0.0 {
+                anyhow::bail!("Weight of move '{token}' must not be negative!");
+            }
+            self.add_move(&board, pp, weight);
+        }
+        Ok(())
+    }
+
+    /// Reads a book from `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is no book.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Could not read book '{}'!", path.display()))?;
+        Self::parse(&text)
+            .with_context(|| format!("Could not parse book '{}'!", path.display()))
+    }
+
+    /// Writes the book to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, self.to_string())
+            .with_context(|| format!("Could not write book '{}'!", path.display()))
+    }
+}
+
+impl fmt::Display for OpeningBook {
+    /// Writes the book in its text format, ordered by position.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(board, moves)| {
+                (board.to_string().trim_end().replace('\n', "/"), moves)
+            })
+            .collect();
+        entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        for (position, moves) in entries {
+            write!(f, "{position}")?;
+            for (pp, weight) in moves {
+                write!(f, " {},{}:{weight}", pp.row, pp.column)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::exploiting::ExploitingArena;
+    use crate::player::onelookahead::OneLookaheadPlayer;
+    use crate::referee::NaiveReferee;
+    use rand::rng;
+
+    #[test]
+    fn book_survives_a_round_trip() {
+        let text = "; comment\n\n.../.0./... 0,0:2.5 0,1:1\n0../1../... 2,2:1\n";
+        let book = OpeningBook::parse(text).unwrap();
+        assert_eq!(book.len(), 2);
+        let board = parse_mask("...\n.0.\n...").unwrap();
+        assert_eq!(
+            book.get_moves(&board),
+            Some(
+                &[
+                    (PointPlacement { row: 0, column: 0 }, 2.5),
+                    (PointPlacement { row: 0, column: 1 }, 1.0)
+                ][..]
+            )
+        );
+        assert_eq!(OpeningBook::parse(&book.to_string()).unwrap(), book);
+    }
+
+    #[rstest::rstest]
+    #[case(".x./... 0,0:1")]
+    #[case(".../... 0,0")]
+    #[case(".../... 0,0:x")]
+    #[case(".../... 0,0:-1")]
+    #[case(".../... 2,0:1")]
+    #[case("0../... 0,0:1")]
+    fn invalid_books_are_rejected(#[case] text: &str) {
+        assert!(OpeningBook::parse(text).is_err());
+    }
+
+    #[test]
+    fn only_legal_moves_of_positive_weight_are_sampled() {
+        let mut book = OpeningBook::new();
+        let board = GameState::new(2, 2, None);
+        let legal_move = PointPlacement { row: 1, column: 1 };
+        book.add_move(&board, PointPlacement { row: 0, column: 0 }, 5.0);
+        book.add_move(&board, PointPlacement { row: 0, column: 1 }, 0.0);
+        book.add_move(&board, legal_move, 1.0);
+        let legal_moves = [PointPlacement { row: 0, column: 1 }, legal_move];
+        for _ in 0..10 {
+            assert_eq!(
+                book.sample_move(&board, &legal_moves, &mut rng()),
+                Some(legal_move)
+            );
+        }
+        assert_eq!(
+            book.sample_move(&board, &legal_moves[..1], &mut rng()),
+            None
+        );
+        let other_board = GameState::new(2, 3, None);
+        assert_eq!(
+            book.sample_move(&other_board, &legal_moves, &mut rng()),
+            None
+        );
+    }
+
+    #[test]
+    fn searches_follow_the_best_moves() {
+        let referee = NaiveReferee::new(3);
+        let mut player0 = OneLookaheadPlayer::new(1, Box::new(referee.clone()), 0);
+        let mut player1 = OneLookaheadPlayer::new(0, Box::new(referee.clone()), 1);
+        let board = parse_mask("00.\n11.\n...").unwrap();
+        let mut book = OpeningBook::new();
+        book.add_searches(&board, &mut [&mut player0, &mut player1], &referee, 3, 2);
+        // The winning move ends the game, so nothing else is recorded.
+        assert_eq!(book.len(), 1);
+        assert_eq!(
+            book.get_moves(&board),
+            Some(&[(PointPlacement { row: 0, column: 2 }, 1.0)][..])
+        );
+    }
+
+    #[test]
+    fn moves_of_games_are_weighted_by_points() {
+        let mut referee = NaiveReferee::new(3);
+        let mut player0 = OneLookaheadPlayer::new(1, Box::new(referee.clone()), 0);
+        let mut player1 = OneLookaheadPlayer::new(0, Box::new(referee.clone()), 1);
+        let board = parse_mask("0..\n...\n1..").unwrap();
+        let mut arena = ExploitingArena::new(
+            0,
+            board.clone(),
+            vec![&mut player0, &mut player1],
+            &mut referee,
+        );
+        let mut book = OpeningBook::new();
+        let (result, _) = book.add_game(&mut arena, 1);
+        assert_ne!(result, GameResult::Undecided);
+        let moves = book.get_moves(&board).unwrap();
+        assert_eq!(moves.len(), 1);
+        assert!(moves[0].1 >= 0.0 && moves[0].1 <= 1.0);
+    }
+}
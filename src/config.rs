@@ -0,0 +1,1220 @@
+use crate::arena::exploiting::ExploitingArena;
+use crate::arena::exploring::ExploringTicTacToeArena;
+use crate::bitboard::BitBoard;
+use crate::database::Database;
+use crate::game_state_storage::{NaiveGameStateStorage, ZobristGameStateStorage};
+use crate::interfaces::{
+    GameState, Player, PlayerID, TicTacToeArena, TicTacToeReferee, WinLengthT,
+};
+use crate::layout::Shape;
+use crate::player::alphabeta::AlphaBetaPlayer;
+use crate::player::bitboardminmax::BitBoardMinMaxPlayer;
+use crate::player::cli::CLIPlayer;
+use crate::player::countboundmcts::CountBoundMCTSPlayer;
+use crate::player::guessing::GuessingPlayer;
+use crate::player::maxn::MaxNPlayer;
+use crate::player::minmax::MinMaxPlayer;
+use crate::player::onelookahead::OneLookaheadPlayer;
+use crate::player::oracle::OraclePlayer;
+use crate::player::parallel_uct::{ParallelUCTPlayer, Parallelisation};
+use crate::player::perfect::PerfectPlayer;
+use crate::player::timeboundmcts::TimeBoundMCTSPlayer;
+use crate::player::timeboundminmax::TimeBoundMinMaxPlayer;
+use crate::player::uct::{DEFAULT_EXPLORATION_CONSTANT, UCTPlayer};
+use crate::referee::{
+    BitBoardReferee, ConnectReferee, FasterRefereeV1, GomokuReferee, GravityReferee,
+    Grid, HexReferee, IncrementalReferee, MisereReferee, NaiveReferee, NdReferee,
+    RuleSet, Topology, TurnSchedule, UltimateReferee,
+};
+use anyhow::Context;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Rules of the game, which can be combined with any referee implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // The flags toggle independent variants.
+pub struct RuleSpec {
+    /// Whether players win by connecting their pair of opposite edges, as in Hex,
+    /// instead of completing a line.
+    pub connect_edges: bool,
+    /// Number of dimensions of the board, which is embedded into a two-dimensional one
+    /// if there are more, see `get_embedding_size`.
+    pub dimensions: u32,
+    /// Player making the first move, which is restricted by the renju rules and
+    /// connects the top and bottom edge in Hex.
+    pub first_player: PlayerID,
+    /// Whether stones fall down to the lowest free cell of their column.
+    pub gravity: bool,
+    pub grid: Grid,
+    /// Whether completing a line loses instead of winning.
+    pub misere: bool,
+    pub rule_set: RuleSet,
+    pub topology: Topology,
+    pub turn_schedule: TurnSchedule,
+    /// Whether ultimate tic-tac-toe is played on a board of 3×3 small boards, see
+    /// `UltimateState`. The winning length is ignored then.
+    pub ultimate: bool,
+    pub winning_length: WinLengthT,
+}
+
+impl RuleSpec {
+    /// Returns the rules of plain k-in-a-row on a bounded board.
+    #[must_use]
+    pub fn new(winning_length: WinLengthT) -> Self {
+        Self {
+            connect_edges: false,
+            dimensions: 2,
+            first_player: 0,
+            gravity: false,
+            grid: Grid::Square,
+            misere: false,
+            rule_set: RuleSet::Freestyle,
+            topology: Topology::Bounded,
+            turn_schedule: TurnSchedule::default(),
+            ultimate: false,
+            winning_length,
+        }
+    }
+
+    /// Returns whether the rules are those of plain k-in-a-row on a bounded square
+    /// grid, with one stone per turn.
+    #[must_use]
+    pub fn is_plain(&self) -> bool {
+        !self.connect_edges
+            && self.dimensions == 2
+            && !self.gravity
+            && self.grid == Grid::Square
+            && !self.misere
+            && self.rule_set == RuleSet::Freestyle
+            && self.topology == Topology::Bounded
+            && self.turn_schedule == TurnSchedule::default()
+            && !self.ultimate
+    }
+
+    /// Returns whether the positions of the game can be stored in a `Database`.
+    ///
+    /// This requires turns of a single stone and stones telling nothing but their
+    /// player, unlike those of ultimate tic-tac-toe.
+    #[must_use]
+    pub fn fits_database(&self) -> bool {
+        self.turn_schedule == TurnSchedule::default() && !self.ultimate
+    }
+
+    /// Checks whether the rules can be combined.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if
+    /// - a rule set other than freestyle is played on a torus or a hex grid,
+    /// - edges are to be connected on anything but a bounded hex grid,
+    /// - a board of other than two dimensions is combined with anything but plain
+    ///   lines, misère and gravity in three dimensions,
+    /// - ultimate tic-tac-toe is combined with anything but misère or
+    /// - a turn consists of no stones.
+    pub fn check(&self) -> anyhow::Result<()> {
+        let TurnSchedule {
+            first_turn_stones,
+            stones_per_turn,
+        } = self.turn_schedule;
+        if first_turn_stones == 0 || stones_per_turn == 0 {
+            anyhow::bail!("Every turn must consist of at least one stone!");
+        }
+        if self.rule_set != RuleSet::Freestyle && self.topology != Topology::Bounded {
+            anyhow::bail!("Rule set '{}' cannot be played on a torus!", self.rule_set);
+        }
+        if self.rule_set != RuleSet::Freestyle && self.grid != Grid::Square {
+            anyhow::bail!(
+                "Rule set '{}' cannot be played on a hex grid!",
+                self.rule_set
+            );
+        }
+        if self.connect_edges
+            && (self.grid != Grid::Hex
+                || self.topology != Topology::Bounded
+                || self.rule_set != RuleSet::Freestyle)
+        {
+            anyhow::bail!(
+                "Edges can only be connected on a bounded hex grid with freestyle rules!"
+            );
+        }
+        if self.dimensions == 0 {
+            anyhow::bail!("A board needs at least one dimension!");
+        }
+        if self.dimensions != 2
+            && (self.connect_edges
+                || self.grid != Grid::Square
+                || self.rule_set != RuleSet::Freestyle
+                || self.topology != Topology::Bounded)
+        {
+            anyhow::bail!(
+                "Boards of {} dimensions only support lines on a bounded square grid \
+                 with freestyle rules!",
+                self.dimensions
+            );
+        }
+        if self.gravity && self.dimensions > 3 {
+            anyhow::bail!("Gravity is supported for up to three dimensions only!");
+        }
+        if self.ultimate
+            && (self.connect_edges
+                || self.dimensions != 2
+                || self.gravity
+                || self.grid != Grid::Square
+                || self.rule_set != RuleSet::Freestyle
+                || self.topology != Topology::Bounded
+                || self.turn_schedule != TurnSchedule::default())
+        {
+            anyhow::bail!("Ultimate tic-tac-toe can only be combined with misère!");
+        }
+        Ok(())
+    }
+}
+
+impl RuleSet {
+    pub const VARIANTS: [&'static str; 3] = ["freestyle", "renju", "standard"];
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleSet::Freestyle => write!(f, "freestyle"),
+            RuleSet::Renju => write!(f, "renju"),
+            RuleSet::Standard => write!(f, "standard"),
+        }
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "freestyle" => Ok(RuleSet::Freestyle),
+            "renju" => Ok(RuleSet::Renju),
+            "standard" => Ok(RuleSet::Standard),
+            _ => anyhow::bail!(
+                "Unknown rule set '{s}'. Choose one of: {}",
+                Self::VARIANTS.join(", ")
+            ),
+        }
+    }
+}
+
+impl Grid {
+    pub const VARIANTS: [&'static str; 2] = ["hex", "square"];
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Grid::Hex => write!(f, "hex"),
+            Grid::Square => write!(f, "square"),
+        }
+    }
+}
+
+impl FromStr for Grid {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Grid::Hex),
+            "square" => Ok(Grid::Square),
+            _ => anyhow::bail!(
+                "Unknown grid '{s}'. Choose one of: {}",
+                Self::VARIANTS.join(", ")
+            ),
+        }
+    }
+}
+
+impl Shape {
+    pub const VARIANTS: [&'static str; 3] = ["cross", "diamond", "rectangle"];
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shape::Cross => write!(f, "cross"),
+            Shape::Diamond => write!(f, "diamond"),
+            Shape::Rectangle => write!(f, "rectangle"),
+        }
+    }
+}
+
+impl FromStr for Shape {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cross" => Ok(Shape::Cross),
+            "diamond" => Ok(Shape::Diamond),
+            "rectangle" => Ok(Shape::Rectangle),
+            _ => anyhow::bail!(
+                "Unknown shape '{s}'. Choose one of: {}",
+                Self::VARIANTS.join(", ")
+            ),
+        }
+    }
+}
+
+/// Referee implementations selectable at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefereeSpec {
+    FasterV1,
+    Incremental,
+    Naive,
+}
+
+impl RefereeSpec {
+    pub const VARIANTS: [&'static str; 3] = ["faster-v1", "incremental", "naive"];
+
+    /// Builds a referee judging by `rules`.
+    ///
+    /// Only `GomokuReferee` tells lines by their exact length, so it is used for all
+    /// rule sets but freestyle, regardless of the implementation selected. The same
+    /// holds for `UltimateReferee` in ultimate tic-tac-toe, for `HexReferee` if edges
+    /// are to be connected and for `NdReferee` if the board has other than two
+    /// dimensions.
+    #[must_use]
+    pub fn build(self, rules: RuleSpec) -> Box<dyn TicTacToeReferee> {
+        let RuleSpec {
+            grid,
+            topology,
+            winning_length,
+            ..
+        } = rules;
+        let mut referee: Box<dyn TicTacToeReferee> = match self {
+            _ if rules.ultimate => Box::new(UltimateReferee::new()),
+            _ if rules.connect_edges => Box::new(HexReferee::new(rules.first_player)),
+            _ if rules.dimensions != 2 => {
+                Box::new(NdReferee::new(rules.dimensions, winning_length))
+            }
+            _ if rules.rule_set != RuleSet::Freestyle => Box::new(GomokuReferee::new(
+                rules.first_player,
+                rules.rule_set,
+                winning_length,
+            )),
+            RefereeSpec::FasterV1 => Box::new(FasterRefereeV1::new_with_grid(
+                grid,
+                topology,
+                winning_length,
+            )),
+            RefereeSpec::Incremental => Box::new(IncrementalReferee::new_with_grid(
+                grid,
+                topology,
+                winning_length,
+            )),
+            RefereeSpec::Naive => {
+                Box::new(NaiveReferee::new_with_grid(grid, topology, winning_length))
+            }
+        };
+        if rules.misere {
+            referee = Box::new(MisereReferee::new(referee));
+        }
+        if rules.gravity {
+            referee = Box::new(GravityReferee::new(referee));
+        }
+        if rules.turn_schedule != TurnSchedule::default() {
+            referee = Box::new(ConnectReferee::new(referee, rules.turn_schedule));
+        }
+        referee
+    }
+}
+
+impl FromStr for RefereeSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "faster-v1" => Ok(RefereeSpec::FasterV1),
+            "incremental" => Ok(RefereeSpec::Incremental),
+            "naive" => Ok(RefereeSpec::Naive),
+            _ => anyhow::bail!(
+                "Unknown referee '{s}'. Choose one of: {}",
+                Self::VARIANTS.join(", ")
+            ),
+        }
+    }
+}
+
+/// Arena implementations selectable at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaSpec {
+    Exploiting,
+    Exploring,
+}
+
+impl ArenaSpec {
+    pub const VARIANTS: [&'static str; 2] = ["exploiting", "exploring"];
+
+    pub fn build<'arena>(
+        self,
+        board: GameState,
+        players: Vec<&'arena mut dyn Player>,
+        referee: &'arena mut dyn TicTacToeReferee,
+        rng: StdRng,
+        starting_player: PlayerID,
+    ) -> Box<dyn TicTacToeArena + 'arena> {
+        match self {
+            ArenaSpec::Exploiting => Box::new(ExploitingArena::new(
+                starting_player,
+                board,
+                players,
+                referee,
+            )),
+            ArenaSpec::Exploring => Box::new(ExploringTicTacToeArena::new_with_rng(
+                board,
+                players,
+                starting_player,
+                referee,
+                rng,
+            )),
+        }
+    }
+}
+
+impl FromStr for ArenaSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exploiting" => Ok(ArenaSpec::Exploiting),
+            "exploring" => Ok(ArenaSpec::Exploring),
+            _ => anyhow::bail!(
+                "Unknown arena '{s}'. Choose one of: {}",
+                Self::VARIANTS.join(", ")
+            ),
+        }
+    }
+}
+
+/// Description of a player, parsed from strings like `minmax:4` or `human`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerSpec {
+    AlphaBeta {
+        depth: u32,
+    },
+    BitBoardMinMax {
+        depth: u32,
+    },
+    CountBoundMCTS {
+        nsamples: u16,
+    },
+    Guessing,
+    Human,
+    MaxN {
+        depth: u32,
+    },
+    MinMax {
+        depth: u32,
+    },
+    OneLookahead,
+    Oracle,
+    ParallelUCT {
+        niterations: u32,
+        nworkers: u32,
+        parallelisation: Parallelisation,
+    },
+    Perfect {
+        nodes: usize,
+    },
+    TimeBoundMCTS {
+        milliseconds: u64,
+    },
+    TimeBoundMinMax {
+        milliseconds: u64,
+    },
+    UCT {
+        niterations: u32,
+    },
+}
+
+impl PlayerSpec {
+    pub const VARIANTS: [&'static str; 15] = [
+        "alphabeta:<depth>",
+        "bitboard-minmax:<depth>",
+        "guessing",
+        "human",
+        "maxn:<depth>",
+        "mcts:<samples>",
+        "minmax:<depth>",
+        "onelookahead",
+        "oracle",
+        "perfect:<nodes>",
+        "root-uct:<iterations>:<workers>",
+        "timed-mcts:<milliseconds>",
+        "timed-minmax:<milliseconds>",
+        "tree-uct:<iterations>:<workers>",
+        "uct:<iterations>",
+    ];
+
+    /// Returns whether the player can cope with more than one opponent.
+    ///
+    /// All other players assume that they take turns with a single opponent, e.g.
+    /// `OneLookaheadPlayer` only blocks the player moving after it.
+    #[must_use]
+    pub fn supports_multiple_opponents(self) -> bool {
+        matches!(
+            self,
+            PlayerSpec::Guessing | PlayerSpec::Human | PlayerSpec::MaxN { .. }
+        )
+    }
+
+    /// Returns whether the player can play a game with the given rules.
+    ///
+    /// `BitBoardMinMaxPlayer` brings its own referee, which only knows plain
+    /// k-in-a-row on a bounded board. `OraclePlayer` needs a `Database` of the game.
+    #[must_use]
+    pub fn supports_rules(self, rules: RuleSpec) -> bool {
+        match self {
+            PlayerSpec::BitBoardMinMax { .. } => rules.is_plain(),
+            PlayerSpec::Oracle => rules.fits_database(),
+            _ => true,
+        }
+    }
+
+    /// Returns whether the player can play on `board`.
+    ///
+    /// `BitBoardMinMaxPlayer` has no representation for blocked cells and only handles
+    /// boards that fit into a `BitBoard`. `OraclePlayer` needs a `Database` of the
+    /// board.
+    #[must_use]
+    pub fn supports_board(self, board: &GameState) -> bool {
+        match self {
+            PlayerSpec::BitBoardMinMax { .. } => {
+                board.iter_2d().all(|(_, cell)| !cell.is_blocked())
+                    && BitBoard::new(
+                        board.get_number_of_rows(),
+                        board.get_number_of_columns(),
+                        [0, 1],
+                    )
+                    .is_ok()
+            }
+            PlayerSpec::Oracle => Database::supports_board(board),
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for PlayerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayerSpec::AlphaBeta { depth } => write!(f, "alphabeta:{depth}"),
+            PlayerSpec::BitBoardMinMax { depth } => {
+                write!(f, "bitboard-minmax:{depth}")
+            }
+            PlayerSpec::CountBoundMCTS { nsamples } => write!(f, "mcts:{nsamples}"),
+            PlayerSpec::Guessing => write!(f, "guessing"),
+            PlayerSpec::Human => write!(f, "human"),
+            PlayerSpec::MaxN { depth } => write!(f, "maxn:{depth}"),
+            PlayerSpec::MinMax { depth } => write!(f, "minmax:{depth}"),
+            PlayerSpec::OneLookahead => write!(f, "onelookahead"),
+            PlayerSpec::Oracle => write!(f, "oracle"),
+            PlayerSpec::ParallelUCT {
+                niterations,
+                nworkers,
+                parallelisation,
+            } => {
+                let kind = match parallelisation {
+                    Parallelisation::Root => "root-uct",
+                    Parallelisation::Tree => "tree-uct",
+                };
+                write!(f, "{kind}:{niterations}:{nworkers}")
+            }
+            PlayerSpec::Perfect { nodes } => write!(f, "perfect:{nodes}"),
+            PlayerSpec::TimeBoundMCTS { milliseconds } => {
+                write!(f, "timed-mcts:{milliseconds}")
+            }
+            PlayerSpec::TimeBoundMinMax { milliseconds } => {
+                write!(f, "timed-minmax:{milliseconds}")
+            }
+            PlayerSpec::UCT { niterations } => write!(f, "uct:{niterations}"),
+        }
+    }
+}
+
+impl FromStr for PlayerSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, parameter) = match s.split_once(':') {
+            Some((kind, parameter)) => (kind, Some(parameter)),
+            None => (s, None),
+        };
+        let get_parameter = || {
+            parameter
+                .with_context(|| format!("Player type '{kind}' needs a parameter!"))
+        };
+        let spec = match kind {
+            "alphabeta" | "bitboard-minmax" | "maxn" | "minmax" => {
+                let depth = get_parameter()?
+                    .parse()
+                    .context("Search depth must be a positive number!")?;
+                if depth == 0 {
+                    anyhow::bail!("Search depth must be at least 1!");
+                }
+                match kind {
+                    "alphabeta" => PlayerSpec::AlphaBeta { depth },
+                    "maxn" => PlayerSpec::MaxN { depth },
+                    "minmax" => PlayerSpec::MinMax { depth },
+                    _ => PlayerSpec::BitBoardMinMax { depth },
+                }
+            }
+            "mcts" => PlayerSpec::CountBoundMCTS {
+                nsamples: get_parameter()?
+                    .parse()
+                    .context("Number of samples must fit into u16!")?,
+            },
+            "perfect" => PlayerSpec::Perfect {
+                nodes: get_parameter()?
+                    .parse()
+                    .context("Number of nodes must be a positive number!")?,
+            },
+            "root-uct" | "tree-uct" => {
+                let (niterations, nworkers) = get_parameter()?
+                    .split_once(':')
+                    .context("Iterations and workers must be given!")?;
+                let nworkers = nworkers
+                    .parse()
+                    .context("Number of workers must be a positive number!")?;
+                if nworkers == 0 {
+                    anyhow::bail!("Number of workers must be at least 1!");
+                }
+                PlayerSpec::ParallelUCT {
+                    niterations: niterations
+                        .parse()
+                        .context("Number of iterations must be a positive number!")?,
+                    nworkers,
+                    parallelisation: if kind == "root-uct" {
+                        Parallelisation::Root
+                    } else {
+                        Parallelisation::Tree
+                    },
+                }
+            }
+            "timed-mcts" => PlayerSpec::TimeBoundMCTS {
+                milliseconds: get_parameter()?
+                    .parse()
+                    .context("Time budget must be given in milliseconds!")?,
+            },
+            "timed-minmax" => PlayerSpec::TimeBoundMinMax {
+                milliseconds: get_parameter()?
+                    .parse()
+                    .context("Time budget must be given in milliseconds!")?,
+            },
+            "uct" => PlayerSpec::UCT {
+                niterations: get_parameter()?
+                    .parse()
+                    .context("Number of iterations must be a positive number!")?,
+            },
+            "guessing" | "human" | "onelookahead" | "oracle" => {
+                if parameter.is_some() {
+                    anyhow::bail!("Player type '{kind}' takes no parameter!");
+                }
+                match kind {
+                    "guessing" => PlayerSpec::Guessing,
+                    "human" => PlayerSpec::Human,
+                    "onelookahead" => PlayerSpec::OneLookahead,
+                    _ => PlayerSpec::Oracle,
+                }
+            }
+            _ => anyhow::bail!(
+                "Unknown player '{s}'. Choose one of: {}",
+                Self::VARIANTS.join(", ")
+            ),
+        };
+        Ok(spec)
+    }
+}
+
+/// Spreads the seeds of the players of a game, see `PlayerResources::new_with_seed`.
+const PLAYER_SEED_MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Owns everything a player built from a `PlayerSpec` borrows.
+///
+/// Most players borrow their referee and, in case of `CountBoundMCTSPlayer`, the
+/// players used for the rollouts. These have to outlive the player, so they are kept
+/// here.
+pub struct PlayerResources {
+    database: Option<Database>,
+    other_id: PlayerID,
+    player_ids: Vec<PlayerID>,
+    referee: Box<dyn TicTacToeReferee>,
+    referee_spec: RefereeSpec,
+    rollout_players: [OneLookaheadPlayer; 2],
+    rollout_referee: Box<dyn TicTacToeReferee>,
+    rng: StdRng,
+    rules: RuleSpec,
+    self_id: PlayerID,
+}
+
+impl PlayerResources {
+    /// Creates the resources for player `self_id` of a game between `player_ids`.
+    ///
+    /// The players are expected to move in the order given. Players supporting only
+    /// a single opponent consider the one moving after `self_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self_id` is not one of `player_ids`.
+    #[must_use]
+    pub fn new(
+        player_ids: &[PlayerID],
+        referee_spec: RefereeSpec,
+        rules: RuleSpec,
+        self_id: PlayerID,
+    ) -> Self {
+        Self::new_with_seed(player_ids, referee_spec, rules, rand::random(), self_id)
+    }
+
+    /// Creates resources whose players draw their random decisions from a generator
+    /// seeded with `seed`, see `new`.
+    ///
+    /// The seed is combined with `self_id`, so all players of a game can be given the
+    /// same seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self_id` is not one of `player_ids`.
+    #[must_use]
+    pub fn new_with_seed(
+        player_ids: &[PlayerID],
+        referee_spec: RefereeSpec,
+        rules: RuleSpec,
+        seed: u64,
+        self_id: PlayerID,
+    ) -> Self {
+        let self_index = player_ids
+            .iter()
+            .position(|&id| id == self_id)
+            .unwrap_or_else(|| panic!("Player ID {self_id} is not taking part!"));
+        let other_id = player_ids[(self_index + 1) % player_ids.len()];
+        Self {
+            database: None,
+            other_id,
+            player_ids: player_ids.to_vec(),
+            referee: referee_spec.build(rules),
+            referee_spec,
+            rollout_players: [
+                OneLookaheadPlayer::new(other_id, referee_spec.build(rules), self_id),
+                OneLookaheadPlayer::new(self_id, referee_spec.build(rules), other_id),
+            ],
+            rollout_referee: referee_spec.build(rules),
+            rng: StdRng::seed_from_u64(
+                seed ^ (u64::from(self_id) + 1).wrapping_mul(PLAYER_SEED_MULTIPLIER),
+            ),
+            rules,
+            self_id,
+        }
+    }
+
+    /// Lets oracle players look up their moves in `database` instead of generating
+    /// one.
+    pub fn set_database(&mut self, database: Database) {
+        self.database = Some(database);
+    }
+
+    /// Returns a new random number generator for a component of the player.
+    pub fn split_rng(&mut self) -> StdRng {
+        StdRng::from_rng(&mut self.rng)
+    }
+
+    fn build_parallel_uct_player(
+        &self,
+        niterations: u32,
+        nworkers: u32,
+        parallelisation: Parallelisation,
+        rng: StdRng,
+    ) -> ParallelUCTPlayer<'static> {
+        let referee_spec = self.referee_spec;
+        let rules = self.rules;
+        ParallelUCTPlayer::new_with_rng(
+            DEFAULT_EXPLORATION_CONSTANT,
+            niterations,
+            nworkers,
+            self.other_id,
+            parallelisation,
+            Box::new(move || referee_spec.build(rules)),
+            rng,
+            self.self_id,
+        )
+    }
+
+    fn build_oracle_player(&self) -> OraclePlayer<'_> {
+        match &self.database {
+            Some(database) => OraclePlayer::new_with_database(
+                database.clone(),
+                self.other_id,
+                &*self.referee,
+                self.self_id,
+            ),
+            None => OraclePlayer::new(self.other_id, &*self.referee, self.self_id),
+        }
+    }
+
+    pub fn build_player(&mut self, spec: PlayerSpec) -> Box<dyn Player + '_> {
+        let self_id = self.self_id;
+        let other_id = self.other_id;
+        let rng = self.split_rng();
+        match spec {
+            PlayerSpec::AlphaBeta { depth } => Box::new(AlphaBetaPlayer::new(
+                depth,
+                other_id,
+                &*self.referee,
+                self_id,
+            )),
+            PlayerSpec::BitBoardMinMax { depth } => {
+                Box::new(BitBoardMinMaxPlayer::new(
+                    depth,
+                    other_id,
+                    NaiveGameStateStorage::new(),
+                    BitBoardReferee::new(self.rules.winning_length),
+                    self_id,
+                ))
+            }
+            PlayerSpec::CountBoundMCTS { nsamples } => {
+                let [rollout_player0, rollout_player1] = &mut self.rollout_players;
+                Box::new(CountBoundMCTSPlayer::new_with_rng(
+                    self_id,
+                    nsamples,
+                    rollout_player0,
+                    rollout_player1,
+                    &mut *self.rollout_referee,
+                    rng,
+                ))
+            }
+            PlayerSpec::Guessing => Box::new(GuessingPlayer { id: self_id }),
+            PlayerSpec::Human => Box::new(CLIPlayer { id: self_id }),
+            PlayerSpec::MaxN { depth } => Box::new(MaxNPlayer::new(
+                depth,
+                self.player_ids.clone(),
+                &*self.referee,
+                self_id,
+            )),
+            PlayerSpec::MinMax { depth } => Box::new(MinMaxPlayer::new(
+                depth,
+                other_id,
+                ZobristGameStateStorage::new(false),
+                &*self.referee,
+                self_id,
+            )),
+            PlayerSpec::OneLookahead => Box::new(OneLookaheadPlayer::new(
+                other_id,
+                self.referee_spec.build(self.rules),
+                self_id,
+            )),
+            PlayerSpec::Oracle => Box::new(self.build_oracle_player()),
+            PlayerSpec::ParallelUCT {
+                niterations,
+                nworkers,
+                parallelisation,
+            } => Box::new(self.build_parallel_uct_player(
+                niterations,
+                nworkers,
+                parallelisation,
+                rng,
+            )),
+            PlayerSpec::Perfect { nodes } => {
+                Box::new(PerfectPlayer::new(nodes, other_id, &*self.referee, self_id))
+            }
+            PlayerSpec::TimeBoundMCTS { milliseconds } => {
+                let [rollout_player0, rollout_player1] = &mut self.rollout_players;
+                Box::new(TimeBoundMCTSPlayer::new_with_rng(
+                    self_id,
+                    Duration::from_millis(milliseconds),
+                    rollout_player0,
+                    rollout_player1,
+                    &mut *self.rollout_referee,
+                    rng,
+                ))
+            }
+            PlayerSpec::TimeBoundMinMax { milliseconds } => {
+                Box::new(TimeBoundMinMaxPlayer::new(
+                    other_id,
+                    ZobristGameStateStorage::new(false),
+                    &*self.referee,
+                    self_id,
+                    Duration::from_millis(milliseconds),
+                ))
+            }
+            PlayerSpec::UCT { niterations } => Box::new(UCTPlayer::new_with_rng(
+                DEFAULT_EXPLORATION_CONSTANT,
+                niterations,
+                other_id,
+                &*self.referee,
+                rng,
+                self_id,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::{GameResult, PointPlacement};
+    use crate::layout::parse_mask;
+    use rstest::*;
+
+    #[rstest]
+    #[case("alphabeta:5", PlayerSpec::AlphaBeta { depth: 5 })]
+    #[case("bitboard-minmax:3", PlayerSpec::BitBoardMinMax { depth: 3 })]
+    #[case("guessing", PlayerSpec::Guessing)]
+    #[case("human", PlayerSpec::Human)]
+    #[case("maxn:2", PlayerSpec::MaxN { depth: 2 })]
+    #[case("mcts:1000", PlayerSpec::CountBoundMCTS { nsamples: 1000 })]
+    #[case("minmax:4", PlayerSpec::MinMax { depth: 4 })]
+    #[case("onelookahead", PlayerSpec::OneLookahead)]
+    #[case("oracle", PlayerSpec::Oracle)]
+    #[case("perfect:100000", PlayerSpec::Perfect { nodes: 100_000 })]
+    #[case("timed-mcts:250", PlayerSpec::TimeBoundMCTS { milliseconds: 250 })]
+    #[case("timed-minmax:2000", PlayerSpec::TimeBoundMinMax { milliseconds: 2000 })]
+    #[case("uct:5000", PlayerSpec::UCT { niterations: 5000 })]
+    #[case(
+        "root-uct:5000:8",
+        PlayerSpec::ParallelUCT {
+            niterations: 5000,
+            nworkers: 8,
+            parallelisation: Parallelisation::Root,
+        }
+    )]
+    #[case(
+        "tree-uct:100:2",
+        PlayerSpec::ParallelUCT {
+            niterations: 100,
+            nworkers: 2,
+            parallelisation: Parallelisation::Tree,
+        }
+    )]
+    fn player_specs_are_parsed(#[case] input: &str, #[case] expected: PlayerSpec) {
+        let spec = PlayerSpec::from_str(input).unwrap();
+        assert_eq!(spec, expected);
+        assert_eq!(spec.to_string(), input);
+    }
+
+    #[rstest]
+    #[case("minmax")]
+    #[case("minmax:0")]
+    #[case("minmax:-1")]
+    #[case("alphabeta:0")]
+    #[case("mcts:100000")]
+    #[case("human:1")]
+    #[case("root-uct:100")]
+    #[case("tree-uct:100:0")]
+    #[case("alphazero")]
+    fn invalid_player_specs_are_rejected(#[case] input: &str) {
+        assert!(PlayerSpec::from_str(input).is_err());
+    }
+
+    #[test]
+    fn every_player_can_be_built() {
+        let specs = [
+            "alphabeta:1",
+            "bitboard-minmax:1",
+            "guessing",
+            "human",
+            "maxn:1",
+            "mcts:1",
+            "minmax:1",
+            "onelookahead",
+            "root-uct:1:2",
+            "timed-mcts:1",
+            "timed-minmax:1",
+            "tree-uct:1:2",
+            "uct:1",
+        ];
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap();
+            for (gravity, misere, topology) in [
+                (false, false, Topology::Bounded),
+                (true, false, Topology::Bounded),
+                (false, true, Topology::Bounded),
+                (false, false, Topology::Torus),
+            ] {
+                let rules = RuleSpec {
+                    gravity,
+                    misere,
+                    topology,
+                    ..RuleSpec::new(3)
+                };
+                let mut resources = PlayerResources::new(&[0, 1], referee, rules, 0);
+                for spec in specs {
+                    let player = resources.build_player(spec.parse().unwrap());
+                    assert_eq!(player.get_id(), 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lines_wrap_around_for_every_torus_referee() {
+        let rules = RuleSpec {
+            topology: Topology::Torus,
+            ..RuleSpec::new(3)
+        };
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board =
+                GameState::new_with_values([[Some(0), None, Some(0), None]]).unwrap();
+            let pp = PointPlacement { row: 0, column: 3 };
+            assert_eq!(referee.receive_move(&mut board, pp, 0), GameResult::Victory);
+        }
+    }
+
+    #[test]
+    fn lines_lose_for_every_misere_referee() {
+        let rules = RuleSpec {
+            misere: true,
+            ..RuleSpec::new(3)
+        };
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board =
+                GameState::new_with_values([[Some(0), Some(0), None]]).unwrap();
+            let pp = PointPlacement { row: 0, column: 2 };
+            assert_eq!(referee.receive_move(&mut board, pp, 0), GameResult::Defeat);
+        }
+    }
+
+    #[test]
+    fn overlines_do_not_win_with_standard_rules() {
+        let rules = RuleSpec {
+            rule_set: RuleSet::Standard,
+            ..RuleSpec::new(3)
+        };
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board =
+                GameState::new_with_values([[Some(0), Some(0), None, Some(0)]])
+                    .unwrap();
+            let pp = PointPlacement { row: 0, column: 2 };
+            assert_eq!(referee.receive_move(&mut board, pp, 0), GameResult::Draw);
+        }
+    }
+
+    #[test]
+    fn exact_rule_sets_are_rejected_on_a_torus() {
+        let rules = RuleSpec {
+            rule_set: RuleSet::Renju,
+            topology: Topology::Torus,
+            ..RuleSpec::new(5)
+        };
+        assert!(rules.check().is_err());
+        assert!(RuleSpec::new(5).check().is_ok());
+    }
+
+    #[test]
+    fn empty_turns_are_rejected() {
+        let rules = RuleSpec {
+            turn_schedule: TurnSchedule::new(1, 0),
+            ..RuleSpec::new(6)
+        };
+        assert!(rules.check().is_err());
+    }
+
+    #[test]
+    fn turns_of_several_stones_are_told_by_every_referee() {
+        let rules = RuleSpec {
+            turn_schedule: TurnSchedule::new(1, 2),
+            ..RuleSpec::new(3)
+        };
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board = GameState::new(3, 3, None);
+            let expected_turn_ends = [true, false, true, false, true];
+            for (idx, expected) in expected_turn_ends.into_iter().enumerate() {
+                let idx = u16::try_from(idx).unwrap();
+                let pp = PointPlacement {
+                    row: idx / 3,
+                    column: idx % 3,
+                };
+                referee.receive_move(&mut board, pp, idx % 2);
+                assert_eq!(referee.is_turn_over(&board), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn blocked_cells_break_lines_for_every_referee() {
+        for rule_set in [RuleSet::Freestyle, RuleSet::Standard] {
+            let rules = RuleSpec {
+                rule_set,
+                ..RuleSpec::new(3)
+            };
+            for referee in RefereeSpec::VARIANTS {
+                let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+                let mut board = parse_mask("00#0.\n.....").unwrap();
+                let pp = PointPlacement { row: 0, column: 4 };
+                assert_eq!(
+                    referee.receive_move(&mut board, pp, 0),
+                    GameResult::Undecided
+                );
+                let blocked = PointPlacement { row: 0, column: 2 };
+                assert_eq!(
+                    referee.receive_move(&mut board, blocked, 0),
+                    GameResult::IllegalMove
+                );
+                assert!(!referee.get_legal_moves(&board).contains(&blocked));
+            }
+        }
+    }
+
+    #[test]
+    fn lines_follow_the_hex_grid_for_every_referee() {
+        let rules = RuleSpec {
+            grid: Grid::Hex,
+            ..RuleSpec::new(3)
+        };
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board = parse_mask("0.0\n.0.\n...").unwrap();
+            let pp = PointPlacement { row: 2, column: 2 };
+            assert_eq!(
+                referee.receive_move(&mut board, pp, 0),
+                GameResult::Undecided
+            );
+            let pp = PointPlacement { row: 2, column: 0 };
+            assert_eq!(referee.receive_move(&mut board, pp, 0), GameResult::Victory);
+        }
+    }
+
+    #[test]
+    fn edges_are_connected_with_any_implementation() {
+        let rules = RuleSpec {
+            connect_edges: true,
+            first_player: 1,
+            grid: Grid::Hex,
+            ..RuleSpec::new(3)
+        };
+        assert!(rules.check().is_ok());
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board = parse_mask("1..\n1..\n...").unwrap();
+            let pp = PointPlacement { row: 2, column: 0 };
+            assert_eq!(referee.receive_move(&mut board, pp, 1), GameResult::Victory);
+        }
+    }
+
+    #[rstest]
+    #[case(Grid::Square, Topology::Bounded, RuleSet::Freestyle)]
+    #[case(Grid::Hex, Topology::Torus, RuleSet::Freestyle)]
+    #[case(Grid::Hex, Topology::Bounded, RuleSet::Renju)]
+    fn edges_are_connected_on_bounded_hex_grids_only(
+        #[case] grid: Grid,
+        #[case] topology: Topology,
+        #[case] rule_set: RuleSet,
+    ) {
+        let rules = RuleSpec {
+            connect_edges: true,
+            grid,
+            rule_set,
+            topology,
+            ..RuleSpec::new(5)
+        };
+        assert!(rules.check().is_err());
+    }
+
+    #[test]
+    fn lines_run_through_all_dimensions_with_any_implementation() {
+        let rules = RuleSpec {
+            dimensions: 3,
+            ..RuleSpec::new(3)
+        };
+        assert!(rules.check().is_ok());
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board = parse_mask("0...0....\n.........\n.........").unwrap();
+            let pp = PointPlacement { row: 0, column: 8 };
+            assert_eq!(referee.receive_move(&mut board, pp, 0), GameResult::Victory);
+        }
+    }
+
+    #[rstest]
+    #[case(RuleSpec { dimensions: 0, ..RuleSpec::new(3) })]
+    #[case(RuleSpec { dimensions: 3, grid: Grid::Hex, ..RuleSpec::new(3) })]
+    #[case(RuleSpec { dimensions: 3, topology: Topology::Torus, ..RuleSpec::new(3) })]
+    #[case(RuleSpec { dimensions: 4, gravity: true, ..RuleSpec::new(3) })]
+    fn unsupported_dimensions_are_rejected(#[case] rules: RuleSpec) {
+        assert!(rules.check().is_err());
+    }
+
+    #[rstest]
+    #[case(RuleSpec { gravity: true, ..RuleSpec::new(3) }, false)]
+    #[case(RuleSpec { grid: Grid::Hex, ..RuleSpec::new(3) }, false)]
+    #[case(RuleSpec { rule_set: RuleSet::Renju, ..RuleSpec::new(3) }, false)]
+    #[case(RuleSpec { misere: true, ..RuleSpec::new(3) }, true)]
+    fn ultimate_is_only_combined_with_misere(
+        #[case] rules: RuleSpec,
+        #[case] expected: bool,
+    ) {
+        let rules = RuleSpec {
+            ultimate: true,
+            ..rules
+        };
+        assert_eq!(rules.check().is_ok(), expected);
+    }
+
+    #[rstest]
+    #[case("..\n#.", false)]
+    #[case("...\n...", true)]
+    #[case(&"............\n".repeat(12), false)]
+    fn bitboard_players_need_a_fitting_board(
+        #[case] mask: &str,
+        #[case] expected: bool,
+    ) {
+        let board = parse_mask(mask).unwrap();
+        let spec = PlayerSpec::BitBoardMinMax { depth: 1 };
+        assert_eq!(spec.supports_board(&board), expected);
+        assert!(PlayerSpec::Guessing.supports_board(&board));
+    }
+
+    #[test]
+    fn oracles_need_a_board_fitting_a_database() {
+        assert!(PlayerSpec::Oracle.supports_board(&GameState::new(4, 4, None)));
+        assert!(!PlayerSpec::Oracle.supports_board(&GameState::new(7, 7, None)));
+    }
+
+    #[test]
+    fn ultimate_is_refereed_by_every_implementation() {
+        let rules = RuleSpec {
+            ultimate: true,
+            ..RuleSpec::new(5)
+        };
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board = GameState::new(9, 9, None);
+            let centre = PointPlacement { row: 4, column: 4 };
+            assert_eq!(
+                referee.receive_move(&mut board, centre, 0),
+                GameResult::Undecided
+            );
+            assert_eq!(referee.get_legal_moves(&board).len(), 8);
+        }
+    }
+
+    #[test]
+    fn gravity_is_enforced_by_every_referee() {
+        let rules = RuleSpec {
+            gravity: true,
+            ..RuleSpec::new(3)
+        };
+        for referee in RefereeSpec::VARIANTS {
+            let referee = RefereeSpec::from_str(referee).unwrap().build(rules);
+            let mut board = GameState::new(3, 3, None);
+            let floating = PointPlacement { row: 0, column: 0 };
+            let bottom = PointPlacement { row: 2, column: 0 };
+            assert_eq!(
+                referee.receive_move(&mut board, floating, 0),
+                GameResult::IllegalMove
+            );
+            assert_eq!(
+                referee.receive_move(&mut board, bottom, 0),
+                GameResult::Undecided
+            );
+        }
+    }
+}
@@ -0,0 +1,693 @@
+use crate::interfaces::{
+    BoardSizeT, BoardStateEntry, GameResult, GameState, PlayerID, PointPlacement,
+    TicTacToeReferee,
+};
+use anyhow::Context;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 6] = b"TTTDB\x01";
+/// Number of cells whose contents, each one of three, fit into a `u64` key.
+const MAX_CELLS: usize = 40;
+const DISTANCE_BITS: u32 = 6;
+/// Number of entries allocated before reading a database, more are allocated as needed.
+const MAX_PREALLOCATED_ENTRIES: usize = 1 << 20;
+
+/// Value of a position for the player to move, as stored in a `Database`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// `Victory`, `Draw` or `Defeat` with perfect play of both players.
+    pub result: GameResult,
+    /// Number of moves until the game ends, if the winning side wins as fast as
+    /// possible and the losing side resists as long as possible.
+    pub distance: u8,
+}
+
+impl Entry {
+    /// Returns the entry of a move of the player to move, given the referee's verdict
+    /// and, if the game goes on, the entry of the position reached.
+    #[must_use]
+    pub fn of_move(result: GameResult, next: Option<Entry>) -> Option<Self> {
+        let result = match result {
+            GameResult::Victory => GameResult::Victory,
+            GameResult::Defeat | GameResult::IllegalMove => GameResult::Defeat,
+            GameResult::Draw => GameResult::Draw,
+            GameResult::Undecided => {
+                let next = next?;
+                return Some(Self {
+                    result: match next.result {
+                        GameResult::Victory => GameResult::Defeat,
+                        GameResult::Defeat => GameResult::Victory,
+                        result => result,
+                    },
+                    distance: next.distance + 1,
+                });
+            }
+        };
+        Some(Self {
+            result,
+            distance: 1,
+        })
+    }
+
+    /// Returns how much the player to move prefers the entry, the higher the better.
+    fn get_score(self) -> i32 {
+        let distance = i32::from(self.distance);
+        match self.result {
+            GameResult::Victory => 1000 - distance,
+            GameResult::Defeat => distance - 1000,
+            _ => 0,
+        }
+    }
+
+    /// Returns the best of `entries`, or a draw if there is none.
+    fn get_best(entries: impl Iterator<Item = Entry>) -> Self {
+        entries
+            .max_by_key(|entry| entry.get_score())
+            .unwrap_or(Self {
+                result: GameResult::Draw,
+                distance: 0,
+            })
+    }
+
+    fn to_byte(self) -> u8 {
+        let result: u8 = match self.result {
+            GameResult::Defeat => 0,
+            GameResult::Draw => 1,
+            _ => 2,
+        };
+        result << DISTANCE_BITS | self.distance
+    }
+
+    fn from_byte(byte: u8) -> anyhow::Result<Self> {
+        let result = match byte >> DISTANCE_BITS {
+            0 => GameResult::Defeat,
+            1 => GameResult::Draw,
+            2 => GameResult::Victory,
+            _ => anyhow::bail!("Invalid entry {byte:#x} in database!"),
+        };
+        Ok(Self {
+            result,
+            distance: byte & ((1 << DISTANCE_BITS) - 1),
+        })
+    }
+}
+
+/// Everything needed to turn boards into keys and back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Layout {
+    blocked: Vec<bool>,
+    ncolumns: BoardSizeT,
+    nrows: BoardSizeT,
+    /// Number of stones on the board the database was generated from.
+    nroot_stones: usize,
+    /// The player to move on the board the database was generated from, followed by
+    /// their opponent.
+    players: [PlayerID; 2],
+}
+
+impl Layout {
+    fn new(board: &GameState, players: [PlayerID; 2]) -> anyhow::Result<Self> {
+        let blocked: Vec<_> =
+            board.iter_2d().map(|(_, cell)| cell.is_blocked()).collect();
+        let ncells = blocked.iter().filter(|&&is_blocked| !is_blocked).count();
+        if ncells > MAX_CELLS {
+            anyhow::bail!(
+                "Boards of more than {MAX_CELLS} free cells are not supported, got \
+                 {ncells}!"
+            );
+        }
+        if players[0] == players[1] || players.contains(&PlayerID::MAX) {
+            anyhow::bail!("Database needs two distinct players!");
+        }
+        let layout = Self {
+            blocked,
+            ncolumns: board.get_number_of_columns(),
+            nrows: board.get_number_of_rows(),
+            nroot_stones: 0,
+            players,
+        };
+        let nroot_stones = layout
+            .get_number_of_stones(board)
+            .context("Board contains stones of other players!")?;
+        Ok(Self {
+            nroot_stones,
+            ..layout
+        })
+    }
+
+    fn fits(&self, board: &GameState) -> bool {
+        board.get_number_of_rows() == self.nrows
+            && board.get_number_of_columns() == self.ncolumns
+    }
+
+    fn get_number_of_stones(&self, board: &GameState) -> Option<usize> {
+        Some(self.get_digits(board)?.filter(|&digit| digit != 0).count())
+    }
+
+    /// Returns the player to move on `board`, assuming that the players take turns.
+    fn get_player_to_move(&self, board: &GameState) -> Option<PlayerID> {
+        let nstones = self.get_number_of_stones(board)?;
+        let nmoves = nstones.checked_sub(self.nroot_stones)?;
+        Some(self.players[nmoves % 2])
+    }
+
+    /// Returns the contents of the cells that are not blocked, with 0 for a free cell
+    /// and 1 or 2 for a stone of the first or second player.
+    fn get_digits<'board>(
+        &'board self,
+        board: &'board GameState,
+    ) -> Option<impl Iterator<Item = u64> + 'board> {
+        if !self.fits(board) {
+            return None;
+        }
+        let is_valid =
+            board
+                .iter_2d()
+                .zip(&self.blocked)
+                .all(|((_, cell), &is_blocked)| {
+                    cell.is_blocked() == is_blocked
+                        && Option::<PlayerID>::from(*cell)
+                            .is_none_or(|id| is_blocked || self.players.contains(&id))
+                });
+        is_valid.then(|| {
+            board
+                .iter_2d()
+                .zip(&self.blocked)
+                .filter(|&(_, &is_blocked)| !is_blocked)
+                .map(|((_, cell), _)| match Option::<PlayerID>::from(*cell) {
+                    None => 0,
+                    Some(id) if id == self.players[0] => 1,
+                    Some(_) => 2,
+                })
+        })
+    }
+
+    fn encode(&self, board: &GameState) -> Option<u64> {
+        Some(
+            self.get_digits(board)?
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .fold(0, |key, digit| key * 3 + digit),
+        )
+    }
+
+    fn decode(&self, mut key: u64) -> GameState {
+        let mut board = GameState::new(self.nrows, self.ncolumns, None);
+        let cells: Vec<_> = board.iter_2d().map(|(pp, _)| pp).collect();
+        for (pp, &is_blocked) in cells.into_iter().zip(&self.blocked) {
+            board[pp] = if is_blocked {
+                BoardStateEntry::BLOCKED
+            } else {
+                let digit = key % 3;
+                key /= 3;
+                match digit {
+                    0 => None.into(),
+                    1 => Some(self.players[0]).into(),
+                    _ => Some(self.players[1]).into(),
+                }
+            };
+        }
+        board
+    }
+}
+
+/// Values of all positions reachable from a board, computed by retrograde analysis.
+///
+/// The positions are enumerated layer by layer, each layer holding one stone more than
+/// the previous one. Then, the layers are valued from the last to the first, so that
+/// the values of all positions a move leads to are known when valuing a position.
+/// Positions in which the game is over are not stored.
+///
+/// Two players take turns placing a single stone each. Every position is stored as a
+/// key of one digit per cell that is not blocked, so at most 40 of them are supported.
+/// On disk, the database takes about two bytes per position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Database {
+    entries: Vec<u8>,
+    keys: Vec<u64>,
+    layout: Layout,
+}
+
+impl Database {
+    /// Returns whether `board` has few enough free cells for a database.
+    #[must_use]
+    pub fn supports_board(board: &GameState) -> bool {
+        board
+            .iter_2d()
+            .filter(|(_, cell)| !cell.is_blocked())
+            .count()
+            <= MAX_CELLS
+    }
+
+    /// Generates the database of all positions reachable from `board`, on which
+    /// `players[0]` is to move against `players[1]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the board has too many cells or stones of other players.
+    pub fn generate(
+        board: &GameState,
+        referee: &dyn TicTacToeReferee,
+        players: [PlayerID; 2],
+    ) -> anyhow::Result<Self> {
+        let layout = Layout::new(board, players)?;
+        let mut layers = Vec::new();
+        if referee.get_winner(board).is_none() {
+            let root_key = layout
+                .encode(board)
+                .context("Board does not fit its own layout!")?;
+            layers.push(vec![root_key]);
+        }
+        while let Some(layer) = layers.last() {
+            let next_layer =
+                Self::get_next_layer(&layout, referee, layer, layers.len());
+            if next_layer.is_empty() {
+                break;
+            }
+            layers.push(next_layer);
+        }
+
+        let mut entries: Vec<Vec<u8>> = vec![Vec::new(); layers.len()];
+        for depth in (0..layers.len()).rev() {
+            let player = players[depth % 2];
+            let next_layer = layers.get(depth + 1).map_or(&[][..], |keys| &keys[..]);
+            let next_entries = entries.get(depth + 1).map_or(&[][..], |keys| &keys[..]);
+            let get_next_entry = |board: &GameState| {
+                let key = layout.encode(board)?;
+                let idx = next_layer.binary_search(&key).ok()?;
+                Entry::from_byte(next_entries[idx]).ok()
+            };
+            entries[depth] = layers[depth]
+                .iter()
+                .map(|&key| {
+                    let mut board = layout.decode(key);
+                    let entries = Self::get_move_entries(
+                        &mut board,
+                        referee,
+                        player,
+                        &get_next_entry,
+                    );
+                    Entry::get_best(entries.into_iter().map(|(_, entry)| entry))
+                        .to_byte()
+                })
+                .collect();
+        }
+
+        let mut pairs: Vec<_> = layers
+            .into_iter()
+            .flatten()
+            .zip(entries.into_iter().flatten())
+            .collect();
+        pairs.sort_unstable();
+        let (keys, entries) = pairs.into_iter().unzip();
+        Ok(Self {
+            entries,
+            keys,
+            layout,
+        })
+    }
+
+    /// Returns all positions reached by a move from those of `layer` in which the
+    /// game goes on, ordered by key.
+    fn get_next_layer(
+        layout: &Layout,
+        referee: &dyn TicTacToeReferee,
+        layer: &[u64],
+        depth: usize,
+    ) -> Vec<u64> {
+        let player = layout.players[(depth - 1) % 2];
+        let mut next_layer = Vec::new();
+        for &key in layer {
+            let mut board = layout.decode(key);
+            for pp in referee.get_legal_moves(&board) {
+                let old_entry = board[pp];
+                if referee.receive_move(&mut board, pp, player) == GameResult::Undecided
+                {
+                    next_layer
+                        .push(layout.encode(&board).expect("Layout fits the board."));
+                }
+                board[pp] = old_entry;
+            }
+        }
+        next_layer.sort_unstable();
+        next_layer.dedup();
+        next_layer
+    }
+
+    /// Returns the entries of all legal moves of `player` on `board`, looking up the
+    /// positions in which the game goes on with `get_next_entry`.
+    ///
+    /// Moves leading to positions without an entry are left out.
+    fn get_move_entries(
+        board: &mut GameState,
+        referee: &dyn TicTacToeReferee,
+        player: PlayerID,
+        get_next_entry: &dyn Fn(&GameState) -> Option<Entry>,
+    ) -> Vec<(PointPlacement, Entry)> {
+        let mut entries = Vec::new();
+        for pp in referee.get_legal_moves(board) {
+            let old_entry = board[pp];
+            let result = referee.receive_move(board, pp, player);
+            let next = (result == GameResult::Undecided)
+                .then(|| get_next_entry(board))
+                .flatten();
+            if let Some(entry) = Entry::of_move(result, next) {
+                entries.push((pp, entry));
+            }
+            board[pp] = old_entry;
+        }
+        entries
+    }
+
+    /// Returns the number of positions stored.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the player to move on `board`, or `None` if the board cannot be reached
+    /// from the one the database was generated from.
+    #[must_use]
+    pub fn get_player_to_move(&self, board: &GameState) -> Option<PlayerID> {
+        self.layout.get_player_to_move(board)
+    }
+
+    /// Returns the value of `board` for the player to move.
+    ///
+    /// Returns `None` if the game is over on `board` or if it cannot be reached from
+    /// the board the database was generated from.
+    #[must_use]
+    pub fn lookup(&self, board: &GameState) -> Option<Entry> {
+        let key = self.layout.encode(board)?;
+        let idx = self.keys.binary_search(&key).ok()?;
+        Entry::from_byte(self.entries[idx]).ok()
+    }
+
+    /// Returns the entries of all legal moves of the player to move on `board`.
+    ///
+    /// Returns an empty vector if the database does not know `board`.
+    #[must_use]
+    pub fn get_move_entries_of(
+        &self,
+        board: &GameState,
+        referee: &dyn TicTacToeReferee,
+    ) -> Vec<(PointPlacement, Entry)> {
+        let Some(player) = self.get_player_to_move(board) else {
+            return Vec::new();
+        };
+        if self.lookup(board).is_none() {
+            return Vec::new();
+        }
+        let mut board = board.clone();
+        Self::get_move_entries(&mut board, referee, player, &|board| self.lookup(board))
+    }
+
+    /// Returns the moves of the player to move on `board` that keep its value, i.e.
+    /// win as fast or lose as slowly as possible.
+    ///
+    /// Returns an empty vector if the database does not know `board`.
+    #[must_use]
+    pub fn get_best_moves(
+        &self,
+        board: &GameState,
+        referee: &dyn TicTacToeReferee,
+    ) -> Vec<PointPlacement> {
+        let entries = self.get_move_entries_of(board, referee);
+        let Some(best) = entries.iter().map(|(_, entry)| entry.get_score()).max()
+        else {
+            return Vec::new();
+        };
+        entries
+            .into_iter()
+            .filter(|(_, entry)| entry.get_score() == best)
+            .map(|(pp, _)| pp)
+            .collect()
+    }
+
+    /// Writes the database to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Could not create '{}'!", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a database written by `save` from `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is no database.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Could not open '{}'!", path.display()))?;
+        Self::read_from(&mut BufReader::new(file))
+            .with_context(|| format!("Could not read database '{}'!", path.display()))
+    }
+
+    /// Writes the database in a compact binary format.
+    ///
+    /// After a header describing the board, the keys are stored in ascending order as
+    /// variable-length differences to their predecessor, each followed by its entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn write_to(&self, writer: &mut dyn Write) -> anyhow::Result<()> {
+        let layout = &self.layout;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&layout.nrows.to_le_bytes())?;
+        writer.write_all(&layout.ncolumns.to_le_bytes())?;
+        let blocked: Vec<u8> = layout.blocked.iter().map(|&b| u8::from(b)).collect();
+        writer.write_all(&blocked)?;
+        for player in layout.players {
+            writer.write_all(&player.to_le_bytes())?;
+        }
+        writer.write_all(&(layout.nroot_stones as u64).to_le_bytes())?;
+        writer.write_all(&(self.keys.len() as u64).to_le_bytes())?;
+        let mut previous = 0;
+        for (&key, &entry) in self.keys.iter().zip(&self.entries) {
+            let mut delta = key - previous;
+            loop {
+                #[allow(clippy::cast_possible_truncation)]
+                // Only the lowest seven bits are kept.
+                let byte = (delta & 0x7f) as u8;
+                delta >>= 7;
+                if delta == 0 {
+                    writer.write_all(&[byte])?;
+                    break;
+                }
+                writer.write_all(&[byte | 0x80])?;
+            }
+            writer.write_all(&[entry])?;
+            previous = key;
+        }
+        Ok(())
+    }
+
+    /// Reads a database written by `write_to`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or the data is no database.
+    pub fn read_from(reader: &mut dyn Read) -> anyhow::Result<Self> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            anyhow::bail!("Data is no database of this version!");
+        }
+        let nrows = u16::from_le_bytes(read_array(reader)?);
+        let ncolumns = u16::from_le_bytes(read_array(reader)?);
+        let ncells = usize::from(nrows) * usize::from(ncolumns);
+        // Reading piecewise, a truncated file does not allocate the whole board.
+        let mut bytes = Vec::new();
+        reader.take(ncells as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != ncells {
+            anyhow::bail!("Data ends within the board!");
+        }
+        let blocked: Vec<_> = bytes.into_iter().map(|byte| byte != 0).collect();
+        let nfree_cells = blocked.iter().filter(|&&is_blocked| !is_blocked).count();
+        if nfree_cells > MAX_CELLS {
+            anyhow::bail!(
+                "Board has {nfree_cells} free cells, at most {MAX_CELLS} are supported!"
+            );
+        }
+        let players = [
+            u16::from_le_bytes(read_array(reader)?),
+            u16::from_le_bytes(read_array(reader)?),
+        ];
+        let nroot_stones = usize::try_from(u64::from_le_bytes(read_array(reader)?))?;
+        let nentries = usize::try_from(u64::from_le_bytes(read_array(reader)?))?;
+        // Every free cell is empty or taken by one of the two players.
+        if 3_u64
+            .checked_pow(u32::try_from(nfree_cells)?)
+            .is_some_and(|npositions| (nentries as u64) > npositions)
+        {
+            anyhow::bail!("Database has more entries than there are positions!");
+        }
+        // Even so, the entries may take more memory than the data provides, which is
+        // only allocated as the entries are read.
+        let capacity = nentries.min(MAX_PREALLOCATED_ENTRIES);
+        let mut keys = Vec::with_capacity(capacity);
+        let mut entries = Vec::with_capacity(capacity);
+        let mut previous: u64 = 0;
+        for _ in 0..nentries {
+            let mut delta: u64 = 0;
+            for shift in (0..64).step_by(7) {
+                let [byte] = read_array(reader)?;
+                delta |= u64::from(byte & 0x7f) << shift;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            let [entry] = read_array(reader)?;
+            Entry::from_byte(entry)?;
+            previous = previous
+                .checked_add(delta)
+                .context("Key is out of range!")?;
+            keys.push(previous);
+            entries.push(entry);
+        }
+        Ok(Self {
+            entries,
+            keys,
+            layout: Layout {
+                blocked,
+                ncolumns,
+                nrows,
+                nroot_stones,
+                players,
+            },
+        })
+    }
+}
+
+fn read_array<const N: usize>(reader: &mut dyn Read) -> anyhow::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::parse_mask;
+    use crate::referee::{GravityReferee, NaiveReferee};
+    use crate::solver::ProofNumberSearch;
+    use rstest::*;
+
+    #[test]
+    fn all_positions_of_tic_tac_toe_are_found() {
+        let referee = NaiveReferee::new(3);
+        let board = GameState::new(3, 3, None);
+        let database = Database::generate(&board, &referee, [0, 1]).unwrap();
+        // 5478 legal positions, of which 958 end the game
+        assert_eq!(database.len(), 5478 - 958);
+        assert_eq!(
+            database.lookup(&board),
+            Some(Entry {
+                result: GameResult::Draw,
+                distance: 9
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("00.\n11.\n...", GameResult::Victory, 1)]
+    #[case("00.\n1..\n1.0", GameResult::Defeat, 2)]
+    #[case("0..\n...\n..1", GameResult::Victory, 5)]
+    fn entries_match_the_solver(
+        #[case] mask: &str,
+        #[case] expected: GameResult,
+        #[case] distance: u8,
+    ) {
+        let referee = NaiveReferee::new(3);
+        let board = parse_mask(mask).unwrap();
+        let database = Database::generate(&board, &referee, [1, 0]).unwrap();
+        let entry = database.lookup(&board).unwrap();
+        let solution = ProofNumberSearch::new(100_000, 0, &referee, 1)
+            .solve(&board)
+            .unwrap();
+        assert_eq!(entry.result, expected);
+        assert_eq!(entry.result, solution.result);
+        assert_eq!(entry.distance, distance);
+    }
+
+    #[test]
+    fn unknown_positions_are_not_found() {
+        let referee = NaiveReferee::new(3);
+        let board = parse_mask("0..\n...\n...").unwrap();
+        let database = Database::generate(&board, &referee, [1, 0]).unwrap();
+        // other size, other player, before the root and game over
+        for mask in [
+            "0..\n...",
+            "2..\n...\n...",
+            "...\n...\n...",
+            "000\n11.\n...",
+        ] {
+            assert_eq!(database.lookup(&parse_mask(mask).unwrap()), None);
+        }
+        let board = parse_mask("01.\n...\n...").unwrap();
+        assert_eq!(database.get_player_to_move(&board), Some(0));
+        assert!(database.lookup(&board).is_some());
+    }
+
+    #[test]
+    fn database_survives_a_round_trip() {
+        let referee = GravityReferee::new(Box::new(NaiveReferee::new(3)));
+        let board = parse_mask("....\n.#..\n....").unwrap();
+        let database = Database::generate(&board, &referee, [0, 1]).unwrap();
+        let mut bytes = Vec::new();
+        database.write_to(&mut bytes).unwrap();
+        assert!(bytes.len() < 3 * database.len());
+        let loaded = Database::read_from(&mut &bytes[..]).unwrap();
+        assert_eq!(loaded, database);
+        assert!(Database::read_from(&mut &bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn corrupt_sizes_are_rejected() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(u16::MAX.to_le_bytes());
+        bytes.extend(u16::MAX.to_le_bytes());
+        assert!(Database::read_from(&mut &bytes[..]).is_err());
+
+        let board = GameState::new(2, 2, None);
+        let database =
+            Database::generate(&board, &NaiveReferee::new(3), [0, 1]).unwrap();
+        let mut bytes = Vec::new();
+        database.write_to(&mut bytes).unwrap();
+        // The number of entries follows the magic, the board size, the four cells, the
+        // players and the number of stones on the root.
+        let offset = MAGIC.len() + 4 + 4 + 4 + 8;
+        bytes[offset..offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(Database::read_from(&mut &bytes[..]).is_err());
+    }
+
+    #[test]
+    fn best_moves_are_told() {
+        let referee = NaiveReferee::new(3);
+        let board = parse_mask("1..\n...\n...").unwrap();
+        let database = Database::generate(&board, &referee, [0, 1]).unwrap();
+        let draws: Vec<_> = database
+            .get_move_entries_of(&board, &referee)
+            .into_iter()
+            .filter(|(_, entry)| entry.result == GameResult::Draw)
+            .map(|(pp, _)| pp)
+            .collect();
+        assert_eq!(draws, vec![PointPlacement { row: 1, column: 1 }]);
+    }
+}
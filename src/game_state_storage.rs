@@ -1,14 +1,18 @@
+use crate::board::{Board, Symmetry};
+use crate::interfaces::{GameState, SearchPosition};
+use crate::zobrist::ZobristGameState;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::hash::Hash;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
 
 pub trait GameStateStorage<
     KeyT: Clone + Eq + Hash,
-    Payload,
+    Payload: Clone,
     DepthT: std::cmp::PartialOrd + Copy = u32,
 >
 {
     fn register_game_state(&mut self, board: &KeyT, payload: Payload, depth: DepthT);
-    fn get_payload(&self, board: &KeyT, depth: DepthT) -> Option<&Payload>;
+    fn get_payload(&self, board: &KeyT, depth: DepthT) -> Option<Cow<'_, Payload>>;
 }
 
 #[derive(Clone, Debug)]
@@ -23,7 +27,7 @@ pub struct NaiveGameStateStorage<
 impl<KeyT: Clone + Eq + Hash, Payload, DepthT: std::cmp::PartialOrd + Copy>
     NaiveGameStateStorage<KeyT, Payload, DepthT>
 {
-    #[allow(dead_code)]
+    #[must_use]
     pub fn new() -> Self {
         Self {
             storage: HashMap::new(),
@@ -31,7 +35,15 @@ impl<KeyT: Clone + Eq + Hash, Payload, DepthT: std::cmp::PartialOrd + Copy>
     }
 }
 
-impl<KeyT: Clone + Eq + Hash, Payload, DepthT: std::cmp::PartialOrd + Copy>
+impl<KeyT: Clone + Eq + Hash, Payload, DepthT: std::cmp::PartialOrd + Copy> Default
+    for NaiveGameStateStorage<KeyT, Payload, DepthT>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<KeyT: Clone + Eq + Hash, Payload: Clone, DepthT: std::cmp::PartialOrd + Copy>
     GameStateStorage<KeyT, Payload, DepthT>
     for NaiveGameStateStorage<KeyT, Payload, DepthT>
 {
@@ -54,10 +66,10 @@ impl<KeyT: Clone + Eq + Hash, Payload, DepthT: std::cmp::PartialOrd + Copy>
     /// # Arguments
     /// * `board` - The board to retrieve the payload for.
     /// * `depth` - The minimal required search depth.
-    fn get_payload(&self, board: &KeyT, depth: DepthT) -> Option<&Payload> {
+    fn get_payload(&self, board: &KeyT, depth: DepthT) -> Option<Cow<'_, Payload>> {
         self.storage.get(board).and_then(|(stored_depth, payload)| {
             if *stored_depth >= depth {
-                Some(payload)
+                Some(Cow::Borrowed(payload))
             } else {
                 None
             }
@@ -65,10 +77,179 @@ impl<KeyT: Clone + Eq + Hash, Payload, DepthT: std::cmp::PartialOrd + Copy>
     }
 }
 
+/// Storage treating rotated and reflected boards as the same game state.
+///
+/// Every board is stored in a canonical orientation, namely the smallest of its
+/// transformations under the symmetries the board shape allows. Payloads are boards
+/// as well, e.g. an `Evaluation`, and are transformed along with the game state, so
+/// that a lookup returns the payload in the orientation of the queried board.
+///
+/// This is only correct for referees that do not distinguish between symmetric
+/// positions, which holds for k-in-a-row on plain two-dimensional square grids. It
+/// does not hold with gravity, which only allows the left-right reflection, on hex
+/// grids, whose lines break under some of the maps, on boards of more dimensions or
+/// in ultimate tic-tac-toe, whose stones also encode the order of the moves. If a
+/// board is symmetric itself, its payload is expected to share that symmetry.
+#[derive(Clone, Debug)]
+pub struct SymmetricGameStateStorage<T, DepthT: std::cmp::PartialOrd + Copy = u32> {
+    storage: NaiveGameStateStorage<GameState, Board<T>, DepthT>,
+}
+
+impl<T: Copy, DepthT: std::cmp::PartialOrd + Copy>
+    SymmetricGameStateStorage<T, DepthT>
+{
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            storage: NaiveGameStateStorage::new(),
+        }
+    }
+
+    /// Returns the canonical form of `board` and the symmetry leading to it.
+    fn canonicalise(board: &GameState) -> (GameState, Symmetry) {
+        Symmetry::for_shape(board.get_number_of_rows(), board.get_number_of_columns())
+            .iter()
+            .map(|&symmetry| (board.transform(symmetry), symmetry))
+            .min_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs))
+            .expect("There is always the identity.")
+    }
+}
+
+impl<T: Copy, DepthT: std::cmp::PartialOrd + Copy> Default
+    for SymmetricGameStateStorage<T, DepthT>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy, DepthT: std::cmp::PartialOrd + Copy>
+    GameStateStorage<GameState, Board<T>, DepthT>
+    for SymmetricGameStateStorage<T, DepthT>
+{
+    fn register_game_state(
+        &mut self,
+        board: &GameState,
+        payload: Board<T>,
+        depth: DepthT,
+    ) {
+        let (canonical_board, symmetry) = Self::canonicalise(board);
+        self.storage.register_game_state(
+            &canonical_board,
+            payload.transform(symmetry),
+            depth,
+        );
+    }
+
+    fn get_payload(
+        &self,
+        board: &GameState,
+        depth: DepthT,
+    ) -> Option<Cow<'_, Board<T>>> {
+        let (canonical_board, symmetry) = Self::canonicalise(board);
+        self.storage
+            .get_payload(&canonical_board, depth)
+            .map(|payload| Cow::Owned(payload.transform(symmetry.inverse())))
+    }
+}
+
+/// Hasher passing through the Zobrist hash, which is well distributed already.
+#[derive(Default)]
+struct ZobristHasher(u64);
+
+impl Hasher for ZobristHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {
+        unreachable!("Only Zobrist hashes are expected.");
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = value;
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ZobristEntry<Payload, DepthT> {
+    board: Option<GameState>,
+    depth: DepthT,
+    payload: Payload,
+}
+
+/// Storage keyed on the Zobrist hash of a game state.
+///
+/// Neither the board is hashed nor is it cloned for the key. The price is that two
+/// different boards sharing a hash are mistaken for each other. With verification
+/// enabled, a copy of each board is kept and compared on lookup, so that such
+/// collisions are detected. The newer board then replaces the older one.
+#[derive(Clone, Debug)]
+pub struct ZobristGameStateStorage<Payload, DepthT: std::cmp::PartialOrd + Copy = u32> {
+    storage:
+        HashMap<u64, ZobristEntry<Payload, DepthT>, BuildHasherDefault<ZobristHasher>>,
+    verify: bool,
+}
+
+impl<Payload, DepthT: std::cmp::PartialOrd + Copy>
+    ZobristGameStateStorage<Payload, DepthT>
+{
+    #[must_use]
+    pub fn new(verify: bool) -> Self {
+        Self {
+            storage: HashMap::default(),
+            verify,
+        }
+    }
+
+    fn get_entry(
+        &self,
+        board: &ZobristGameState,
+    ) -> Option<&ZobristEntry<Payload, DepthT>> {
+        self.storage.get(&board.get_hash()).filter(|entry| {
+            entry
+                .board
+                .as_ref()
+                .is_none_or(|stored_board| stored_board == board.get_game_state())
+        })
+    }
+}
+
+impl<Payload: Clone, DepthT: std::cmp::PartialOrd + Copy>
+    GameStateStorage<ZobristGameState, Payload, DepthT>
+    for ZobristGameStateStorage<Payload, DepthT>
+{
+    fn register_game_state(
+        &mut self,
+        board: &ZobristGameState,
+        payload: Payload,
+        depth: DepthT,
+    ) {
+        if self.get_payload(board, depth).is_none() {
+            let entry = ZobristEntry {
+                board: self.verify.then(|| board.get_game_state().clone()),
+                depth,
+                payload,
+            };
+            self.storage.insert(board.get_hash(), entry);
+        }
+    }
+
+    fn get_payload(
+        &self,
+        board: &ZobristGameState,
+        depth: DepthT,
+    ) -> Option<Cow<'_, Payload>> {
+        self.get_entry(board)
+            .filter(|entry| entry.depth >= depth)
+            .map(|entry| Cow::Borrowed(&entry.payload))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::interfaces::{GameState, PointPlacement};
+    use crate::interfaces::PointPlacement;
 
     #[test]
     fn test_can_store_and_retrieve() {
@@ -81,7 +262,7 @@ mod tests {
 
         storage.register_game_state(&board, payload.clone(), depth);
         let result = storage.get_payload(&board, depth);
-        assert_eq!(result, Some(&payload));
+        assert_eq!(result.as_deref(), Some(&payload));
     }
 
     #[test]
@@ -97,7 +278,7 @@ mod tests {
 
         storage.register_game_state(&board, payload.clone(), depth + 1);
         let result = storage.get_payload(&board, depth);
-        assert_eq!(result, Some(&payload));
+        assert_eq!(result.as_deref(), Some(&payload));
     }
 
     #[test]
@@ -148,8 +329,8 @@ mod tests {
         storage.register_game_state(&board, deep_payload.clone(), depth);
         let shallow_result = storage.get_payload(&board, depth - 1);
         let deep_result = storage.get_payload(&board, depth);
-        assert_eq!(shallow_result, Some(&deep_payload));
-        assert_eq!(deep_result, Some(&deep_payload));
+        assert_eq!(shallow_result.as_deref(), Some(&deep_payload));
+        assert_eq!(deep_result.as_deref(), Some(&deep_payload));
     }
 
     #[test]
@@ -165,6 +346,100 @@ mod tests {
         storage.register_game_state(&board, deep_payload.clone(), depth);
         storage.register_game_state(&board, shallow_payload.clone(), depth - 1);
         let result = storage.get_payload(&board, depth - 1);
-        assert_eq!(result, Some(&deep_payload));
+        assert_eq!(result.as_deref(), Some(&deep_payload));
+    }
+
+    #[test]
+    fn test_symmetric_storage_transforms_payload() {
+        // The board must not be symmetric itself, as the payload is not either.
+        let board = GameState::new_with_values([
+            [None, Some(0), None],
+            [None, None, None],
+            [None, None, Some(1)],
+        ])
+        .unwrap();
+        let payload = Board::<f32>::new_with_values([
+            [0.0, 1.0, 2.0],
+            [3.0, 4.0, 5.0],
+            [6.0, 7.0, 8.0],
+        ])
+        .unwrap();
+        let mut storage = SymmetricGameStateStorage::<f32>::new();
+
+        storage.register_game_state(&board, payload.clone(), 2);
+        for symmetry in Symmetry::ALL {
+            let result = storage.get_payload(&board.transform(symmetry), 2);
+            assert_eq!(result.as_deref(), Some(&payload.transform(symmetry)));
+        }
+    }
+
+    #[test]
+    fn test_symmetric_storage_keeps_rectangular_boards_apart() {
+        let board =
+            GameState::new_with_values([[Some(0), None, None], [None, None, None]])
+                .unwrap();
+        let mut storage = SymmetricGameStateStorage::<f32>::new();
+
+        storage.register_game_state(&board, Board::new_from_existing(&board, 1.0), 2);
+        let flipped = board.transform(Symmetry::Rotate180);
+        assert!(storage.get_payload(&flipped, 2).is_some());
+        let transposed = board.transform(Symmetry::Transpose);
+        assert!(storage.get_payload(&transposed, 2).is_none());
+    }
+
+    #[test]
+    fn test_symmetric_storage_distinguishes_different_boards() {
+        let mut board = GameState::new(3, 3, None);
+        board[PointPlacement { row: 0, column: 0 }] = Some(0).into();
+        let mut other_board = GameState::new(3, 3, None);
+        other_board[PointPlacement { row: 0, column: 1 }] = Some(0).into();
+        let mut storage = SymmetricGameStateStorage::<f32>::new();
+
+        storage.register_game_state(&board, Board::new(3, 3, 1.0), 2);
+        assert!(storage.get_payload(&other_board, 2).is_none());
+    }
+
+    #[rstest::rstest]
+    fn test_zobrist_storage_finds_transpositions(#[values(false, true)] verify: bool) {
+        let board = GameState::new(3, 3, None);
+        let first = PointPlacement { row: 0, column: 1 };
+        let second = PointPlacement { row: 2, column: 2 };
+        let mut position = ZobristGameState::from_game_state(&board);
+        let mut transposed_position = position.clone();
+        let mut storage = ZobristGameStateStorage::<String>::new(verify);
+
+        position.set_entry(first, Some(0).into());
+        position.set_entry(second, Some(1).into());
+        storage.register_game_state(&position, "Payload".to_string(), 2);
+        assert!(storage.get_payload(&transposed_position, 2).is_none());
+
+        transposed_position.set_entry(second, Some(1).into());
+        transposed_position.set_entry(first, Some(0).into());
+        let result = storage.get_payload(&transposed_position, 2);
+        assert_eq!(result.as_deref().map(String::as_str), Some("Payload"));
+        assert!(storage.get_payload(&transposed_position, 3).is_none());
+    }
+
+    #[rstest::rstest]
+    #[case(false, true)]
+    #[case(true, false)]
+    fn test_zobrist_storage_verification_detects_collisions(
+        #[case] verify: bool,
+        #[case] expect_payload: bool,
+    ) {
+        let board = GameState::new(3, 3, None);
+        let mut other_board = board.clone();
+        other_board[PointPlacement { row: 1, column: 1 }] = Some(0).into();
+        let position = ZobristGameState::from_game_state(&board);
+        let mut storage = ZobristGameStateStorage::<String>::new(verify);
+
+        // Fake a collision by storing another board under the same hash.
+        let entry = ZobristEntry {
+            board: verify.then_some(other_board),
+            depth: 2,
+            payload: "Other Payload".to_string(),
+        };
+        storage.storage.insert(position.get_hash(), entry);
+        assert_eq!(storage.get_payload(&position, 2).is_some(), expect_payload);
     }
 }
@@ -1,5 +1,6 @@
 use crate::board::Board;
 use std::fmt;
+use std::hash::Hash;
 
 pub type BoardSizeT = u16;
 pub type WinLengthT = u16;
@@ -10,10 +11,22 @@ pub type Evaluation = Board<f32>;
 pub type Placement = Board<f32>;
 pub type GameState = Board<BoardStateEntry>;
 
-#[derive(PartialEq, Hash, Eq, Copy, Clone, Debug)]
+#[derive(PartialEq, Hash, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
 pub struct BoardStateEntry(Option<PlayerID>);
 
 impl BoardStateEntry {
+    /// Cell that cannot be played and breaks every line running through it.
+    ///
+    /// Blocked cells are taken by a reserved player ID, which must not be used by any
+    /// player.
+    pub const BLOCKED: Self = BoardStateEntry(Some(PlayerID::MAX));
+
+    #[inline]
+    #[must_use]
+    pub fn is_blocked(self) -> bool {
+        self == Self::BLOCKED
+    }
+
     #[inline]
     #[must_use]
     pub fn is_taken(self) -> bool {
@@ -25,11 +38,20 @@ impl BoardStateEntry {
     pub fn is_free(self) -> bool {
         !self.is_taken()
     }
+
+    /// Returns whether a player's stone is on the cell, i.e. whether it is taken but
+    /// not blocked.
+    #[inline]
+    #[must_use]
+    pub fn has_stone(self) -> bool {
+        self.is_taken() && !self.is_blocked()
+    }
 }
 
 impl fmt::Display for BoardStateEntry {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.0 {
+            _ if self.is_blocked() => write!(f, "#"),
             Some(player_id) => write!(f, "{player_id}"),
             None => write!(f, "."),
         }
@@ -42,6 +64,12 @@ impl From<Option<PlayerID>> for BoardStateEntry {
     }
 }
 
+impl From<BoardStateEntry> for Option<PlayerID> {
+    fn from(value: BoardStateEntry) -> Self {
+        value.0
+    }
+}
+
 #[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
 pub struct PointPlacement {
     pub row: BoardSizeT,
@@ -54,7 +82,7 @@ impl fmt::Display for PointPlacement {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub enum GameResult {
     Defeat,
     Draw,
@@ -81,6 +109,103 @@ pub trait TicTacToeReferee {
         placement: PointPlacement,
         player: PlayerID,
     ) -> GameResult;
+
+    /// Judges `placement` like `receive_move`, given the number of cells that are free
+    /// on `board` before the placement.
+    ///
+    /// Callers keeping track of the free cells spare referees counting them to detect
+    /// a full board. By default, the count is ignored.
+    fn receive_counted_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player: PlayerID,
+        _nfree_cells: usize,
+    ) -> GameResult {
+        self.receive_move(board, placement, player)
+    }
+
+    /// Returns all placements that are legal on `board`.
+    ///
+    /// By default, every free cell may be taken. Players should only consider these
+    /// placements instead of trying every cell.
+    fn get_legal_moves(&self, board: &GameState) -> Vec<PointPlacement> {
+        board
+            .iter_2d()
+            .filter(|(_, cell)| cell.is_free())
+            .map(|(pp, _)| pp)
+            .collect()
+    }
+
+    /// Returns whether the player who placed the last stone on `board` has finished
+    /// their turn.
+    ///
+    /// By default, every turn consists of a single stone. Otherwise, the same player
+    /// has to move again.
+    fn is_turn_over(&self, _board: &GameState) -> bool {
+        true
+    }
+
+    /// Returns the player who has won on `board`, if any.
+    ///
+    /// This works for any number of players. By default, every stone is taken back and
+    /// placed again, until a placement is judged a victory.
+    fn get_winner(&self, board: &GameState) -> Option<PlayerID> {
+        let mut temporary_board = board.clone();
+        for (pp, cell) in board.iter_2d() {
+            let Some(player) =
+                Option::<PlayerID>::from(*cell).filter(|_| cell.has_stone())
+            else {
+                continue;
+            };
+            temporary_board[pp] = None.into();
+            let result = self.receive_move(&mut temporary_board, pp, player);
+            temporary_board[pp] = *cell;
+            if result == GameResult::Victory {
+                return Some(player);
+            }
+        }
+        None
+    }
+}
+
+/// Game state a search can play on and use as key of a `GameStateStorage`.
+///
+/// Implementors may keep additional information about the board up to date, e.g. a
+/// hash, as long as all changes go through `receive_move` and `set_entry`.
+pub trait SearchPosition: Clone + Eq + Hash {
+    fn from_game_state(board: &GameState) -> Self;
+    fn get_game_state(&self) -> &GameState;
+    fn receive_move(
+        &mut self,
+        referee: &dyn TicTacToeReferee,
+        placement: PointPlacement,
+        player: PlayerID,
+    ) -> GameResult;
+    fn set_entry(&mut self, placement: PointPlacement, entry: BoardStateEntry);
+}
+
+impl SearchPosition for GameState {
+    fn from_game_state(board: &GameState) -> Self {
+        board.clone()
+    }
+
+    fn get_game_state(&self) -> &GameState {
+        self
+    }
+
+    fn receive_move(
+        &mut self,
+        referee: &dyn TicTacToeReferee,
+        placement: PointPlacement,
+        player: PlayerID,
+    ) -> GameResult {
+        referee.receive_move(self, placement, player)
+    }
+
+    fn set_entry(&mut self, placement: PointPlacement, entry: BoardStateEntry) {
+        self[placement] = entry;
+    }
 }
 
 pub trait Player {
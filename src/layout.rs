@@ -0,0 +1,191 @@
+use crate::interfaces::{BoardSizeT, BoardStateEntry, GameState, PointPlacement};
+use anyhow::Context;
+use rand::Rng;
+use rand::seq::index;
+
+/// Outline of a board, outside of which all cells are blocked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Shape {
+    /// Plus-shaped board made of the middle thirds of the rows and columns.
+    Cross,
+    /// Rhombus touching the middle of every edge of the board.
+    Diamond,
+    #[default]
+    Rectangle,
+}
+
+impl Shape {
+    /// Returns whether the cell `pp` of a board of the given size belongs to the shape.
+    #[must_use]
+    pub fn contains(
+        self,
+        nrows: BoardSizeT,
+        ncolumns: BoardSizeT,
+        pp: PointPlacement,
+    ) -> bool {
+        match self {
+            Shape::Cross => {
+                let is_in_middle_third = |index: BoardSizeT, size: BoardSizeT| {
+                    index >= size / 3 && index < size - size / 3
+                };
+                is_in_middle_third(pp.row, nrows)
+                    || is_in_middle_third(pp.column, ncolumns)
+            }
+            Shape::Diamond => {
+                // Distances to the centre are doubled to stay with integers.
+                let row_distance =
+                    (2 * u32::from(pp.row)).abs_diff(u32::from(nrows) - 1);
+                let column_distance =
+                    (2 * u32::from(pp.column)).abs_diff(u32::from(ncolumns) - 1);
+                let row_radius = u32::from(nrows) - 1;
+                let column_radius = u32::from(ncolumns) - 1;
+                row_distance * column_radius + column_distance * row_radius
+                    <= row_radius * column_radius
+            }
+            Shape::Rectangle => true,
+        }
+    }
+
+    /// Returns an empty board of the given size with all cells outside of the shape
+    /// blocked.
+    #[must_use]
+    pub fn build(self, nrows: BoardSizeT, ncolumns: BoardSizeT) -> GameState {
+        let mut board = GameState::new(nrows, ncolumns, None);
+        for row in 0..nrows {
+            for column in 0..ncolumns {
+                let pp = PointPlacement { row, column };
+                if !self.contains(nrows, ncolumns, pp) {
+                    board[pp] = BoardStateEntry::BLOCKED;
+                }
+            }
+        }
+        board
+    }
+}
+
+/// Parses a board from a text mask, with one line per row.
+///
+/// Free cells are given by `.`, blocked cells by `#` and stones by the ID of their
+/// player, which must be a single digit. This is the format boards are printed in.
+/// Leading and trailing blank lines as well as whitespace at the end of lines are
+/// ignored.
+///
+/// # Errors
+///
+/// Returns an error if the mask contains other characters or if it is not a
+/// non-empty rectangle.
+///
+/// # Examples
+///
+/// ```rust
+/// # use tic_tac_toe_mcts::layout::parse_mask;
+/// let board = parse_mask(".#.\n#0#\n.#.")?;
+/// assert_eq!(board.get_number_of_rows(), 3);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn parse_mask(mask: &str) -> anyhow::Result<GameState> {
+    let rows = mask
+        .trim_matches(|c| c == '\n' || c == '\r')
+        .lines()
+        .enumerate()
+        .map(|(row, line)| {
+            line.trim_end()
+                .chars()
+                .map(|c| match c {
+                    '.' => Ok(BoardStateEntry::from(None)),
+                    '#' => Ok(BoardStateEntry::BLOCKED),
+                    _ => c
+                        .to_digit(10)
+                        .and_then(|id| u16::try_from(id).ok())
+                        .map(|id| Some(id).into())
+                        .with_context(|| {
+                            format!("Invalid character '{c}' in row {row} of the mask!")
+                        }),
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    GameState::new_with_values(rows)
+}
+
+/// Blocks `nobstacles` free cells of `board`, chosen uniformly at random.
+///
+/// # Errors
+///
+/// Returns an error if `board` has less than `nobstacles` free cells.
+pub fn block_random_cells<R: Rng + ?Sized>(
+    board: &mut GameState,
+    nobstacles: usize,
+    rng: &mut R,
+) -> anyhow::Result<()> {
+    let free_cells: Vec<_> = board
+        .iter_2d()
+        .filter(|(_, cell)| cell.is_free())
+        .map(|(pp, _)| pp)
+        .collect();
+    if free_cells.len() < nobstacles {
+        anyhow::bail!(
+            "Cannot place {nobstacles} obstacles on a board with {} free cells!",
+            free_cells.len()
+        );
+    }
+    for idx in index::sample(rng, free_cells.len(), nobstacles) {
+        board[free_cells[idx]] = BoardStateEntry::BLOCKED;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rng;
+    use rstest::*;
+
+    #[rstest]
+    #[case(Shape::Rectangle, 3, 3, "...\n...\n...")]
+    #[case(Shape::Diamond, 5, 5, "##.##\n#...#\n.....\n#...#\n##.##")]
+    #[case(Shape::Diamond, 3, 5, "##.##\n.....\n##.##")]
+    #[case(Shape::Cross, 3, 3, "#.#\n...\n#.#")]
+    #[case(Shape::Cross, 6, 6, "##..##\n##..##\n......\n......\n##..##\n##..##")]
+    fn shapes_are_built_correctly(
+        #[case] shape: Shape,
+        #[case] nrows: BoardSizeT,
+        #[case] ncolumns: BoardSizeT,
+        #[case] mask: &str,
+    ) {
+        assert_eq!(shape.build(nrows, ncolumns), parse_mask(mask).unwrap());
+    }
+
+    #[test]
+    fn masks_are_parsed_like_boards_are_printed() {
+        let mask = "0.#\n#.1\n";
+        let board = parse_mask(mask).unwrap();
+        assert!(board[PointPlacement { row: 0, column: 2 }].is_blocked());
+        assert_eq!(board[PointPlacement { row: 1, column: 2 }], Some(1).into());
+        assert_eq!(board.to_string(), mask);
+    }
+
+    #[test]
+    fn only_stones_of_players_are_stones() {
+        let board = parse_mask("0.#").unwrap();
+        let has_stone: Vec<_> =
+            board.iter_2d().map(|(_, cell)| cell.has_stone()).collect();
+        assert_eq!(has_stone, [true, false, false]);
+    }
+
+    #[rstest]
+    #[case("..\n.x")]
+    #[case("..\n.")]
+    #[case("")]
+    fn invalid_masks_are_rejected(#[case] mask: &str) {
+        assert!(parse_mask(mask).is_err());
+    }
+
+    #[test]
+    fn obstacles_are_placed_on_free_cells_only() {
+        let mut board = parse_mask("0#..\n....").unwrap();
+        block_random_cells(&mut board, 6, &mut rng()).unwrap();
+        assert_eq!(board, parse_mask("0###\n####").unwrap());
+        assert!(block_random_cells(&mut board, 1, &mut rng()).is_err());
+    }
+}
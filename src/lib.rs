@@ -1,3 +1,14 @@
+pub mod arena;
+pub mod bitboard;
 pub mod board;
+pub mod book;
+pub mod config;
+pub mod database;
+pub mod game_state_storage;
 pub mod interfaces;
+pub mod layout;
+pub mod player;
 pub mod referee;
+pub mod solver;
+pub mod tournament;
+pub mod zobrist;
@@ -1,53 +1,796 @@
-// use crate::player::countboundmcts::*;
-// use crate::player::onelookahead::*;
-use crate::game_state_storage::NaiveGameStateStorage;
-use arena::exploiting::ExploitingArena;
-use interfaces::{
-    BoardSizeT, Evaluation, GameResult, GameState, TicTacToeArena, WinLengthT,
+use anyhow::Context;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, value_parser};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fs;
+use std::path::PathBuf;
+use tic_tac_toe_mcts::book::OpeningBook;
+use tic_tac_toe_mcts::config::{
+    ArenaSpec, PlayerResources, PlayerSpec, RefereeSpec, RuleSpec,
 };
-use player::minmax::MinMaxPlayer;
-use referee::NaiveReferee;
-
-mod arena;
-mod board;
-mod game_state_storage;
-mod interfaces;
-mod player;
-mod referee;
-
-fn main() {
-    const N: BoardSizeT = 7;
-    const K: WinLengthT = 4;
-    let referee = NaiveReferee::new(K);
-
-    // let mut mcts_base_player0 = OneLookaheadPlayer::new(1, Box::new(NaiveReferee::<K> {}), 0);
-    // let mut mcts_base_player1 = OneLookaheadPlayer::new(0, Box::new(NaiveReferee::<K> {}), 1);
-    // let mut player0 = CountBoundMCTSPlayer::<K>::new(
-    //     0,
-    //     (N * N * 10000) as WinLengthT,
-    //     &mut mcts_base_player0,
-    //     &mut mcts_base_player1,
-    //     &mut mcts_referee,
-    // );
-    let mut player0 = MinMaxPlayer::new(
-        4,
-        1,
-        NaiveGameStateStorage::<_, Evaluation>::new(),
-        &referee,
-        0,
+use tic_tac_toe_mcts::database::Database;
+use tic_tac_toe_mcts::interfaces::{
+    BoardSizeT, GameResult, GameState, Player, PlayerID, TicTacToeReferee, WinLengthT,
+};
+use tic_tac_toe_mcts::layout::{Shape, block_random_cells, parse_mask};
+use tic_tac_toe_mcts::player::book::BookPlayer;
+use tic_tac_toe_mcts::referee::{
+    Grid, RuleSet, Topology, TurnSchedule, UltimateState, get_cells_per_dimension,
+    get_embedding_size, ultimate_referee,
+};
+use tic_tac_toe_mcts::tournament::{Tournament, TournamentConfig};
+
+fn build_cli() -> Command {
+    Command::new("tic-tac-toe-mcts")
+        .about("Let two players compete in a game of k-in-a-row.")
+        .arg(
+            Arg::new("rows")
+                .long("rows")
+                .global(true)
+                .help("Number of rows of the board")
+                .value_parser(value_parser!(BoardSizeT).range(1..))
+                .default_value("7"),
+        )
+        .arg(
+            Arg::new("columns")
+                .long("columns")
+                .global(true)
+                .help("Number of columns of the board")
+                .value_parser(value_parser!(BoardSizeT).range(1..))
+                .default_value("7"),
+        )
+        .args(build_board_args())
+        .args(build_rule_args())
+        .arg(
+            Arg::new("referee")
+                .long("referee")
+                .global(true)
+                .help(format!(
+                    "Referee implementation [{}]",
+                    RefereeSpec::VARIANTS.join(", ")
+                ))
+                .value_parser(str::parse::<RefereeSpec>)
+                .default_value("naive"),
+        )
+        .arg(
+            Arg::new("arena")
+                .long("arena")
+                .global(true)
+                .help(format!(
+                    "Arena implementation [{}]",
+                    ArenaSpec::VARIANTS.join(", ")
+                ))
+                .value_parser(str::parse::<ArenaSpec>)
+                .default_value("exploiting"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .global(true)
+                .help("Seed of all random decisions, chosen at random if not given")
+                .value_parser(value_parser!(u64)),
+        )
+        .args(build_file_args())
+        .arg(
+            Arg::new("player0")
+                .long("player0")
+                .help(format!(
+                    "Player with ID 0 [{}]",
+                    PlayerSpec::VARIANTS.join(", ")
+                ))
+                .value_parser(str::parse::<PlayerSpec>)
+                .default_value("minmax:4"),
+        )
+        .arg(
+            Arg::new("player1")
+                .long("player1")
+                .help("Player with ID 1, see --player0")
+                .value_parser(str::parse::<PlayerSpec>)
+                .default_value("minmax:4"),
+        )
+        .arg(
+            Arg::new("player2")
+                .long("player2")
+                .help("Optional third player with ID 2, see --player0")
+                .value_parser(str::parse::<PlayerSpec>),
+        )
+        .arg(
+            Arg::new("player3")
+                .long("player3")
+                .help("Optional fourth player with ID 3, see --player0")
+                .value_parser(str::parse::<PlayerSpec>)
+                .requires("player2"),
+        )
+        .arg(
+            Arg::new("starting-player")
+                .long("starting-player")
+                .help("ID of the player making the first move")
+                .value_parser(value_parser!(PlayerID).range(0..=3))
+                .default_value("0"),
+        )
+        .subcommand(build_tournament_command())
+        .subcommand(build_database_command())
+        .subcommand(build_book_command())
+}
+
+/// Returns the arguments giving files players look up their moves in.
+fn build_file_args() -> [Arg; 2] {
+    [
+        Arg::new("book")
+            .long("book")
+            .global(true)
+            .help("Opening book consulted by all but human players")
+            .value_parser(value_parser!(PathBuf)),
+        Arg::new("database")
+            .long("database")
+            .help("Database oracle players look up their moves in, see 'database'")
+            .value_parser(value_parser!(PathBuf)),
+    ]
+}
+
+/// Returns the arguments selecting the layout of the board, besides its size.
+fn build_board_args() -> [Arg; 3] {
+    [
+        Arg::new("shape")
+            .long("shape")
+            .help(format!(
+                "Shape of the board, cells outside of it are blocked [{}]",
+                Shape::VARIANTS.join(", ")
+            ))
+            .value_parser(str::parse::<Shape>)
+            .default_value("rectangle"),
+        Arg::new("mask")
+            .long("mask")
+            .help(
+                "File with a text mask of the board, with '.' for free cells, '#' for \
+                 blocked cells and player IDs for stones. Replaces --rows, --columns \
+                 and --shape",
+            )
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with("shape"),
+        Arg::new("obstacles")
+            .long("obstacles")
+            .help("Number of free cells to block at random")
+            .value_parser(value_parser!(usize))
+            .default_value("0"),
+    ]
+}
+
+/// Returns the arguments selecting the rules of the game.
+fn build_rule_args() -> [Arg; 11] {
+    [
+        Arg::new("winning-length")
+            .long("winning-length")
+            .global(true)
+            .short('k')
+            .help("Number of stones in a row needed to win")
+            .value_parser(value_parser!(WinLengthT).range(1..))
+            .default_value("4"),
+        Arg::new("rule-set")
+            .long("rule-set")
+            .global(true)
+            .help(format!(
+                "Rules deciding which lines win [{}]",
+                RuleSet::VARIANTS.join(", ")
+            ))
+            .value_parser(str::parse::<RuleSet>)
+            .default_value("freestyle"),
+        Arg::new("dimensions")
+            .long("dimensions")
+            .global(true)
+            .help(
+                "Number of dimensions of the board, which has --rows cells in each. \
+                 Layers of 3-D boards are shown side by side",
+            )
+            .value_parser(value_parser!(u32).range(1..=8))
+            .default_value("2"),
+        Arg::new("grid")
+            .long("grid")
+            .global(true)
+            .help(format!(
+                "Arrangement of the cells, rows of a hex grid are shifted by half a cell \
+                 each [{}]",
+                Grid::VARIANTS.join(", ")
+            ))
+            .value_parser(str::parse::<Grid>)
+            .default_value("square"),
+        Arg::new("connect-edges")
+            .long("connect-edges")
+            .global(true)
+            .help(
+                "Win by connecting the own pair of opposite edges as in Hex, the \
+                 starting player connects top and bottom",
+            )
+            .action(ArgAction::SetTrue),
+        Arg::new("gravity")
+            .long("gravity")
+            .global(true)
+            .help("Let stones fall down to the lowest free cell of their column")
+            .action(ArgAction::SetTrue),
+        Arg::new("misere")
+            .long("misere")
+            .global(true)
+            .help("Let the player completing a line lose instead of win")
+            .action(ArgAction::SetTrue),
+        Arg::new("torus")
+            .long("torus")
+            .global(true)
+            .help("Let lines wrap around the edges of the board")
+            .action(ArgAction::SetTrue),
+        Arg::new("ultimate")
+            .long("ultimate")
+            .global(true)
+            .help(
+                "Play ultimate tic-tac-toe on 3x3 small boards of 3x3 cells, the cell \
+                 played picks the small board of the next move",
+            )
+            .action(ArgAction::SetTrue),
+        Arg::new("first-turn-stones")
+            .long("first-turn-stones")
+            .global(true)
+            .help("Number of stones placed on the first turn of the game")
+            .value_parser(value_parser!(u16).range(1..))
+            .default_value("1"),
+        Arg::new("stones-per-turn")
+            .long("stones-per-turn")
+            .global(true)
+            .help("Number of stones placed on every turn but the first")
+            .value_parser(value_parser!(u16).range(1..))
+            .default_value("1"),
+    ]
+}
+
+fn build_tournament_command() -> Command {
+    Command::new("tournament")
+        .about("Play a round-robin tournament and compute Elo ratings.")
+        .arg(
+            Arg::new("participant")
+                .long("participant")
+                .short('p')
+                .help(format!(
+                    "Participant of the tournament, given at least twice [{}]",
+                    PlayerSpec::VARIANTS.join(", ")
+                ))
+                .value_parser(str::parse::<PlayerSpec>)
+                .action(ArgAction::Append)
+                .required(true),
+        )
+        .arg(
+            Arg::new("games")
+                .long("games")
+                .help("Number of games per pairing")
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("10"),
+        )
+}
+
+fn build_database_command() -> Command {
+    Command::new("database")
+        .about(
+            "Compute the values of all positions reachable from the board by \
+             retrograde analysis and save them, or look up the board in a saved \
+             database.",
+        )
+        .args(build_board_args())
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("File to write the database to")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("input")
+                .long("input")
+                .short('i')
+                .help("Database to look up the board in, e.g. one given by --mask")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .group(
+            ArgGroup::new("file")
+                .args(["output", "input"])
+                .required(true),
+        )
+}
+
+fn build_book_command() -> Command {
+    Command::new("book")
+        .about(
+            "Build an opening book from searches or self-play games, extending the \
+             book if it exists.",
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("File to write the book to")
+                .value_parser(value_parser!(PathBuf))
+                .required(true),
+        )
+        .arg(
+            Arg::new("method")
+                .long("method")
+                .help(
+                    "Follow the best moves of every position or record the moves of \
+                     whole games",
+                )
+                .value_parser(["search", "self-play"])
+                .default_value("search"),
+        )
+        .arg(
+            Arg::new("player")
+                .long("player")
+                .help(format!(
+                    "Player choosing the moves [{}]",
+                    PlayerSpec::VARIANTS.join(", ")
+                ))
+                .value_parser(str::parse::<PlayerSpec>)
+                .default_value("minmax:4"),
+        )
+        .arg(
+            Arg::new("plies")
+                .long("plies")
+                .help("Number of moves recorded from the start of the game")
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("4"),
+        )
+        .arg(
+            Arg::new("width")
+                .long("width")
+                .help("Number of moves followed per position when searching")
+                .value_parser(value_parser!(usize))
+                .default_value("2"),
+        )
+        .arg(
+            Arg::new("games")
+                .long("games")
+                .help("Number of self-play games, see --arena")
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("100"),
+        )
+}
+
+fn get_arg<T: Clone + Send + Sync + 'static>(matches: &ArgMatches, id: &str) -> T {
+    matches
+        .get_one::<T>(id)
+        .expect("Argument has a default value.")
+        .clone()
+}
+
+fn run_tournament(matches: &ArgMatches) -> anyhow::Result<()> {
+    let (nrows, ncolumns) = get_board_size(matches)?;
+    let config = TournamentConfig {
+        arena_spec: get_arg(matches, "arena"),
+        ncolumns,
+        ngames_per_pairing: get_arg(matches, "games"),
+        nrows,
+        referee_spec: get_arg(matches, "referee"),
+        rules: get_rules(matches)?,
+        seed: get_seed(matches),
+    };
+    println!("Seed: {}", config.seed);
+    let participants: Vec<PlayerSpec> = matches
+        .get_many::<PlayerSpec>("participant")
+        .expect("Participants are required.")
+        .copied()
+        .collect();
+    let tournament =
+        Tournament::new_with_book(load_book(matches)?, config, participants.clone())?;
+    let results = tournament.run(|record| {
+        let outcome = match record.winner {
+            Some(winner) => format!("#{winner} won"),
+            None => "draw".to_string(),
+        };
+        println!(
+            "#{} {} vs. #{} {} (#{} started, seed {}): {outcome}",
+            record.first,
+            participants[record.first],
+            record.second,
+            participants[record.second],
+            record.starting,
+            record.seed,
+        );
+    });
+    println!();
+    println!("{results}");
+    Ok(())
+}
+
+fn generate_database(matches: &ArgMatches) -> anyhow::Result<()> {
+    let rules = get_rules(matches)?;
+    rules.check()?;
+    if !rules.fits_database() {
+        anyhow::bail!("Databases only support turns of a single plain stone!");
+    }
+    let seed = get_seed(matches);
+    println!("Seed: {seed}");
+    let board = get_board(matches, &mut StdRng::seed_from_u64(seed))?;
+    let referee = get_arg::<RefereeSpec>(matches, "referee").build(rules);
+    if let Some(path) = matches.get_one::<PathBuf>("input") {
+        return query_database(&Database::load(path)?, &board, &*referee);
+    }
+    let database = Database::generate(&board, &*referee, [0, 1])?;
+    let path: PathBuf = get_arg(matches, "output");
+    database.save(&path)?;
+    println!(
+        "Saved {} positions to '{}'.",
+        database.len(),
+        path.display()
     );
-    let mut player1 = MinMaxPlayer::new(
-        4,
-        0,
-        NaiveGameStateStorage::<_, Evaluation>::new(),
-        &referee,
-        1,
+    if let Some(entry) = database.lookup(&board) {
+        println!(
+            "Player 0 moving first: {} after {} moves.",
+            entry.result, entry.distance
+        );
+    }
+    Ok(())
+}
+
+/// Prints the value of `board` for the player to move and the moves keeping it.
+fn query_database(
+    database: &Database,
+    board: &GameState,
+    referee: &dyn TicTacToeReferee,
+) -> anyhow::Result<()> {
+    let (Some(player), Some(entry)) =
+        (database.get_player_to_move(board), database.lookup(board))
+    else {
+        anyhow::bail!("Database does not know the board or the game is over!");
+    };
+    println!(
+        "Player {player} to move: {} after {} moves.",
+        entry.result, entry.distance
+    );
+    for pp in database.get_best_moves(board, referee) {
+        println!("Best move: {pp}");
+    }
+    Ok(())
+}
+
+/// Returns the database given by `--database`, if any.
+fn load_database(matches: &ArgMatches) -> anyhow::Result<Option<Database>> {
+    matches
+        .get_one::<PathBuf>("database")
+        .map(|path| Database::load(path))
+        .transpose()
+}
+
+fn generate_book(matches: &ArgMatches) -> anyhow::Result<()> {
+    const PLAYER_IDS: [PlayerID; 2] = [0, 1];
+    if matches.get_flag("ultimate") {
+        // Stones of ultimate tic-tac-toe also tell their move number, which the text
+        // format of books cannot store.
+        anyhow::bail!("Opening books do not support ultimate tic-tac-toe!");
+    }
+    let path: PathBuf = get_arg(matches, "output");
+    let mut book = if path.exists() {
+        OpeningBook::load(&path)?
+    } else {
+        OpeningBook::new()
+    };
+    let rules = get_rules(matches)?;
+    rules.check()?;
+    let spec: PlayerSpec = get_arg(matches, "player");
+    if !spec.supports_rules(rules) {
+        anyhow::bail!("Player '{spec}' does not support the rules of the game!");
+    }
+    let (nrows, ncolumns) = get_board_size(matches)?;
+    let board = GameState::new(nrows, ncolumns, None);
+    if !spec.supports_board(&board) {
+        anyhow::bail!("Player '{spec}' does not support the board!");
+    }
+    let referee_spec: RefereeSpec = get_arg(matches, "referee");
+    let nplies: u32 = get_arg(matches, "plies");
+    let ngames: u32 = get_arg(matches, "games");
+    let seed = get_seed(matches);
+    println!("Seed: {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+    for starting_player in PLAYER_IDS {
+        let rules = RuleSpec {
+            first_player: starting_player,
+            ..rules
+        };
+        let resources_seed = rng.random();
+        let mut all_resources: Vec<_> = PLAYER_IDS
+            .iter()
+            .map(|&id| {
+                PlayerResources::new_with_seed(
+                    &PLAYER_IDS,
+                    referee_spec,
+                    rules,
+                    resources_seed,
+                    id,
+                )
+            })
+            .collect();
+        let mut players: Vec<_> = all_resources
+            .iter_mut()
+            .map(|resources| resources.build_player(spec))
+            .collect();
+        let mut players: Vec<_> = players
+            .iter_mut()
+            .map(|player| &mut **player as &mut dyn Player)
+            .collect();
+        let mut referee = referee_spec.build(rules);
+        if get_arg::<String>(matches, "method") == "search" {
+            players.rotate_left(usize::from(starting_player));
+            book.add_searches(
+                &board,
+                &mut players,
+                &*referee,
+                nplies,
+                get_arg(matches, "width"),
+            );
+            continue;
+        }
+        // Both starting players get half of the games.
+        let ngames = (ngames + 1 - u32::from(starting_player)) / 2;
+        for _ in 0..ngames {
+            let mut arena = get_arg::<ArenaSpec>(matches, "arena").build(
+                board.clone(),
+                players
+                    .iter_mut()
+                    .map(|player| &mut **player as &mut dyn Player)
+                    .collect(),
+                &mut *referee,
+                StdRng::from_rng(&mut rng),
+                starting_player,
+            );
+            book.add_game(&mut *arena, nplies);
+        }
+    }
+    book.save(&path)?;
+    println!("Saved {} positions to '{}'.", book.len(), path.display());
+    Ok(())
+}
+
+/// Returns the seed given by `--seed` or a random one.
+fn get_seed(matches: &ArgMatches) -> u64 {
+    matches
+        .get_one::<u64>("seed")
+        .copied()
+        .unwrap_or_else(rand::random)
+}
+
+/// Returns the opening book given by `--book`, if any.
+fn load_book(matches: &ArgMatches) -> anyhow::Result<Option<OpeningBook>> {
+    let Some(path) = matches.get_one::<PathBuf>("book") else {
+        return Ok(None);
+    };
+    if matches.get_flag("ultimate") {
+        anyhow::bail!("Opening books do not support ultimate tic-tac-toe!");
+    }
+    OpeningBook::load(path).map(Some)
+}
+
+fn get_rules(matches: &ArgMatches) -> anyhow::Result<RuleSpec> {
+    let topology = if matches.get_flag("torus") {
+        Topology::Torus
+    } else {
+        Topology::Bounded
+    };
+    let rules = RuleSpec {
+        connect_edges: matches.get_flag("connect-edges"),
+        dimensions: get_arg(matches, "dimensions"),
+        gravity: matches.get_flag("gravity"),
+        grid: get_arg(matches, "grid"),
+        misere: matches.get_flag("misere"),
+        rule_set: get_arg(matches, "rule-set"),
+        topology,
+        turn_schedule: TurnSchedule::new(
+            get_arg(matches, "first-turn-stones"),
+            get_arg(matches, "stones-per-turn"),
+        ),
+        ultimate: matches.get_flag("ultimate"),
+        ..RuleSpec::new(get_arg(matches, "winning-length"))
+    };
+    rules.check()?;
+    Ok(rules)
+}
+
+/// Returns the number of rows and columns of the board, which embeds all of its
+/// dimensions into two and is fixed for ultimate tic-tac-toe.
+fn get_board_size(matches: &ArgMatches) -> anyhow::Result<(BoardSizeT, BoardSizeT)> {
+    if matches.get_flag("ultimate") {
+        return Ok((ultimate_referee::BOARD_SIZE, ultimate_referee::BOARD_SIZE));
+    }
+    let dimensions: u32 = get_arg(matches, "dimensions");
+    let nrows: BoardSizeT = get_arg(matches, "rows");
+    if dimensions == 2 {
+        return Ok((nrows, get_arg(matches, "columns")));
+    }
+    get_embedding_size(dimensions, nrows).context("Board has too many cells!")
+}
+
+/// Returns the board to start the game on, placing obstacles with a generator seeded
+/// with `seed`.
+fn get_board(matches: &ArgMatches, rng: &mut StdRng) -> anyhow::Result<GameState> {
+    let dimensions: u32 = get_arg(matches, "dimensions");
+    let (nrows, ncolumns) = get_board_size(matches)?;
+    let mut board = if let Some(path) = matches.get_one::<PathBuf>("mask") {
+        if matches.get_flag("ultimate") {
+            anyhow::bail!("Ultimate tic-tac-toe cannot start from a mask!");
+        }
+        if dimensions != 2 {
+            anyhow::bail!("Only boards of two dimensions can be given by a mask!");
+        }
+        let mask = fs::read_to_string(path)
+            .with_context(|| format!("Could not read mask '{}'!", path.display()))?;
+        parse_mask(&mask)?
+    } else if dimensions == 2 {
+        get_arg::<Shape>(matches, "shape").build(nrows, ncolumns)
+    } else {
+        if matches.value_source("shape") == Some(ValueSource::CommandLine) {
+            anyhow::bail!("Only boards of two dimensions can be shaped!");
+        }
+        GameState::new(nrows, ncolumns, None)
+    };
+    block_random_cells(&mut board, get_arg(matches, "obstacles"), rng)?;
+    Ok(board)
+}
+
+/// Returns `board` as text, with blocks of `size` rows and columns set apart.
+fn format_blocks(board: &GameState, size: usize) -> String {
+    let mut text = String::new();
+    for (row, line) in board.to_string().lines().enumerate() {
+        if row > 0 && row % size == 0 {
+            text.push('\n');
+        }
+        for (column, cell) in line.chars().enumerate() {
+            if column > 0 && column % size == 0 {
+                text.push(' ');
+            }
+            text.push(cell);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Returns `board` as text, with the rows of a hex grid shifted by half a cell each,
+/// the layers of boards of more than two dimensions and the small boards of ultimate
+/// tic-tac-toe set apart.
+fn format_board(board: &GameState, rules: &RuleSpec) -> String {
+    match rules.grid {
+        Grid::Square if rules.ultimate => format_blocks(
+            &UltimateState::new(board).to_plain_board(),
+            usize::from(ultimate_referee::SMALL_BOARD_SIZE),
+        ),
+        Grid::Square if rules.dimensions > 2 => format_blocks(
+            board,
+            usize::from(get_cells_per_dimension(rules.dimensions, board)),
+        ),
+        Grid::Hex => {
+            let mut text = String::new();
+            for (row, line) in board.to_string().lines().enumerate() {
+                let cells: Vec<_> = line.chars().map(String::from).collect();
+                text.push_str(&" ".repeat(row));
+                text.push_str(&cells.join(" "));
+                text.push('\n');
+            }
+            text
+        }
+        Grid::Square => board.to_string(),
+    }
+}
+
+/// Returns the specs of all players taking part, ordered by their IDs.
+fn get_player_specs(matches: &ArgMatches) -> anyhow::Result<Vec<PlayerSpec>> {
+    let player_specs: Vec<PlayerSpec> = ["player0", "player1", "player2", "player3"]
+        .into_iter()
+        .filter_map(|id| matches.get_one::<PlayerSpec>(id).copied())
+        .collect();
+    if player_specs.len() > 2
+        && let Some(spec) = player_specs
+            .iter()
+            .find(|spec| !spec.supports_multiple_opponents())
+    {
+        anyhow::bail!("Player '{spec}' does not support more than one opponent!");
+    }
+    let rules = get_rules(matches)?;
+    if let Some(spec) = player_specs.iter().find(|spec| !spec.supports_rules(rules)) {
+        anyhow::bail!("Player '{spec}' does not support the rules of the game!");
+    }
+    Ok(player_specs)
+}
+
+/// Returns the resources of all players, letting oracle players look up their moves
+/// in the database given by `--database`.
+fn build_all_resources(
+    matches: &ArgMatches,
+    player_ids: &[PlayerID],
+    referee_spec: RefereeSpec,
+    rules: RuleSpec,
+    seed: u64,
+) -> anyhow::Result<Vec<PlayerResources>> {
+    let database = load_database(matches)?;
+    Ok(player_ids
+        .iter()
+        .map(|&id| {
+            let mut resources = PlayerResources::new_with_seed(
+                player_ids,
+                referee_spec,
+                rules,
+                seed,
+                id,
+            );
+            if let Some(database) = &database {
+                resources.set_database(database.clone());
+            }
+            resources
+        })
+        .collect())
+}
+
+fn main() -> anyhow::Result<()> {
+    let matches = build_cli().get_matches();
+    if let Some(tournament_matches) = matches.subcommand_matches("tournament") {
+        return run_tournament(tournament_matches);
+    }
+    if let Some(database_matches) = matches.subcommand_matches("database") {
+        return generate_database(database_matches);
+    }
+    if let Some(book_matches) = matches.subcommand_matches("book") {
+        return generate_book(book_matches);
+    }
+    let seed = get_seed(&matches);
+    println!("Seed: {seed}");
+    // The arena draws from the first generator split off, like in tournaments, so
+    // that their games can be replayed.
+    let mut rng = StdRng::seed_from_u64(seed);
+    let arena_rng = StdRng::from_rng(&mut rng);
+    let board = get_board(&matches, &mut StdRng::from_rng(&mut rng))?;
+    let referee_spec: RefereeSpec = get_arg(&matches, "referee");
+    let arena_spec: ArenaSpec = get_arg(&matches, "arena");
+    let player_specs = get_player_specs(&matches)?;
+    if let Some(spec) = player_specs
+        .iter()
+        .find(|spec| !spec.supports_board(&board))
+    {
+        anyhow::bail!("Player '{spec}' does not support the board!");
+    }
+    let starting_player: PlayerID = get_arg(&matches, "starting-player");
+    let player_ids: Vec<PlayerID> = (0..).take(player_specs.len()).collect();
+    if !player_ids.contains(&starting_player) {
+        anyhow::bail!("There is no player with ID {starting_player}!");
+    }
+    let rules = RuleSpec {
+        first_player: starting_player,
+        ..get_rules(&matches)?
+    };
+
+    let book = load_book(&matches)?;
+    let book_referee = referee_spec.build(rules);
+    let mut all_resources =
+        build_all_resources(&matches, &player_ids, referee_spec, rules, seed)?;
+    // The generators are split off in the same order as in tournaments, so that their
+    // games can be replayed.
+    let mut players: Vec<_> = all_resources
+        .iter_mut()
+        .zip(player_specs)
+        .map(|(resources, spec)| {
+            let book_rng = resources.split_rng();
+            let player = resources.build_player(spec);
+            match &book {
+                Some(book) if spec != PlayerSpec::Human => Box::new(
+                    BookPlayer::new_with_rng(book, player, &*book_referee, book_rng),
+                ),
+                _ => player,
+            }
+        })
+        .collect();
+    let mut referee = referee_spec.build(rules);
+    let mut arena = arena_spec.build(
+        board,
+        players
+            .iter_mut()
+            .map(|player| &mut **player as &mut dyn Player)
+            .collect(),
+        &mut *referee,
+        arena_rng,
+        starting_player,
     );
-    // let mut player1 = CLIPlayer { id: 1 };
-    let mut referee = NaiveReferee::new(K);
-    let board = GameState::new(N, N, None);
-    let mut arena =
-        ExploitingArena::new(0, board, [&mut player1, &mut player0], &mut referee);
     loop {
         let (result, player_id, maybe_point_placement) = arena.do_next_move();
         println!(
@@ -58,7 +801,7 @@ fn main() {
             }
         );
         let board = arena.get_board();
-        println!("{board}");
+        println!("{}", format_board(&board, &rules));
         let maybe_result_msg: Option<String> = match result {
             GameResult::Defeat => Some(format!("Player {player_id} lost.")),
             GameResult::Victory => Some(format!("Player {player_id} won.")),
@@ -68,9 +811,422 @@ fn main() {
             }
             GameResult::Undecided => None,
         };
-        if maybe_result_msg.is_some() {
-            println!("{}", maybe_result_msg.unwrap());
+        if let Some(result_msg) = maybe_result_msg {
+            println!("{result_msg}");
             break;
         }
     }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use tic_tac_toe_mcts::interfaces::PointPlacement;
+    use tic_tac_toe_mcts::referee::NaiveReferee;
+
+    #[test]
+    fn cli_is_consistent() {
+        build_cli().debug_assert();
+    }
+
+    #[test]
+    fn arguments_are_parsed() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--rows",
+            "3",
+            "--columns",
+            "4",
+            "-k",
+            "3",
+            "--referee",
+            "incremental",
+            "--arena",
+            "exploring",
+            "--player0",
+            "uct:100",
+            "--player1",
+            "human",
+            "--starting-player",
+            "1",
+        ]);
+        assert_eq!(get_arg::<BoardSizeT>(&matches, "rows"), 3);
+        assert_eq!(get_arg::<BoardSizeT>(&matches, "columns"), 4);
+        assert_eq!(get_arg::<WinLengthT>(&matches, "winning-length"), 3);
+        assert_eq!(
+            get_arg::<RefereeSpec>(&matches, "referee"),
+            RefereeSpec::Incremental
+        );
+        assert_eq!(
+            get_arg::<ArenaSpec>(&matches, "arena"),
+            ArenaSpec::Exploring
+        );
+        assert_eq!(
+            get_arg::<PlayerSpec>(&matches, "player0"),
+            PlayerSpec::UCT { niterations: 100 }
+        );
+        assert_eq!(
+            get_arg::<PlayerSpec>(&matches, "player1"),
+            PlayerSpec::Human
+        );
+        assert_eq!(get_arg::<PlayerID>(&matches, "starting-player"), 1);
+    }
+
+    #[test]
+    fn tournament_arguments_are_parsed() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "tournament",
+            "--rows",
+            "3",
+            "-p",
+            "minmax:2",
+            "-p",
+            "guessing",
+            "--games",
+            "6",
+        ]);
+        let matches = matches.subcommand_matches("tournament").unwrap();
+        assert_eq!(get_arg::<BoardSizeT>(matches, "rows"), 3);
+        assert_eq!(get_arg::<u32>(matches, "games"), 6);
+        let participants: Vec<_> = matches
+            .get_many::<PlayerSpec>("participant")
+            .unwrap()
+            .copied()
+            .collect();
+        assert_eq!(
+            participants,
+            [PlayerSpec::MinMax { depth: 2 }, PlayerSpec::Guessing]
+        );
+    }
+
+    #[test]
+    fn book_arguments_are_parsed() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "book",
+            "--method",
+            "self-play",
+            "--player",
+            "uct:100",
+            "--plies",
+            "6",
+            "-o",
+            "book.txt",
+        ]);
+        let matches = matches.subcommand_matches("book").unwrap();
+        assert_eq!(get_arg::<String>(matches, "method"), "self-play");
+        assert_eq!(
+            get_arg::<PlayerSpec>(matches, "player"),
+            PlayerSpec::UCT { niterations: 100 }
+        );
+        assert_eq!(get_arg::<u32>(matches, "plies"), 6);
+        assert_eq!(
+            get_arg::<PathBuf>(matches, "output"),
+            PathBuf::from("book.txt")
+        );
+        assert!(
+            build_cli()
+                .try_get_matches_from(["tic-tac-toe-mcts", "book", "--method", "x"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn database_arguments_are_parsed() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--database",
+            "3x3.db",
+            "database",
+            "-i",
+            "3x3.db",
+        ]);
+        assert_eq!(
+            get_arg::<PathBuf>(&matches, "database"),
+            PathBuf::from("3x3.db")
+        );
+        let matches = matches.subcommand_matches("database").unwrap();
+        assert_eq!(
+            get_arg::<PathBuf>(matches, "input"),
+            PathBuf::from("3x3.db")
+        );
+        for args in [
+            &["tic-tac-toe-mcts", "database"][..],
+            &["tic-tac-toe-mcts", "database", "-i", "a.db", "-o", "b.db"],
+        ] {
+            assert!(build_cli().try_get_matches_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn databases_are_queried() {
+        let referee = NaiveReferee::new(3);
+        let board = GameState::new(3, 3, None);
+        let database = Database::generate(&board, &referee, [0, 1]).unwrap();
+        assert!(query_database(&database, &board, &referee).is_ok());
+        let lost_board = GameState::new_with_values([
+            [Some(0), Some(0), Some(0)],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ])
+        .unwrap();
+        assert!(query_database(&database, &lost_board, &referee).is_err());
+    }
+
+    #[test]
+    fn databases_are_generated_on_the_given_board() {
+        let path = std::env::temp_dir().join("databases_are_generated_on_the_board.db");
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "database",
+            "--rows",
+            "3",
+            "--columns",
+            "3",
+            "-k",
+            "3",
+            "--obstacles",
+            "2",
+            "--seed",
+            "0",
+            "-o",
+            path.to_str().unwrap(),
+        ]);
+        let matches = matches.subcommand_matches("database").unwrap();
+        generate_database(matches).unwrap();
+        let database = Database::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let board = get_board(matches, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert!(database.lookup(&board).is_some());
+        assert!(database.lookup(&GameState::new(3, 3, None)).is_none());
+    }
+
+    #[test]
+    fn books_are_rejected_for_ultimate() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "book",
+            "--ultimate",
+            "-o",
+            "ultimate.book",
+        ]);
+        let matches = matches.subcommand_matches("book").unwrap();
+        assert!(generate_book(matches).is_err());
+        assert!(!PathBuf::from("ultimate.book").exists());
+    }
+
+    #[test]
+    fn three_players_are_parsed() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--player0",
+            "maxn:2",
+            "--player1",
+            "human",
+            "--player2",
+            "guessing",
+        ]);
+        let player_specs = get_player_specs(&matches).unwrap();
+        assert_eq!(
+            player_specs,
+            [
+                PlayerSpec::MaxN { depth: 2 },
+                PlayerSpec::Human,
+                PlayerSpec::Guessing
+            ]
+        );
+    }
+
+    #[test]
+    fn gravity_is_parsed() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--rows",
+            "6",
+            "--gravity",
+            "--player0",
+            "alphabeta:4",
+            "--player1",
+            "uct:1000",
+        ]);
+        let rules = get_rules(&matches).unwrap();
+        assert!(rules.gravity);
+        assert_eq!(rules.winning_length, 4);
+        assert!(get_player_specs(&matches).is_ok());
+        assert!(
+            !get_rules(&build_cli().get_matches_from(["tic-tac-toe-mcts"]))
+                .unwrap()
+                .gravity
+        );
+    }
+
+    #[test]
+    fn misere_is_parsed() {
+        let matches = build_cli().get_matches_from(["tic-tac-toe-mcts", "--misere"]);
+        assert!(get_rules(&matches).unwrap().misere);
+        let matches = build_cli().get_matches_from(["tic-tac-toe-mcts"]);
+        assert!(!get_rules(&matches).unwrap().misere);
+    }
+
+    #[test]
+    fn torus_is_parsed() {
+        let matches = build_cli().get_matches_from(["tic-tac-toe-mcts", "--torus"]);
+        assert_eq!(get_rules(&matches).unwrap().topology, Topology::Torus);
+        let matches = build_cli().get_matches_from(["tic-tac-toe-mcts"]);
+        assert_eq!(get_rules(&matches).unwrap().topology, Topology::Bounded);
+    }
+
+    #[test]
+    fn turn_schedule_is_parsed() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--stones-per-turn",
+            "2",
+        ]);
+        assert_eq!(
+            get_rules(&matches).unwrap().turn_schedule,
+            TurnSchedule::new(1, 2)
+        );
+        let matches = build_cli().get_matches_from(["tic-tac-toe-mcts"]);
+        assert_eq!(
+            get_rules(&matches).unwrap().turn_schedule,
+            TurnSchedule::default()
+        );
+    }
+
+    #[test]
+    fn hex_is_parsed() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--grid",
+            "hex",
+            "--connect-edges",
+        ]);
+        let rules = get_rules(&matches).unwrap();
+        assert_eq!(rules.grid, Grid::Hex);
+        assert!(rules.connect_edges);
+        let matches =
+            build_cli().get_matches_from(["tic-tac-toe-mcts", "--connect-edges"]);
+        assert!(get_rules(&matches).is_err());
+    }
+
+    #[test]
+    fn hex_boards_are_shifted() {
+        let board = GameState::new(2, 2, None);
+        let rules = RuleSpec {
+            grid: Grid::Hex,
+            ..RuleSpec::new(3)
+        };
+        assert_eq!(format_board(&board, &rules), ". .\n . .\n");
+        assert_eq!(format_board(&board, &RuleSpec::new(3)), "..\n..\n");
+    }
+
+    #[test]
+    fn layers_are_set_apart() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--dimensions",
+            "4",
+            "--rows",
+            "2",
+        ]);
+        let rules = get_rules(&matches).unwrap();
+        let board = get_board(&matches, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(
+            format_board(&board, &rules),
+            ".. ..\n.. ..\n\n.. ..\n.. ..\n"
+        );
+    }
+
+    #[test]
+    fn ultimate_board_shows_small_boards() {
+        let matches = build_cli().get_matches_from(["tic-tac-toe-mcts", "--ultimate"]);
+        let rules = get_rules(&matches).unwrap();
+        let mut board = get_board(&matches, &mut StdRng::seed_from_u64(0)).unwrap();
+        board[PointPlacement { row: 4, column: 5 }] = UltimateState::encode_stone(1, 3);
+        let empty_row = "... ... ...\n";
+        let empty_block = empty_row.repeat(3);
+        assert_eq!(
+            format_board(&board, &rules),
+            format!(
+                "{empty_block}\n{empty_row}... ..1 ...\n{empty_row}\n{empty_block}"
+            )
+        );
+    }
+
+    #[test]
+    fn rule_set_is_parsed() {
+        let matches =
+            build_cli().get_matches_from(["tic-tac-toe-mcts", "--rule-set", "renju"]);
+        assert_eq!(get_rules(&matches).unwrap().rule_set, RuleSet::Renju);
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--rule-set",
+            "standard",
+            "--torus",
+        ]);
+        assert!(get_rules(&matches).is_err());
+    }
+
+    #[test]
+    fn shape_and_obstacles_are_applied() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--rows",
+            "5",
+            "--columns",
+            "5",
+            "--shape",
+            "diamond",
+            "--obstacles",
+            "3",
+        ]);
+        let board = get_board(&matches, &mut StdRng::seed_from_u64(0)).unwrap();
+        let nblocked = board
+            .iter_2d()
+            .filter(|(_, cell)| cell.is_blocked())
+            .count();
+        assert_eq!(nblocked, 12 + 3);
+
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--rows",
+            "2",
+            "--columns",
+            "2",
+            "--obstacles",
+            "5",
+        ]);
+        assert!(get_board(&matches, &mut StdRng::seed_from_u64(0)).is_err());
+    }
+
+    #[test]
+    fn bitboard_search_is_rejected_with_gravity() {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--gravity",
+            "--player0",
+            "bitboard-minmax:4",
+        ]);
+        assert!(get_player_specs(&matches).is_err());
+    }
+
+    #[rstest]
+    #[case("minmax:2")]
+    #[case("onelookahead")]
+    fn two_player_searches_are_rejected_in_three_player_games(#[case] spec: &str) {
+        let matches = build_cli().get_matches_from([
+            "tic-tac-toe-mcts",
+            "--player0",
+            spec,
+            "--player1",
+            "maxn:2",
+            "--player2",
+            "guessing",
+        ]);
+        assert!(get_player_specs(&matches).is_err());
+    }
 }
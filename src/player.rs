@@ -1,5 +1,15 @@
+pub mod alphabeta;
+pub mod bitboardminmax;
+pub mod book;
 pub mod cli;
 pub mod countboundmcts;
 pub mod guessing;
+pub mod maxn;
 pub mod minmax;
 pub mod onelookahead;
+pub mod oracle;
+pub mod parallel_uct;
+pub mod perfect;
+pub mod timeboundmcts;
+pub mod timeboundminmax;
+pub mod uct;
@@ -0,0 +1,433 @@
+use crate::game_state_storage::{GameStateStorage, NaiveGameStateStorage};
+use crate::interfaces::{
+    Evaluation, GameResult, GameState, Placement, Player, PlayerID, PointPlacement,
+    TicTacToeReferee,
+};
+use std::borrow::Cow;
+
+const DEFEAT: f32 = -1.0;
+const VICTORY: f32 = 1.0;
+const DRAW: f32 = 0.0;
+
+/// How a stored value relates to the true value of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The true value is at least the stored one.
+    Lower,
+    /// The true value is at most the stored one.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TranspositionEntry {
+    best_move: Option<PointPlacement>,
+    bound: Bound,
+    /// Remaining search depth the value was derived with.
+    depth: u32,
+    value: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    alpha: f32,
+    beta: f32,
+}
+
+impl Window {
+    fn negate(self) -> Self {
+        Self {
+            alpha: -self.beta,
+            beta: -self.alpha,
+        }
+    }
+}
+
+/// Variant of `MinMaxPlayer` using alpha-beta pruning.
+///
+/// The player evaluates positions exactly like `MinMaxPlayer` and finds the same best
+/// moves, but skips subtrees that cannot influence the result. To make cutoffs happen
+/// early, moves are ordered: the best move of a previous search of the position comes
+/// first, followed by moves next to many stones.
+///
+/// At the root, every move is searched with its own window that is just wide enough to
+/// determine whether it is at least as good as the best move found so far. Thus, the
+/// evaluations of the best moves are exact, while worse moves only get an upper bound.
+pub struct AlphaBetaPlayer<'player> {
+    max_depth: u32,
+    nnodes: u64,
+    other_id: PlayerID,
+    referee: &'player dyn TicTacToeReferee,
+    self_id: PlayerID,
+    transposition_table: NaiveGameStateStorage<GameState, TranspositionEntry>,
+}
+
+impl<'player> AlphaBetaPlayer<'player> {
+    #[must_use]
+    pub fn new(
+        max_depth: u32,
+        other_id: PlayerID,
+        referee: &'player dyn TicTacToeReferee,
+        self_id: PlayerID,
+    ) -> Self {
+        Self {
+            max_depth,
+            nnodes: 0,
+            other_id,
+            referee,
+            self_id,
+            transposition_table: NaiveGameStateStorage::new(),
+        }
+    }
+
+    /// Returns the number of positions visited by all searches so far.
+    #[must_use]
+    pub fn get_number_of_nodes(&self) -> u64 {
+        self.nnodes
+    }
+
+    fn get_evaluations(&mut self, board: &GameState) -> Evaluation {
+        let mut evaluation = Evaluation::new_from_existing(board, DEFEAT);
+        let mut temporary_board = board.clone();
+        let mut best = DEFEAT;
+        for pp in self.get_ordered_moves(board, None) {
+            // Any value below the best one so far is irrelevant, but ties are not.
+            let window = Window {
+                alpha: best.next_down(),
+                beta: VICTORY,
+            };
+            let value = self.evaluate_move(
+                &mut temporary_board,
+                pp,
+                self.self_id,
+                self.other_id,
+                self.max_depth,
+                window,
+            );
+            temporary_board[pp] = board[pp];
+            evaluation[pp] = value;
+            best = best.max(value);
+        }
+        evaluation
+    }
+
+    /// Plays `pp` for `self_id` and returns the value of the move.
+    ///
+    /// The stone is left on the board, so that the caller can take it back.
+    fn evaluate_move(
+        &mut self,
+        board: &mut GameState,
+        pp: PointPlacement,
+        self_id: PlayerID,
+        other_id: PlayerID,
+        max_depth: u32,
+        window: Window,
+    ) -> f32 {
+        self.nnodes += 1;
+        match self.referee.receive_move(board, pp, self_id) {
+            GameResult::Defeat | GameResult::IllegalMove => DEFEAT,
+            GameResult::Victory => VICTORY,
+            GameResult::Draw => DRAW,
+            GameResult::Undecided if max_depth <= 1 => DRAW,
+            GameResult::Undecided if self.referee.is_turn_over(board) => -self
+                .get_value(board, other_id, self_id, max_depth - 1, window.negate()),
+            GameResult::Undecided => {
+                self.get_value(board, self_id, other_id, max_depth - 1, window)
+            }
+        }
+    }
+
+    /// Returns the value of the best move of `self_id` on `board`.
+    ///
+    /// Values outside of `window` are bounds only: if the result is at most `alpha`,
+    /// the true value is at most the result, and if it is at least `beta`, the true
+    /// value is at least the result.
+    fn get_value(
+        &mut self,
+        board: &mut GameState,
+        self_id: PlayerID,
+        other_id: PlayerID,
+        max_depth: u32,
+        mut window: Window,
+    ) -> f32 {
+        let stored = self
+            .transposition_table
+            .get_payload(board, 0)
+            .map(Cow::into_owned);
+        // Shallower entries still provide a best move to search first.
+        if let Some(entry) = stored
+            && entry.depth >= max_depth
+        {
+            match entry.bound {
+                Bound::Exact => return entry.value,
+                Bound::Lower if entry.value >= window.beta => return entry.value,
+                Bound::Upper if entry.value <= window.alpha => return entry.value,
+                Bound::Lower => window.alpha = window.alpha.max(entry.value),
+                Bound::Upper => window.beta = window.beta.min(entry.value),
+            }
+        }
+
+        let original_window = window;
+        let mut best = DEFEAT;
+        let mut best_move = None;
+        for pp in self.get_ordered_moves(board, stored.and_then(|e| e.best_move)) {
+            let value =
+                self.evaluate_move(board, pp, self_id, other_id, max_depth, window);
+            board[pp] = None.into();
+            if best_move.is_none() || value > best {
+                best = value;
+                best_move = Some(pp);
+            }
+            window.alpha = window.alpha.max(value);
+            if window.alpha >= window.beta {
+                break;
+            }
+        }
+
+        let bound = if best <= original_window.alpha {
+            Bound::Upper
+        } else if best >= original_window.beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let entry = TranspositionEntry {
+            best_move,
+            bound,
+            depth: max_depth,
+            value: best,
+        };
+        self.transposition_table
+            .register_game_state(board, entry, max_depth);
+        best
+    }
+
+    /// Returns the legal moves on `board` in the order in which they should be searched.
+    fn get_ordered_moves(
+        &self,
+        board: &GameState,
+        first_move: Option<PointPlacement>,
+    ) -> Vec<PointPlacement> {
+        let mut moves: Vec<(PointPlacement, usize)> = self
+            .referee
+            .get_legal_moves(board)
+            .into_iter()
+            .map(|pp| (pp, count_neighbouring_stones(board, pp)))
+            .collect();
+        moves.sort_by_key(|&(pp, nneighbours)| {
+            (Some(pp) != first_move, std::cmp::Reverse(nneighbours))
+        });
+        moves.into_iter().map(|(pp, _)| pp).collect()
+    }
+
+    fn to_placement(evaluations: &Evaluation) -> Placement {
+        let max = evaluations
+            .iter_2d()
+            .map(|(_, val)| *val)
+            .fold(DEFEAT, f32::max);
+        // Direct comparsion is fine as float values are taken from the evaluations array.
+        #[allow(clippy::float_cmp)]
+        if max == DEFEAT {
+            return Placement::new_from_existing(evaluations, 1.0);
+        }
+
+        let mut placements = Placement::new_from_existing(evaluations, 0.0);
+        for (pp, eval) in evaluations.iter_2d() {
+            #[allow(clippy::float_cmp)]
+            if *eval == max {
+                placements[pp] = 1.0;
+            }
+        }
+        placements
+    }
+}
+
+fn count_neighbouring_stones(board: &GameState, pp: PointPlacement) -> usize {
+    let rows = pp.row.saturating_sub(1)..=pp.row.saturating_add(1);
+    rows.filter(|&row| row < board.get_number_of_rows())
+        .flat_map(|row| {
+            let columns = pp.column.saturating_sub(1)..=pp.column.saturating_add(1);
+            columns
+                .filter(|&column| column < board.get_number_of_columns())
+                .map(move |column| PointPlacement { row, column })
+        })
+        .filter(|&neighbour| board[neighbour].has_stone())
+        .count()
+}
+
+impl Player for AlphaBetaPlayer<'_> {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        assert!(self.max_depth > 0, "Lookahead must be at least 1!");
+        let evaluations = self.get_evaluations(board);
+        Self::to_placement(&evaluations)
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.self_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::minmax::MinMaxPlayer;
+    use crate::referee::*;
+    use rstest::*;
+
+    /// Counts the positions a full-width search without transpositions would visit.
+    fn count_full_width_nodes(
+        board: &mut GameState,
+        referee: &dyn TicTacToeReferee,
+        self_id: PlayerID,
+        other_id: PlayerID,
+        max_depth: u32,
+    ) -> u64 {
+        let free_cells: Vec<_> = board
+            .iter_2d()
+            .filter(|(_, cell)| cell.is_free())
+            .map(|(pp, _)| pp)
+            .collect();
+        let mut nnodes = 0;
+        for pp in free_cells {
+            nnodes += 1;
+            let result = referee.receive_move(board, pp, self_id);
+            if result == GameResult::Undecided && max_depth > 1 {
+                nnodes += count_full_width_nodes(
+                    board,
+                    referee,
+                    other_id,
+                    self_id,
+                    max_depth - 1,
+                );
+            }
+            board[pp] = None.into();
+        }
+        nnodes
+    }
+
+    #[rstest]
+    // direct winning moves
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(1), None, None, None],
+            [None, Some(0), None, None, None],
+            [None, None, Some(0), None, Some(0)],
+            [None, Some(0), None, None, Some(1)],
+            [None, Some(1), None, None, Some(1)],
+        ]
+    ).unwrap(),
+        Placement::new_with_values([
+            [1.0, 0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0, 0.0],
+        ]).unwrap(),
+        1
+    )]
+    // indirect winning moves
+    #[case(GameState::new_with_values(
+            [
+                [None, None, Some(0), Some(1)],
+                [None, Some(1), None, None],
+                [Some(0), None, None, None],
+                [Some(1), None, None, None]
+            ],
+
+        ).unwrap(),
+        Placement::new_with_values([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ]).unwrap(),
+        3
+    )]
+    fn correct_moves_are_found(
+        #[case] board: GameState,
+        #[case] expected: Placement,
+        #[case] lookahead: u32,
+    ) {
+        let referee = NaiveReferee::new(3);
+        let mut player = AlphaBetaPlayer::new(lookahead, 1, &referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case(GameState::new(4, 4, None), 3, 4)]
+    #[case(GameState::new_with_values(
+            [
+                [None, None, Some(0), Some(1)],
+                [None, Some(1), None, None],
+                [Some(0), None, None, None],
+                [Some(1), None, None, None]
+            ],
+
+        ).unwrap(), 3, 5)]
+    #[case(GameState::new_with_values(
+            [
+                [None, None, None, None, None],
+                [None, None, Some(1), None, None],
+                [None, Some(0), Some(0), None, None],
+                [None, None, Some(1), None, None],
+                [None, None, None, None, None],
+            ],
+
+        ).unwrap(), 3, 4)]
+    fn same_moves_as_minmax_with_fewer_nodes(
+        #[case] board: GameState,
+        #[case] winning_length: u16,
+        #[case] lookahead: u32,
+    ) {
+        let referee = NaiveReferee::new(winning_length);
+        let mut minmax_player = MinMaxPlayer::new(
+            lookahead,
+            1,
+            NaiveGameStateStorage::<GameState, _>::new(),
+            &referee,
+            0,
+        );
+        let mut alphabeta_player = AlphaBetaPlayer::new(lookahead, 1, &referee, 0);
+
+        let expected = minmax_player.do_move(&board);
+        let result = alphabeta_player.do_move(&board);
+        assert_eq!(result, expected);
+
+        let full_width_nodes =
+            count_full_width_nodes(&mut board.clone(), &referee, 0, 1, lookahead);
+        assert!(alphabeta_player.get_number_of_nodes() < full_width_nodes / 4);
+    }
+
+    #[rstest]
+    #[case(GameState::new(4, 4, None), 3)]
+    #[case(GameState::new_with_values(
+            [
+                [Some(0), None, None],
+                [None, None, None],
+                [Some(1), Some(1), None],
+            ],
+        ).unwrap(), 2)]
+    fn same_moves_as_minmax_with_several_stones_per_turn(
+        #[case] board: GameState,
+        #[case] lookahead: u32,
+    ) {
+        let referee = ConnectReferee::new(
+            Box::new(NaiveReferee::new(3)),
+            TurnSchedule::new(1, 2),
+        );
+        let mut minmax_player = MinMaxPlayer::new(
+            lookahead,
+            1,
+            NaiveGameStateStorage::<GameState, _>::new(),
+            &referee,
+            0,
+        );
+        let mut alphabeta_player = AlphaBetaPlayer::new(lookahead, 1, &referee, 0);
+
+        let expected = minmax_player.do_move(&board);
+        let result = alphabeta_player.do_move(&board);
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::bitboard::BitBoard;
+use crate::game_state_storage::{GameStateStorage, NaiveGameStateStorage};
+use crate::interfaces::{
+    Evaluation, GameResult, GameState, Placement, Player, PlayerID, PointPlacement,
+};
+use crate::referee::BitBoardReferee;
+
+const DEFEAT: f32 = -1.0;
+const VICTORY: f32 = 1.0;
+const DRAW: f32 = 0.0;
+
+/// Variant of `MinMaxPlayer` that searches on a `BitBoard`.
+///
+/// It finds the same moves as `MinMaxPlayer`, but neither clones nor hashes
+/// `GameState`s during the search. The given board is converted once per move, so it
+/// has to fit into a `BitBoard` and may only contain stones of the two players.
+pub struct BitBoardMinMaxPlayer {
+    max_depth: u32,
+    other_id: PlayerID,
+    game_state_storage: NaiveGameStateStorage<BitBoard, f32>,
+    referee: BitBoardReferee,
+    self_id: PlayerID,
+}
+
+impl BitBoardMinMaxPlayer {
+    #[must_use]
+    pub fn new(
+        max_depth: u32,
+        other_id: PlayerID,
+        game_state_storage: NaiveGameStateStorage<BitBoard, f32>,
+        referee: BitBoardReferee,
+        self_id: PlayerID,
+    ) -> Self {
+        Self {
+            max_depth,
+            other_id,
+            game_state_storage,
+            referee,
+            self_id,
+        }
+    }
+
+    fn get_evaluations(&mut self, board: &BitBoard) -> Evaluation {
+        let mut evaluation = Evaluation::new(
+            board.get_number_of_rows(),
+            board.get_number_of_columns(),
+            DEFEAT,
+        );
+        let mut temporary_board = *board;
+        for pp in board.iter_free_cells() {
+            evaluation[pp] = self.evaluate_move(
+                &mut temporary_board,
+                pp,
+                self.self_id,
+                self.other_id,
+                self.max_depth,
+            );
+            temporary_board.remove(pp);
+        }
+        evaluation
+    }
+
+    /// Returns the value of the best move of `self_id` on `board`.
+    fn get_value(
+        &mut self,
+        board: &mut BitBoard,
+        self_id: PlayerID,
+        other_id: PlayerID,
+        max_depth: u32,
+    ) -> f32 {
+        if let Some(value) = self.game_state_storage.get_payload(board, max_depth) {
+            return *value;
+        }
+        let mut value = DEFEAT;
+        for pp in board.iter_free_cells() {
+            let move_value =
+                self.evaluate_move(board, pp, self_id, other_id, max_depth);
+            board.remove(pp);
+            value = value.max(move_value);
+        }
+        self.game_state_storage
+            .register_game_state(board, value, max_depth);
+        value
+    }
+
+    /// Plays `pp` on `board` and returns its value for `self_id`.
+    ///
+    /// The stone is left on the board, so that the caller can remove it again.
+    fn evaluate_move(
+        &mut self,
+        board: &mut BitBoard,
+        pp: PointPlacement,
+        self_id: PlayerID,
+        other_id: PlayerID,
+        max_depth: u32,
+    ) -> f32 {
+        match self.referee.receive_move(board, pp, self_id) {
+            GameResult::Defeat | GameResult::IllegalMove => DEFEAT,
+            GameResult::Victory => VICTORY,
+            GameResult::Draw => DRAW,
+            GameResult::Undecided => {
+                if max_depth <= 1 {
+                    DRAW
+                } else {
+                    -self.get_value(board, other_id, self_id, max_depth - 1)
+                }
+            }
+        }
+    }
+
+    fn to_placement(evaluations: &Evaluation) -> Placement {
+        let max = evaluations
+            .iter_2d()
+            .map(|(_, val)| *val)
+            .fold(DEFEAT, f32::max);
+        // Direct comparsion is fine as float values are taken from the evaluations array.
+        #[allow(clippy::float_cmp)]
+        if max == DEFEAT {
+            return Placement::new_from_existing(evaluations, 1.0);
+        }
+
+        let mut placements = Placement::new_from_existing(evaluations, 0.0);
+        for (pp, eval) in evaluations.iter_2d() {
+            #[allow(clippy::float_cmp)]
+            if *eval == max {
+                placements[pp] = 1.0;
+            }
+        }
+        placements
+    }
+}
+
+impl Player for BitBoardMinMaxPlayer {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        assert!(self.max_depth > 0, "Lookahead must be at least 1!");
+        let board = BitBoard::from_game_state(board, [self.self_id, self.other_id])
+            .expect("Board must be representable as BitBoard!");
+        let evaluations = self.get_evaluations(&board);
+        Self::to_placement(&evaluations)
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.self_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    // direct winning moves
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(1), None, None, None],
+            [None, Some(0), None, None, None],
+            [None, None, Some(0), None, Some(0)],
+            [None, Some(0), None, None, Some(1)],
+            [None, Some(1), None, None, Some(1)],
+        ]
+    ).unwrap(),
+        Placement::new_with_values([
+            [1.0, 0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0, 0.0],
+        ]).unwrap(),
+        1
+    )]
+    // indirect winning moves
+    #[case(GameState::new_with_values(
+            [
+                [None, None, Some(0), Some(1)],
+                [None, Some(1), None, None],
+                [Some(0), None, None, None],
+                [Some(1), None, None, None]
+            ],
+
+        ).unwrap(),
+        Placement::new_with_values([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ]).unwrap(),
+        3
+    )]
+    fn correct_moves_are_found(
+        #[case] board: GameState,
+        #[case] expected: Placement,
+        #[case] lookahead: u32,
+    ) {
+        let referee = BitBoardReferee::new(3);
+        let mut player = BitBoardMinMaxPlayer::new(
+            lookahead,
+            1,
+            NaiveGameStateStorage::new(),
+            referee,
+            0,
+        );
+
+        let result = player.do_move(&board);
+        assert_eq!(result, expected);
+    }
+}
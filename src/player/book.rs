@@ -0,0 +1,107 @@
+use crate::book::OpeningBook;
+use crate::interfaces::{GameState, Placement, Player, PlayerID, TicTacToeReferee};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/// Player consulting an `OpeningBook` before asking another player.
+///
+/// If the book knows legal moves of positive weight for the position, one of them is
+/// picked with a chance proportional to its weight. Otherwise, the move of the inner
+/// player is made.
+pub struct BookPlayer<'player> {
+    book: &'player OpeningBook,
+    inner: Box<dyn Player + 'player>,
+    referee: &'player dyn TicTacToeReferee,
+    rng: StdRng,
+}
+
+impl<'player> BookPlayer<'player> {
+    #[must_use]
+    pub fn new(
+        book: &'player OpeningBook,
+        inner: Box<dyn Player + 'player>,
+        referee: &'player dyn TicTacToeReferee,
+    ) -> Self {
+        Self::new_with_rng(book, inner, referee, StdRng::from_os_rng())
+    }
+
+    /// Creates a player picking the book moves with `rng`.
+    #[must_use]
+    pub fn new_with_rng(
+        book: &'player OpeningBook,
+        inner: Box<dyn Player + 'player>,
+        referee: &'player dyn TicTacToeReferee,
+        rng: StdRng,
+    ) -> Self {
+        Self {
+            book,
+            inner,
+            referee,
+            rng,
+        }
+    }
+}
+
+impl Player for BookPlayer<'_> {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        let legal_moves = self.referee.get_legal_moves(board);
+        match self.book.sample_move(board, &legal_moves, &mut self.rng) {
+            Some(pp) => {
+                let mut placements = Placement::new_from_existing(board, 0.0);
+                placements[pp] = 1.0;
+                placements
+            }
+            None => self.inner.do_move(board),
+        }
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.inner.get_id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::PointPlacement;
+    use crate::layout::parse_mask;
+    use crate::player::onelookahead::OneLookaheadPlayer;
+    use crate::referee::NaiveReferee;
+
+    fn get_chosen_moves(placements: &Placement) -> Vec<PointPlacement> {
+        placements
+            .iter_2d()
+            .filter(|&(_, &weight)| weight > 0.0)
+            .map(|(pp, _)| pp)
+            .collect()
+    }
+
+    #[test]
+    fn book_moves_are_preferred() {
+        let referee = NaiveReferee::new(3);
+        let board = parse_mask("00.\n11.\n...").unwrap();
+        let book_move = PointPlacement { row: 2, column: 2 };
+        let mut book = OpeningBook::new();
+        book.add_move(&board, book_move, 1.0);
+        let inner = OneLookaheadPlayer::new(1, Box::new(referee.clone()), 0);
+        let mut player = BookPlayer::new(&book, Box::new(inner), &referee);
+        assert_eq!(player.get_id(), 0);
+        assert_eq!(get_chosen_moves(&player.do_move(&board)), vec![book_move]);
+    }
+
+    #[test]
+    fn unknown_positions_are_delegated() {
+        let referee = NaiveReferee::new(3);
+        let board = parse_mask("00.\n11.\n...").unwrap();
+        let mut book = OpeningBook::new();
+        // Taken cells and moves of zero weight are never played.
+        book.add_move(&board, PointPlacement { row: 0, column: 0 }, 1.0);
+        book.add_move(&board, PointPlacement { row: 2, column: 2 }, 0.0);
+        let inner = OneLookaheadPlayer::new(1, Box::new(referee.clone()), 0);
+        let mut player = BookPlayer::new(&book, Box::new(inner), &referee);
+        assert_eq!(
+            get_chosen_moves(&player.do_move(&board)),
+            vec![PointPlacement { row: 0, column: 2 }]
+        );
+    }
+}
@@ -4,15 +4,91 @@ use crate::interfaces::{
     GameResult, GameState, Placement, Player, PlayerID, PointPlacement, TicTacToeArena,
     TicTacToeReferee,
 };
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 
 type NSamplesT = u16;
 
+/// Outcomes of the random games sampled by the MCTS players, per first move.
+pub(crate) struct SampleStatistics {
+    draws: Board<u32>,
+    has_draw_prob: bool,
+    has_win_prob: bool,
+    id: PlayerID,
+    tries: Board<u32>,
+    wins: Board<u32>,
+}
+
+impl SampleStatistics {
+    pub(crate) fn new(board: &GameState, id: PlayerID) -> Self {
+        let tries = Board::new_from_existing(board, 0u32);
+        Self {
+            draws: tries.clone(),
+            has_draw_prob: false,
+            has_win_prob: false,
+            id,
+            wins: tries.clone(),
+            tries,
+        }
+    }
+
+    /// Records a sample starting with `pp` and ending in `result` for `player_id`.
+    pub(crate) fn add(
+        &mut self,
+        pp: PointPlacement,
+        result: GameResult,
+        player_id: PlayerID,
+    ) {
+        self.tries[pp] += 1;
+        let is_won = match result {
+            GameResult::Victory => player_id == self.id,
+            // The player causing a defeat loses, so the other one wins.
+            GameResult::Defeat | GameResult::IllegalMove => player_id != self.id,
+            GameResult::Draw => {
+                self.draws[pp] += 1;
+                self.has_draw_prob = true;
+                false
+            }
+            GameResult::Undecided => false,
+        };
+        self.wins[pp] += u32::from(is_won);
+        self.has_win_prob |= is_won;
+    }
+
+    /// Returns the share of won samples per first move, or the share of drawn ones if
+    /// no sample was won.
+    ///
+    /// If every sample was lost, all sampled moves are equally bad. If there are no
+    /// samples at all, all moves are weighted zero.
+    pub(crate) fn into_placement(self) -> Placement {
+        let working_arr = if self.has_win_prob {
+            self.wins
+        } else if self.has_draw_prob {
+            self.draws
+        } else {
+            self.tries.clone()
+        };
+        let mut placements = Placement::new_from_existing(&working_arr, 0.0);
+        for (pp, count, total) in working_arr.joint_into_iter_2d(self.tries) {
+            #[allow(clippy::cast_precision_loss)]
+            let chance = if total == 0 {
+                0.0
+            } else {
+                count as f32 / total as f32
+            };
+            placements[pp] = chance;
+        }
+        placements
+    }
+}
+
 pub struct CountBoundMCTSPlayer<'player> {
     id: PlayerID,
     nsamples: NSamplesT,
     player0: &'player mut dyn Player,
     player1: &'player mut dyn Player,
     referee: &'player mut dyn TicTacToeReferee,
+    rng: StdRng,
 }
 impl<'player> CountBoundMCTSPlayer<'player> {
     #[allow(dead_code)]
@@ -22,6 +98,25 @@ impl<'player> CountBoundMCTSPlayer<'player> {
         player0: &'player mut dyn Player,
         player1: &'player mut dyn Player,
         referee: &'player mut dyn TicTacToeReferee,
+    ) -> Self {
+        Self::new_with_rng(
+            id,
+            nsamples,
+            player0,
+            player1,
+            referee,
+            StdRng::from_os_rng(),
+        )
+    }
+
+    /// Creates a player whose samples are drawn with `rng`.
+    pub fn new_with_rng(
+        id: PlayerID,
+        nsamples: NSamplesT,
+        player0: &'player mut dyn Player,
+        player1: &'player mut dyn Player,
+        referee: &'player mut dyn TicTacToeReferee,
+        rng: StdRng,
     ) -> Self {
         Self {
             id,
@@ -29,67 +124,28 @@ impl<'player> CountBoundMCTSPlayer<'player> {
             player0,
             player1,
             referee,
+            rng,
         }
     }
 }
 impl Player for CountBoundMCTSPlayer<'_> {
     fn do_move(&mut self, board: &GameState) -> Placement {
-        let mut tries = Board::<NSamplesT>::new_from_existing(board, 0 as NSamplesT);
-        let mut wins = tries.clone();
-        let mut draws = tries.clone();
-        let mut has_win_prob = false;
-
+        let mut statistics = SampleStatistics::new(board, self.id);
         for _ in 0..self.nsamples {
-            let mut my_arena = ExploringTicTacToeArena::new(
+            let mut my_arena = ExploringTicTacToeArena::new_with_rng(
                 board.clone(),
-                [&mut *self.player0, &mut *self.player1],
+                vec![&mut *self.player0, &mut *self.player1],
                 self.id,
                 &mut *self.referee,
+                StdRng::from_rng(&mut self.rng),
             );
 
             let (result, player_id, first_point_placement) =
                 CountBoundMCTSPlayer::do_one_step_sample(&mut my_arena);
-
-            match first_point_placement {
-                Some(pp) => {
-                    tries[pp] += 1;
-                    match result {
-                        GameResult::Victory => {
-                            wins[pp] += NSamplesT::from(player_id == self.id);
-                            has_win_prob |= true;
-                        }
-                        GameResult::Draw => {
-                            draws[pp] += 1;
-                        }
-                        _ => {}
-                    }
-                }
-                None => panic!("No legal move was made!"),
-            }
-        }
-        println!("{wins:?}");
-        println!("{draws:?}");
-        println!("{tries:?}");
-
-        let working_arr = if has_win_prob { wins } else { draws };
-        let mut placements = Placement::new_from_existing(board, 0.0);
-        let placements_iter =
-            working_arr
-                .joint_into_iter_2d(tries)
-                .map(|(pp, count, total)| {
-                    let chance = if total == 0 {
-                        0.0
-                    } else {
-                        f32::from(count) / f32::from(total)
-                    };
-                    (pp, chance)
-                });
-        for (pp, value) in placements_iter {
-            placements[pp] = value;
+            let pp = first_point_placement.expect("No legal move was made!");
+            statistics.add(pp, result, player_id);
         }
-
-        println!("{placements:?}");
-        placements
+        statistics.into_placement()
     }
 
     fn get_id(&self) -> PlayerID {
@@ -98,7 +154,7 @@ impl Player for CountBoundMCTSPlayer<'_> {
 }
 
 impl CountBoundMCTSPlayer<'_> {
-    fn do_one_step_sample(
+    pub(crate) fn do_one_step_sample(
         arena: &mut ExploringTicTacToeArena,
     ) -> (GameResult, PlayerID, Option<PointPlacement>) {
         let (result, first_player_id, first_point_placement) = arena.do_next_move();
@@ -115,3 +171,40 @@ impl CountBoundMCTSPlayer<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::guessing::GuessingPlayer;
+    use crate::referee::{MisereReferee, NaiveReferee};
+
+    #[test]
+    fn lost_samples_do_not_count_as_wins_in_misere_play() {
+        // Every move but the one at the bottom left completes a line of player 0.
+        let board = GameState::new_with_values([
+            [Some(0), Some(0), None],
+            [Some(1), None, Some(1)],
+            [None, Some(1), Some(0)],
+        ])
+        .unwrap();
+        let mut player0 = GuessingPlayer { id: 0 };
+        let mut player1 = GuessingPlayer { id: 1 };
+        let mut referee = MisereReferee::new(Box::new(NaiveReferee::new(3)));
+        let mut player = CountBoundMCTSPlayer::new_with_rng(
+            0,
+            100,
+            &mut player0,
+            &mut player1,
+            &mut referee,
+            StdRng::seed_from_u64(0),
+        );
+
+        let placement = player.do_move(&board);
+        let best = placement
+            .iter_2d()
+            .max_by(|lhs, rhs| lhs.1.total_cmp(rhs.1))
+            .map(|(pp, _)| pp)
+            .unwrap();
+        assert_eq!(best, PointPlacement { row: 2, column: 0 });
+    }
+}
@@ -0,0 +1,242 @@
+use crate::game_state_storage::{GameStateStorage, NaiveGameStateStorage};
+use crate::interfaces::{
+    Evaluation, GameResult, GameState, Placement, Player, PlayerID, PointPlacement,
+    TicTacToeReferee,
+};
+
+const DEFEAT: f32 = -1.0;
+const VICTORY: f32 = 1.0;
+const DRAW: f32 = 0.0;
+
+/// Player for games of any number of players using the max^n algorithm.
+///
+/// Every position is assigned a vector holding one value per player. Each player
+/// chooses the move maximising its own entry, ties being broken in favour of the
+/// first move found. A victory is worth 1 for the winner and -1 for everybody else, a
+/// defeat the other way round. For two players, this is the same as `MinMaxPlayer`.
+pub struct MaxNPlayer<'player> {
+    max_depth: u32,
+    /// Stores the values of a position together with the index of the player to move.
+    game_state_storage: NaiveGameStateStorage<(GameState, usize), Vec<f32>>,
+    player_ids: Vec<PlayerID>,
+    referee: &'player dyn TicTacToeReferee,
+    self_index: usize,
+}
+
+impl<'player> MaxNPlayer<'player> {
+    /// Creates a new player for a game in which `player_ids` move in the given order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self_id` is not one of `player_ids`.
+    #[must_use]
+    pub fn new(
+        max_depth: u32,
+        player_ids: Vec<PlayerID>,
+        referee: &'player dyn TicTacToeReferee,
+        self_id: PlayerID,
+    ) -> Self {
+        let self_index = player_ids
+            .iter()
+            .position(|&id| id == self_id)
+            .unwrap_or_else(|| panic!("Player ID {self_id} is not taking part!"));
+        Self {
+            max_depth,
+            game_state_storage: NaiveGameStateStorage::new(),
+            player_ids,
+            referee,
+            self_index,
+        }
+    }
+
+    fn get_evaluations(&mut self, board: &GameState) -> Evaluation {
+        let mut evaluation = Evaluation::new_from_existing(board, DEFEAT);
+        let mut temporary_board = board.clone();
+        for pp in self.referee.get_legal_moves(board) {
+            let values = self.evaluate_move(
+                &mut temporary_board,
+                pp,
+                self.self_index,
+                self.max_depth,
+            );
+            evaluation[pp] = values[self.self_index];
+            temporary_board[pp] = board[pp];
+        }
+        evaluation
+    }
+
+    /// Plays `pp` for the player with index `mover` and returns the resulting values.
+    ///
+    /// The stone is left on the board, so that the caller can take it back.
+    fn evaluate_move(
+        &mut self,
+        board: &mut GameState,
+        pp: PointPlacement,
+        mover: usize,
+        max_depth: u32,
+    ) -> Vec<f32> {
+        let mover_id = self.player_ids[mover];
+        match self.referee.receive_move(board, pp, mover_id) {
+            GameResult::Victory => self.get_outcome(mover, VICTORY),
+            GameResult::Defeat | GameResult::IllegalMove => {
+                self.get_outcome(mover, DEFEAT)
+            }
+            GameResult::Draw => vec![DRAW; self.player_ids.len()],
+            GameResult::Undecided if max_depth <= 1 => {
+                vec![DRAW; self.player_ids.len()]
+            }
+            GameResult::Undecided => {
+                let next_mover = if self.referee.is_turn_over(board) {
+                    (mover + 1) % self.player_ids.len()
+                } else {
+                    mover
+                };
+                self.get_values(board, next_mover, max_depth - 1)
+            }
+        }
+    }
+
+    /// Returns the values of `board` if the player with index `mover` is to move.
+    fn get_values(
+        &mut self,
+        board: &mut GameState,
+        mover: usize,
+        max_depth: u32,
+    ) -> Vec<f32> {
+        let key = (board.clone(), mover);
+        if let Some(values) = self.game_state_storage.get_payload(&key, max_depth) {
+            return values.into_owned();
+        }
+        let mut best_values: Option<Vec<f32>> = None;
+        for pp in self.referee.get_legal_moves(board) {
+            let values = self.evaluate_move(board, pp, mover, max_depth);
+            board[pp] = None.into();
+            if best_values
+                .as_ref()
+                .is_none_or(|best_values| values[mover] > best_values[mover])
+            {
+                best_values = Some(values);
+            }
+        }
+        let values = best_values.unwrap_or_else(|| vec![DRAW; self.player_ids.len()]);
+        self.game_state_storage
+            .register_game_state(&key, values.clone(), max_depth);
+        values
+    }
+
+    fn get_outcome(&self, player_index: usize, value: f32) -> Vec<f32> {
+        let mut values = vec![-value; self.player_ids.len()];
+        values[player_index] = value;
+        values
+    }
+
+    fn to_placement(evaluations: &Evaluation) -> Placement {
+        let max = evaluations
+            .iter_2d()
+            .map(|(_, val)| *val)
+            .fold(DEFEAT, f32::max);
+        // Direct comparsion is fine as float values are taken from the evaluations array.
+        #[allow(clippy::float_cmp)]
+        if max == DEFEAT {
+            return Placement::new_from_existing(evaluations, 1.0);
+        }
+
+        let mut placements = Placement::new_from_existing(evaluations, 0.0);
+        for (pp, eval) in evaluations.iter_2d() {
+            #[allow(clippy::float_cmp)]
+            if *eval == max {
+                placements[pp] = 1.0;
+            }
+        }
+        placements
+    }
+}
+
+impl Player for MaxNPlayer<'_> {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        assert!(self.max_depth > 0, "Lookahead must be at least 1!");
+        let evaluations = self.get_evaluations(board);
+        Self::to_placement(&evaluations)
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.player_ids[self.self_index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::referee::*;
+    use rstest::*;
+
+    #[rstest]
+    // own winning move is preferred over blocking the next player
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None, None],
+            [Some(1), Some(1), None, None],
+            [Some(2), None, None, Some(2)],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        Placement::new_with_values([
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ]).unwrap(),
+        2
+    )]
+    // the next player has to be blocked
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), None, None, Some(2)],
+            [Some(1), Some(1), None, None],
+            [None, None, None, None],
+            [None, None, Some(0), Some(2)],
+        ]
+    ).unwrap(),
+        Placement::new_with_values([
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ]).unwrap(),
+        2
+    )]
+    fn correct_moves_are_found_among_three_players(
+        #[case] board: GameState,
+        #[case] expected: Placement,
+        #[case] lookahead: u32,
+    ) {
+        let referee = NaiveReferee::new(3);
+        let mut player = MaxNPlayer::new(lookahead, vec![0, 1, 2], &referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn two_player_games_are_played_like_minmax() {
+        let board = GameState::new_with_values([
+            [None, None, Some(0), Some(1)],
+            [None, Some(1), None, None],
+            [Some(0), None, None, None],
+            [Some(1), None, None, None],
+        ])
+        .unwrap();
+        let expected = Placement::new_with_values([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ])
+        .unwrap();
+        let referee = NaiveReferee::new(3);
+        let mut player = MaxNPlayer::new(3, vec![0, 1], &referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(result, expected);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::game_state_storage::{GameStateStorage, NaiveGameStateStorage};
 use crate::interfaces::{
-    Evaluation, GameResult, GameState, Placement, Player, PlayerID, TicTacToeReferee,
+    Evaluation, GameResult, GameState, Placement, Player, PlayerID, SearchPosition,
+    TicTacToeReferee,
 };
 use std::iter::Iterator;
+use std::time::Instant;
 
 const DEFEAT: f32 = -1.0;
 const VICTORY: f32 = 1.0;
@@ -15,12 +17,27 @@ struct GetEvaluationsArgs {
     max_depth: u32,
 }
 
-pub struct MinMaxPlayer<'player> {
+/// Player searching the full game tree up to a fixed depth.
+///
+/// The search plays on a `Position`, which also serves as key of the storage. Use
+/// e.g. `ZobristGameState` together with `ZobristGameStateStorage` to avoid hashing
+/// whole boards.
+pub struct MinMaxPlayer<
+    'player,
+    Storage: GameStateStorage<Position, Evaluation> = NaiveGameStateStorage<
+        GameState,
+        Evaluation,
+    >,
+    Position: SearchPosition = GameState,
+> {
     max_depth: u32,
     other_id: PlayerID,
-    game_state_storage: NaiveGameStateStorage<GameState, Evaluation>,
+    game_state_storage: Storage,
     referee: &'player dyn TicTacToeReferee,
     self_id: PlayerID,
+    deadline: Option<Instant>,
+    is_aborted: bool,
+    _position: std::marker::PhantomData<Position>,
 }
 
 fn get_maximum(evaluations: &Evaluation) -> f32 {
@@ -32,11 +49,20 @@ fn get_maximum(evaluations: &Evaluation) -> f32 {
     *max
 }
 
-impl<'player> MinMaxPlayer<'player> {
+#[allow(clippy::float_cmp)]
+fn is_decided(value: f32) -> bool {
+    value == VICTORY || value == DEFEAT
+}
+
+impl<'player, Storage, Position> MinMaxPlayer<'player, Storage, Position>
+where
+    Storage: GameStateStorage<Position, Evaluation>,
+    Position: SearchPosition,
+{
     pub fn new(
         max_depth: u32,
         other_id: PlayerID,
-        game_state_storage: NaiveGameStateStorage<GameState, Evaluation>,
+        game_state_storage: Storage,
         referee: &'player dyn TicTacToeReferee,
         self_id: PlayerID,
     ) -> Self {
@@ -46,24 +72,72 @@ impl<'player> MinMaxPlayer<'player> {
             game_state_storage,
             referee,
             self_id,
+            deadline: None,
+            is_aborted: false,
+            _position: std::marker::PhantomData,
         }
     }
 
+    /// Searches `board` with the given lookahead, giving up once `deadline` passed.
+    ///
+    /// Returns `None` if the search was aborted. Evaluations of positions that were
+    /// searched completely are kept in the storage nevertheless, so that a subsequent
+    /// search can make use of them.
+    pub(crate) fn get_evaluations_until(
+        &mut self,
+        board: &GameState,
+        max_depth: u32,
+        deadline: Option<Instant>,
+    ) -> Option<Evaluation> {
+        self.max_depth = max_depth;
+        self.deadline = deadline;
+        self.is_aborted = false;
+        let mut board = Position::from_game_state(board);
+        let args = GetEvaluationsArgs {
+            self_id: self.self_id,
+            other_id: self.other_id,
+            max_depth,
+        };
+        let evaluations = self.get_evaluations(&mut board, &args);
+        (!self.is_aborted).then_some(evaluations)
+    }
+
     fn get_evaluations(
         &mut self,
-        board: &mut GameState,
+        board: &mut Position,
         args: &GetEvaluationsArgs,
     ) -> Evaluation {
         if let Some(evaluations) =
             self.game_state_storage.get_payload(board, args.max_depth)
         {
-            return evaluations.to_owned();
+            return evaluations.into_owned();
+        }
+        // Wins and losses found by a shallower search hold for deeper ones, too. This
+        // does not apply to the root, whose evaluations of all cells are needed.
+        if args.max_depth < self.max_depth
+            && let Some(evaluations) = self.game_state_storage.get_payload(board, 1)
+            && is_decided(get_maximum(&evaluations))
+        {
+            return evaluations.into_owned();
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.is_aborted = true;
+        }
+        if self.is_aborted {
+            return Evaluation::new_from_existing(board.get_game_state(), DEFEAT);
         }
         let evaluations = match args.max_depth {
             0 => panic!("Lookahead must be at least 1!"),
             1 => self.get_evaluations_1(board, args),
             _ => self.get_evaluations_n(board, args),
         };
+        if self.is_aborted {
+            // The evaluations are incomplete and must not be reused.
+            return evaluations;
+        }
         self.game_state_storage.register_game_state(
             board,
             evaluations.clone(),
@@ -72,7 +146,7 @@ impl<'player> MinMaxPlayer<'player> {
         evaluations
     }
 
-    fn to_placement(evaluations: &Evaluation) -> Placement {
+    pub(crate) fn to_placement(evaluations: &Evaluation) -> Placement {
         let max = get_maximum(evaluations);
         if max == DEFEAT {
             println!("Sure defeat detected. Using default placements.");
@@ -97,80 +171,81 @@ impl<'player> MinMaxPlayer<'player> {
 
     fn get_evaluations_1(
         &mut self,
-        board: &GameState,
+        board: &Position,
         args: &GetEvaluationsArgs,
     ) -> Evaluation {
-        let mut evaluation = Evaluation::new(
-            board.get_number_of_rows(),
-            board.get_number_of_columns(),
-            DEFEAT,
-        );
+        let mut evaluation =
+            Evaluation::new_from_existing(board.get_game_state(), DEFEAT);
         let mut temporary_board = board.clone();
 
-        for (pp, old_board_val) in board.iter_2d() {
+        for pp in self.referee.get_legal_moves(board.get_game_state()) {
+            let old_board_val = board.get_game_state()[pp];
             let move_result =
-                self.referee
-                    .receive_move(&mut temporary_board, pp, args.self_id);
+                temporary_board.receive_move(self.referee, pp, args.self_id);
             evaluation[pp] = match move_result {
                 GameResult::Defeat | GameResult::IllegalMove => DEFEAT,
                 GameResult::Victory => VICTORY,
                 GameResult::Draw | GameResult::Undecided => DRAW,
             };
-            temporary_board[pp] = *old_board_val;
+            temporary_board.set_entry(pp, old_board_val);
         }
         evaluation
     }
 
     fn get_evaluations_n(
         &mut self,
-        board: &GameState,
+        board: &Position,
         args: &GetEvaluationsArgs,
     ) -> Evaluation {
-        let mut evaluation = Evaluation::new(
-            board.get_number_of_rows(),
-            board.get_number_of_columns(),
-            DEFEAT,
-        );
+        let mut evaluation =
+            Evaluation::new_from_existing(board.get_game_state(), DEFEAT);
         let mut temporary_board = board.clone();
         let pass_down_args = GetEvaluationsArgs {
             other_id: args.self_id,
             self_id: args.other_id,
             max_depth: args.max_depth - 1,
         };
+        let continue_turn_args = GetEvaluationsArgs {
+            max_depth: args.max_depth - 1,
+            ..args.clone()
+        };
 
-        for (pp, old_board_val) in board.iter_2d() {
+        for pp in self.referee.get_legal_moves(board.get_game_state()) {
+            let old_board_val = board.get_game_state()[pp];
             let move_result =
-                self.referee
-                    .receive_move(&mut temporary_board, pp, args.self_id);
+                temporary_board.receive_move(self.referee, pp, args.self_id);
             evaluation[pp] = match move_result {
                 GameResult::Defeat | GameResult::IllegalMove => DEFEAT,
                 GameResult::Victory => VICTORY,
                 GameResult::Draw => DRAW,
-                GameResult::Undecided => {
+                GameResult::Undecided
+                    if self.referee.is_turn_over(temporary_board.get_game_state()) =>
+                {
                     let pp_evaluations =
                         self.get_evaluations(&mut temporary_board, &pass_down_args);
                     -get_maximum(&pp_evaluations)
                 }
+                GameResult::Undecided => {
+                    let pp_evaluations =
+                        self.get_evaluations(&mut temporary_board, &continue_turn_args);
+                    get_maximum(&pp_evaluations)
+                }
             };
-            temporary_board[pp] = *old_board_val;
-        }
-
-        if args.max_depth == self.max_depth {
-            println!("{evaluation:?}");
+            temporary_board.set_entry(pp, old_board_val);
         }
         evaluation
     }
 }
 
-impl Player for MinMaxPlayer<'_> {
+impl<Storage, Position> Player for MinMaxPlayer<'_, Storage, Position>
+where
+    Storage: GameStateStorage<Position, Evaluation>,
+    Position: SearchPosition,
+{
     fn do_move(&mut self, board: &GameState) -> Placement {
-        let mut board = board.clone();
-        let args = GetEvaluationsArgs {
-            self_id: self.self_id,
-            other_id: self.other_id,
-            max_depth: self.max_depth,
-        };
-        let evaluations = self.get_evaluations(&mut board, &args);
+        let evaluations = self
+            .get_evaluations_until(board, self.max_depth, None)
+            .expect("Searches without deadline are never aborted.");
         Self::to_placement(&evaluations)
     }
 
@@ -182,7 +257,9 @@ impl Player for MinMaxPlayer<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::game_state_storage::NaiveGameStateStorage;
+    use crate::game_state_storage::{
+        NaiveGameStateStorage, SymmetricGameStateStorage, ZobristGameStateStorage,
+    };
     use crate::referee::*;
     use rstest::*;
 
@@ -228,22 +305,111 @@ mod tests {
         #[case] board: GameState,
         #[case] expected: Placement,
         #[case] lookahead: u32,
+        #[values("naive", "symmetric", "zobrist")] storage: &str,
     ) {
         let winning_length = 3;
         let other_id = 1;
         let self_id = 0;
-        let game_state_storage = NaiveGameStateStorage::<_, _>::new();
 
-        let mut referee = NaiveReferee::new(winning_length);
-        let mut player = MinMaxPlayer {
-            max_depth: lookahead,
-            self_id,
-            other_id,
-            game_state_storage,
-            referee: &mut referee,
+        let referee = NaiveReferee::new(winning_length);
+        let mut player: Box<dyn Player> = match storage {
+            "naive" => Box::new(MinMaxPlayer::new(
+                lookahead,
+                other_id,
+                NaiveGameStateStorage::<GameState, _>::new(),
+                &referee,
+                self_id,
+            )),
+            "symmetric" => Box::new(MinMaxPlayer::new(
+                lookahead,
+                other_id,
+                SymmetricGameStateStorage::new(),
+                &referee,
+                self_id,
+            )),
+            _ => Box::new(MinMaxPlayer::new(
+                lookahead,
+                other_id,
+                ZobristGameStateStorage::new(true),
+                &referee,
+                self_id,
+            )),
         };
 
         let result = player.do_move(&board);
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn completing_a_line_is_avoided_in_misere_play() {
+        let board = GameState::new_with_values([
+            [Some(0), Some(0), None],
+            [Some(1), None, Some(1)],
+            [None, Some(1), Some(0)],
+        ])
+        .unwrap();
+        let expected = Placement::new_with_values([
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+        ])
+        .unwrap();
+        let referee = MisereReferee::new(Box::new(NaiveReferee::new(3)));
+        let mut player =
+            MinMaxPlayer::new(1, 1, ZobristGameStateStorage::new(true), &referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn both_stones_of_a_turn_are_considered() {
+        // Neither player can complete a line with a single stone, but with two.
+        let board = GameState::new_with_values([
+            [Some(0), None, None],
+            [None, None, None],
+            [Some(1), Some(1), None],
+        ])
+        .unwrap();
+        let expected = Placement::new_with_values([
+            [0.0, 1.0, 1.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ])
+        .unwrap();
+        let referee = ConnectReferee::new(
+            Box::new(NaiveReferee::new(3)),
+            TurnSchedule::new(1, 2),
+        );
+        let mut player =
+            MinMaxPlayer::new(2, 1, ZobristGameStateStorage::new(true), &referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn only_legal_moves_are_considered_with_gravity() {
+        // The winning cell (2, 2) is out of reach, but the opponent's one is not.
+        let board = GameState::new_with_values([
+            [None, None, None, None],
+            [None, None, None, None],
+            [Some(0), Some(0), None, None],
+            [Some(1), Some(1), None, Some(0)],
+        ])
+        .unwrap();
+        let expected = Placement::new_with_values([
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+        ])
+        .unwrap();
+        let referee = GravityReferee::new(Box::new(NaiveReferee::new(3)));
+        let mut player =
+            MinMaxPlayer::new(2, 1, ZobristGameStateStorage::new(true), &referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(result, expected);
+    }
 }
@@ -2,6 +2,11 @@ use crate::interfaces::{
     GameResult, GameState, Placement, Player, PlayerID, TicTacToeReferee,
 };
 
+/// Player looking one move ahead for itself and its opponent.
+///
+/// The player takes a winning move if there is one and otherwise blocks the winning
+/// moves of its opponent. Moves the referee judges a defeat, e.g. completing a line
+/// in misère play, are avoided as long as there are others.
 pub struct OneLookaheadPlayer {
     other_id: PlayerID,
     referee: Box<dyn TicTacToeReferee>,
@@ -9,7 +14,7 @@ pub struct OneLookaheadPlayer {
 }
 
 impl OneLookaheadPlayer {
-    #[allow(dead_code)]
+    #[must_use]
     pub fn new(
         other_id: PlayerID,
         referee: Box<dyn TicTacToeReferee>,
@@ -22,59 +27,60 @@ impl OneLookaheadPlayer {
         }
     }
 
-    fn get_loosing_moves(&mut self, board: &GameState) -> (bool, Placement) {
-        let mut has_loosing_move = false;
-        let mut placements = Placement::new(
-            board.get_number_of_rows(),
-            board.get_number_of_columns(),
-            1.0,
-        );
+    /// Marks all legal moves of `player` whose result satisfies `is_wanted`.
+    ///
+    /// Returns whether there is such a move together with the placements.
+    fn get_moves_with_result(
+        &self,
+        board: &GameState,
+        player: PlayerID,
+        is_wanted: impl Fn(GameResult) -> bool,
+    ) -> (bool, Placement) {
+        let mut has_move = false;
         let mut mut_board = board.clone();
-        for (pp, old_val) in board.iter_2d() {
-            if GameResult::Victory
-                == self.referee.receive_move(&mut mut_board, pp, self.other_id)
-            {
+        let mut placements = Placement::new_from_existing(board, 0.0);
+        for pp in self.referee.get_legal_moves(board) {
+            let old_val = board[pp];
+            if is_wanted(self.referee.receive_move(&mut mut_board, pp, player)) {
                 placements[pp] = 1.0;
-                has_loosing_move = true;
+                has_move = true;
             }
-            mut_board[pp] = *old_val;
+            mut_board[pp] = old_val;
         }
 
-        (has_loosing_move, placements)
-    }
-
-    fn get_winning_moves(&mut self, board: &GameState) -> (bool, Placement) {
-        let mut has_winning_move = false;
-        let mut mut_board = board.clone();
-        let mut placements = Placement::new(
-            board.get_number_of_rows(),
-            board.get_number_of_columns(),
-            0.0,
-        );
-        for (pp, old_val) in board.iter_2d() {
-            if GameResult::Victory
-                == self.referee.receive_move(&mut mut_board, pp, self.get_id())
-            {
-                placements[pp] = 1.0;
-                has_winning_move = true;
-            }
-            mut_board[pp] = *old_val;
-        }
-
-        (has_winning_move, placements)
+        (has_move, placements)
     }
 }
 
 impl Player for OneLookaheadPlayer {
     fn do_move(&mut self, board: &GameState) -> Placement {
-        let (has_winning_move, placements) = self.get_winning_moves(board);
+        let (has_winning_move, placements) =
+            self.get_moves_with_result(board, self.self_id, |result| {
+                result == GameResult::Victory
+            });
         if has_winning_move {
             return placements;
         }
 
-        let (has_loosing_move, placements) = self.get_loosing_moves(board);
+        let (has_safe_move, safe_placements) =
+            self.get_moves_with_result(board, self.self_id, |result| {
+                !matches!(result, GameResult::Defeat | GameResult::IllegalMove)
+            });
+        let (has_loosing_move, mut placements) =
+            self.get_moves_with_result(board, self.other_id, |result| {
+                result == GameResult::Victory
+            });
         if has_loosing_move {
-            return placements;
+            // Blocking by a move that loses right away does not help.
+            for (pp, weight) in safe_placements.iter_2d() {
+                placements[pp] *= weight;
+            }
+            if placements.iter_2d().any(|(_, weight)| *weight > 0.0) {
+                return placements;
+            }
+        }
+        if has_safe_move {
+            return safe_placements;
         }
 
         Placement::new(
@@ -88,3 +94,72 @@ impl Player for OneLookaheadPlayer {
         self.self_id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::referee::{MisereReferee, NaiveReferee};
+    use rstest::*;
+
+    #[rstest]
+    // own winning move
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        Placement::new_with_values([
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0],
+        ]).unwrap(),
+        false
+    )]
+    // blocking the opponent
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), None, None],
+            [Some(1), Some(1), None],
+            [Some(0), None, None],
+        ]
+    ).unwrap(),
+        Placement::new_with_values([
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0],
+        ]).unwrap(),
+        false
+    )]
+    // completing a line loses in misère play
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None],
+            [Some(1), None, Some(1)],
+            [None, Some(1), Some(0)],
+        ]
+    ).unwrap(),
+        Placement::new_with_values([
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+        ]).unwrap(),
+        true
+    )]
+    fn correct_moves_are_found(
+        #[case] board: GameState,
+        #[case] expected: Placement,
+        #[case] is_misere: bool,
+    ) {
+        let referee: Box<dyn TicTacToeReferee> = if is_misere {
+            Box::new(MisereReferee::new(Box::new(NaiveReferee::new(3))))
+        } else {
+            Box::new(NaiveReferee::new(3))
+        };
+        let mut player = OneLookaheadPlayer::new(1, referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(result, expected);
+    }
+}
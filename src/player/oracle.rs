@@ -0,0 +1,123 @@
+use crate::database::Database;
+use crate::interfaces::{GameState, Placement, Player, PlayerID, TicTacToeReferee};
+
+/// Player looking up the best moves in a perfect-play `Database`.
+///
+/// The database is generated from the first position the player has to move on and
+/// generated anew whenever a position is not part of it, e.g. in a new game. Hence,
+/// the player is only suited for small boards.
+///
+/// Moving on a board the database cannot be generated for, see
+/// `Database::supports_board`, panics.
+pub struct OraclePlayer<'player> {
+    database: Option<Database>,
+    other_id: PlayerID,
+    referee: &'player dyn TicTacToeReferee,
+    self_id: PlayerID,
+}
+
+impl<'player> OraclePlayer<'player> {
+    #[must_use]
+    pub fn new(
+        other_id: PlayerID,
+        referee: &'player dyn TicTacToeReferee,
+        self_id: PlayerID,
+    ) -> Self {
+        Self {
+            database: None,
+            other_id,
+            referee,
+            self_id,
+        }
+    }
+
+    /// Creates a player looking up its moves in `database`.
+    #[must_use]
+    pub fn new_with_database(
+        database: Database,
+        other_id: PlayerID,
+        referee: &'player dyn TicTacToeReferee,
+        self_id: PlayerID,
+    ) -> Self {
+        Self {
+            database: Some(database),
+            ..Self::new(other_id, referee, self_id)
+        }
+    }
+
+    /// Returns whether the database knows `board` with the player to move.
+    fn knows(&self, board: &GameState) -> bool {
+        self.database.as_ref().is_some_and(|database| {
+            database.get_player_to_move(board) == Some(self.self_id)
+                && database.lookup(board).is_some()
+        })
+    }
+}
+
+impl Player for OraclePlayer<'_> {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        if !self.knows(board) {
+            let database =
+                Database::generate(board, self.referee, [self.self_id, self.other_id])
+                    .unwrap_or_else(|error| {
+                        panic!("Could not generate database: {error}")
+                    });
+            self.database = Some(database);
+        }
+        let mut placements = Placement::new_from_existing(board, 0.0);
+        let best_moves = self.database.as_ref().map_or_else(Vec::new, |database| {
+            database.get_best_moves(board, self.referee)
+        });
+        if best_moves.is_empty() {
+            for pp in self.referee.get_legal_moves(board) {
+                placements[pp] = 1.0;
+            }
+        }
+        for pp in best_moves {
+            placements[pp] = 1.0;
+        }
+        placements
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.self_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::PointPlacement;
+    use crate::layout::parse_mask;
+    use crate::referee::NaiveReferee;
+
+    fn get_chosen_moves(placements: &Placement) -> Vec<PointPlacement> {
+        placements
+            .iter_2d()
+            .filter(|&(_, &weight)| weight > 0.0)
+            .map(|(pp, _)| pp)
+            .collect()
+    }
+
+    #[test]
+    fn optimal_moves_are_looked_up() {
+        let referee = NaiveReferee::new(3);
+        let mut player = OraclePlayer::new(1, &referee, 0);
+        let placements = player.do_move(&parse_mask("1..\n...\n...").unwrap());
+        assert_eq!(
+            get_chosen_moves(&placements),
+            vec![PointPlacement { row: 1, column: 1 }]
+        );
+        // Against opposite corners, only the edges keep the draw.
+        let placements = player.do_move(&parse_mask("1..\n.0.\n..1").unwrap());
+        assert_eq!(
+            get_chosen_moves(&placements),
+            vec![
+                PointPlacement { row: 0, column: 1 },
+                PointPlacement { row: 1, column: 0 },
+                PointPlacement { row: 1, column: 2 },
+                PointPlacement { row: 2, column: 1 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use crate::interfaces::{GameState, Placement, Player, PlayerID, TicTacToeReferee};
+use crate::player::uct::{Opponents, SearchTree, UCTPlayer};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::panic;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+/// Builds a referee for a worker thread.
+///
+/// Referees are not shared between threads, so every worker builds its own.
+pub type RefereeFactory<'player> =
+    dyn Fn() -> Box<dyn TicTacToeReferee> + Sync + 'player;
+
+/// How the workers of a `ParallelUCTPlayer` split up the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parallelisation {
+    /// Every worker grows a tree of its own. The visits of the root's children are
+    /// summed up in the end.
+    Root,
+    /// All workers grow a single tree, which is locked while selecting and expanding
+    /// a leaf and while propagating a result. Each node on the way to a leaf counts as
+    /// lost until its rollout is finished, which steers the other workers to different
+    /// leaves.
+    Tree,
+}
+
+/// UCT player distributing its iterations over several threads.
+///
+/// In contrast to `UCTPlayer`, the search starts from scratch on every move. Every
+/// worker draws from a random number generator of its own, split off from the one of
+/// the player. Hence, searches are reproducible with root parallelisation, but with
+/// tree parallelisation only if there is a single worker.
+pub struct ParallelUCTPlayer<'player> {
+    exploration_constant: f32,
+    niterations: u32,
+    nworkers: u32,
+    other_id: PlayerID,
+    parallelisation: Parallelisation,
+    referee_factory: Box<RefereeFactory<'player>>,
+    rng: StdRng,
+    self_id: PlayerID,
+}
+
+impl<'player> ParallelUCTPlayer<'player> {
+    /// Creates a player running `niterations` iterations per move on `nworkers`
+    /// threads.
+    ///
+    /// At least one worker is used.
+    #[must_use]
+    pub fn new(
+        exploration_constant: f32,
+        niterations: u32,
+        nworkers: u32,
+        other_id: PlayerID,
+        parallelisation: Parallelisation,
+        referee_factory: Box<RefereeFactory<'player>>,
+        self_id: PlayerID,
+    ) -> Self {
+        Self::new_with_rng(
+            exploration_constant,
+            niterations,
+            nworkers,
+            other_id,
+            parallelisation,
+            referee_factory,
+            StdRng::from_os_rng(),
+            self_id,
+        )
+    }
+
+    /// Creates a player whose workers draw their random moves from generators split
+    /// off from `rng`, see `new`.
+    #[must_use]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_rng(
+        exploration_constant: f32,
+        niterations: u32,
+        nworkers: u32,
+        other_id: PlayerID,
+        parallelisation: Parallelisation,
+        referee_factory: Box<RefereeFactory<'player>>,
+        rng: StdRng,
+        self_id: PlayerID,
+    ) -> Self {
+        Self {
+            exploration_constant,
+            niterations,
+            nworkers: nworkers.max(1),
+            other_id,
+            parallelisation,
+            referee_factory,
+            rng,
+            self_id,
+        }
+    }
+
+    fn build_uct_player<'referee>(
+        &self,
+        niterations: u32,
+        referee: &'referee dyn TicTacToeReferee,
+        rng: StdRng,
+    ) -> UCTPlayer<'referee> {
+        UCTPlayer::new_with_rng(
+            self.exploration_constant,
+            niterations,
+            self.other_id,
+            referee,
+            rng,
+            self.self_id,
+        )
+    }
+
+    /// Splits off one random number generator per worker.
+    fn split_rngs(&mut self) -> Vec<StdRng> {
+        (0..self.nworkers)
+            .map(|_| StdRng::from_rng(&mut self.rng))
+            .collect()
+    }
+
+    fn search_root_parallel(&self, board: &GameState, rngs: Vec<StdRng>) -> Placement {
+        let mut placements = Placement::new_from_existing(board, 0.0);
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.nworkers)
+                .zip(rngs)
+                .map(|(worker, rng)| {
+                    let niterations = self.niterations / self.nworkers
+                        + u32::from(worker < self.niterations % self.nworkers);
+                    scope.spawn(move || {
+                        let referee = (self.referee_factory)();
+                        self.build_uct_player(niterations, &*referee, rng)
+                            .do_move(board)
+                    })
+                })
+                .collect();
+            for worker in workers {
+                let worker_placements = worker
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload));
+                for (pp, visits) in worker_placements.iter_2d() {
+                    placements[pp] += visits;
+                }
+            }
+        });
+        placements
+    }
+
+    fn search_tree_parallel(&self, board: &GameState, rngs: Vec<StdRng>) -> Placement {
+        let opponents = Opponents::new(self.other_id, self.self_id);
+        let tree = Mutex::new(SearchTree::new(
+            board,
+            opponents,
+            &*(self.referee_factory)(),
+        ));
+        let nremaining = AtomicU32::new(self.niterations);
+        thread::scope(|scope| {
+            for mut rng in rngs {
+                let tree = &tree;
+                let nremaining = &nremaining;
+                scope.spawn(move || {
+                    let referee = (self.referee_factory)();
+                    while nremaining
+                        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                            n.checked_sub(1)
+                        })
+                        .is_ok()
+                    {
+                        self.run_shared_iteration(tree, board, &*referee, &mut rng);
+                    }
+                });
+            }
+        });
+        tree.into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_root_visits(board)
+    }
+
+    /// Runs one iteration on the tree shared by all workers, which is unlocked during
+    /// the rollout.
+    fn run_shared_iteration(
+        &self,
+        shared_tree: &Mutex<SearchTree>,
+        root_board: &GameState,
+        referee: &dyn TicTacToeReferee,
+        rng: &mut StdRng,
+    ) {
+        let mut board = root_board.clone();
+        let mut tree = shared_tree.lock().unwrap_or_else(PoisonError::into_inner);
+        let leaf =
+            tree.select_leaf(&mut board, referee, self.exploration_constant, rng);
+        let outcome = if let Some(outcome) = tree.get_terminal_outcome(leaf) {
+            outcome
+        } else {
+            let player = tree.get_player_to_move(leaf, &board, referee);
+            drop(tree);
+            let outcome = Opponents::new(self.other_id, self.self_id)
+                .rollout(&mut board, player, referee, rng);
+            tree = shared_tree.lock().unwrap_or_else(PoisonError::into_inner);
+            outcome
+        };
+        tree.backpropagate(leaf, outcome);
+    }
+}
+
+impl Player for ParallelUCTPlayer<'_> {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        let rngs = self.split_rngs();
+        match self.parallelisation {
+            Parallelisation::Root => self.search_root_parallel(board, rngs),
+            Parallelisation::Tree => self.search_tree_parallel(board, rngs),
+        }
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.self_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::PointPlacement;
+    use crate::player::uct::DEFAULT_EXPLORATION_CONSTANT;
+    use crate::referee::NaiveReferee;
+    use rstest::*;
+
+    fn build_player(
+        niterations: u32,
+        nworkers: u32,
+        parallelisation: Parallelisation,
+    ) -> ParallelUCTPlayer<'static> {
+        ParallelUCTPlayer::new(
+            DEFAULT_EXPLORATION_CONSTANT,
+            niterations,
+            nworkers,
+            1,
+            parallelisation,
+            Box::new(|| Box::new(NaiveReferee::new(3))),
+            0,
+        )
+    }
+
+    #[rstest]
+    fn visit_counts_sum_up_to_iterations(
+        #[values(Parallelisation::Root, Parallelisation::Tree)]
+        parallelisation: Parallelisation,
+        #[values(1, 3)] nworkers: u32,
+    ) {
+        let niterations = 500;
+        let board = GameState::new(3, 3, None);
+        let mut player = build_player(niterations, nworkers, parallelisation);
+        let placement = player.do_move(&board);
+        let total: f32 = placement.iter_2d().map(|(_, val)| val).sum();
+        #[allow(clippy::cast_precision_loss)]
+        let expected = niterations as f32;
+        assert!((total - expected).abs() < f32::EPSILON);
+    }
+
+    #[rstest]
+    #[case(Parallelisation::Root, 3)]
+    #[case(Parallelisation::Tree, 1)]
+    fn searches_are_reproducible_with_the_same_seed(
+        #[case] parallelisation: Parallelisation,
+        #[case] nworkers: u32,
+    ) {
+        let board = GameState::new(3, 3, None);
+        let search = || {
+            ParallelUCTPlayer::new_with_rng(
+                DEFAULT_EXPLORATION_CONSTANT,
+                300,
+                nworkers,
+                1,
+                parallelisation,
+                Box::new(|| Box::new(NaiveReferee::new(3))),
+                StdRng::seed_from_u64(7),
+                0,
+            )
+            .do_move(&board)
+        };
+        assert_eq!(search(), search());
+    }
+
+    #[rstest]
+    fn winning_moves_are_found(
+        #[values(Parallelisation::Root, Parallelisation::Tree)]
+        parallelisation: Parallelisation,
+    ) {
+        let board = GameState::new_with_values([
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ])
+        .unwrap();
+        let mut player = build_player(2000, 4, parallelisation);
+        let placement = player.do_move(&board);
+        let most_visited = placement
+            .iter_2d()
+            .max_by(|lhs, rhs| lhs.1.total_cmp(rhs.1))
+            .map(|(pp, _)| pp)
+            .unwrap();
+        assert_eq!(most_visited, PointPlacement { row: 0, column: 2 });
+    }
+}
@@ -0,0 +1,80 @@
+use crate::interfaces::{GameState, Placement, Player, PlayerID, TicTacToeReferee};
+use crate::solver::ProofNumberSearch;
+
+/// Player solving every position by proof-number search.
+///
+/// If the position is solved, the player makes the first move of a proving line and
+/// thus plays perfectly: it keeps a win or a draw, though not necessarily by the
+/// shortest way, and loses only if the position is lost. Otherwise, all legal moves are
+/// considered equally good.
+pub struct PerfectPlayer<'player> {
+    solver: ProofNumberSearch<'player>,
+    referee: &'player dyn TicTacToeReferee,
+    self_id: PlayerID,
+}
+
+impl<'player> PerfectPlayer<'player> {
+    /// Creates a player whose searches give up after `max_nodes` nodes.
+    #[must_use]
+    pub fn new(
+        max_nodes: usize,
+        other_id: PlayerID,
+        referee: &'player dyn TicTacToeReferee,
+        self_id: PlayerID,
+    ) -> Self {
+        Self {
+            solver: ProofNumberSearch::new(max_nodes, other_id, referee, self_id),
+            referee,
+            self_id,
+        }
+    }
+}
+
+impl Player for PerfectPlayer<'_> {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        let mut placements = Placement::new_from_existing(board, 0.0);
+        match self.solver.solve(board) {
+            Some(solution) if !solution.moves.is_empty() => {
+                placements[solution.moves[0]] = 1.0;
+            }
+            _ => {
+                for pp in self.referee.get_legal_moves(board) {
+                    placements[pp] = 1.0;
+                }
+            }
+        }
+        placements
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.self_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::PointPlacement;
+    use crate::layout::parse_mask;
+    use crate::referee::NaiveReferee;
+    use rstest::*;
+
+    #[rstest]
+    // winning
+    #[case("00.\n1..\n1..", PointPlacement { row: 0, column: 2 })]
+    // blocking
+    #[case("0..\n11.\n0..", PointPlacement { row: 1, column: 2 })]
+    // the only move keeping the draw
+    #[case("1..\n...\n...", PointPlacement { row: 1, column: 1 })]
+    fn perfect_moves_are_made(#[case] mask: &str, #[case] expected: PointPlacement) {
+        let referee = NaiveReferee::new(3);
+        let mut player = PerfectPlayer::new(1_000_000, 1, &referee, 0);
+        let placements = player.do_move(&parse_mask(mask).unwrap());
+        let chosen: Vec<_> = placements
+            .iter_2d()
+            .filter(|&(_, &weight)| weight > 0.0)
+            .map(|(pp, _)| pp)
+            .collect();
+        assert_eq!(chosen, vec![expected]);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::arena::exploring::ExploringTicTacToeArena;
+use crate::interfaces::{GameState, Placement, Player, PlayerID, TicTacToeReferee};
+use crate::player::countboundmcts::{CountBoundMCTSPlayer, SampleStatistics};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Handle to interrupt a running `TimeBoundMCTSPlayer::do_move` from another thread.
+///
+/// A stop request only interrupts the search running when it is issued. Requests
+/// issued between two searches are discarded when the next search starts.
+#[derive(Clone, Debug, Default)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Time-bound counterpart of `CountBoundMCTSPlayer`.
+///
+/// Instead of drawing a fixed number of samples, this player keeps sampling until its
+/// time budget for the current move is used up or it is stopped via its `StopHandle`.
+/// In both cases, the placements derived from the samples drawn so far are returned.
+pub struct TimeBoundMCTSPlayer<'player> {
+    id: PlayerID,
+    player0: &'player mut dyn Player,
+    player1: &'player mut dyn Player,
+    referee: &'player mut dyn TicTacToeReferee,
+    rng: StdRng,
+    stop_handle: StopHandle,
+    time_budget: Duration,
+}
+
+impl<'player> TimeBoundMCTSPlayer<'player> {
+    pub fn new(
+        id: PlayerID,
+        time_budget: Duration,
+        player0: &'player mut dyn Player,
+        player1: &'player mut dyn Player,
+        referee: &'player mut dyn TicTacToeReferee,
+    ) -> Self {
+        Self::new_with_rng(
+            id,
+            time_budget,
+            player0,
+            player1,
+            referee,
+            StdRng::from_os_rng(),
+        )
+    }
+
+    /// Creates a player whose samples are drawn with `rng`.
+    pub fn new_with_rng(
+        id: PlayerID,
+        time_budget: Duration,
+        player0: &'player mut dyn Player,
+        player1: &'player mut dyn Player,
+        referee: &'player mut dyn TicTacToeReferee,
+        rng: StdRng,
+    ) -> Self {
+        Self {
+            id,
+            player0,
+            player1,
+            referee,
+            rng,
+            stop_handle: StopHandle::default(),
+            time_budget,
+        }
+    }
+
+    #[must_use]
+    pub fn get_stop_handle(&self) -> StopHandle {
+        self.stop_handle.clone()
+    }
+
+    fn should_continue(&self, deadline: Instant) -> bool {
+        !self.stop_handle.is_stopped() && Instant::now() < deadline
+    }
+}
+
+impl Player for TimeBoundMCTSPlayer<'_> {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        let deadline = Instant::now() + self.time_budget;
+        self.stop_handle.reset();
+        let mut statistics = SampleStatistics::new(board, self.id);
+        let mut has_samples = false;
+
+        while self.should_continue(deadline) {
+            let mut my_arena = ExploringTicTacToeArena::new_with_rng(
+                board.clone(),
+                vec![&mut *self.player0, &mut *self.player1],
+                self.id,
+                &mut *self.referee,
+                StdRng::from_rng(&mut self.rng),
+            );
+
+            let (result, player_id, first_point_placement) =
+                CountBoundMCTSPlayer::do_one_step_sample(&mut my_arena);
+
+            let Some(pp) = first_point_placement else {
+                // No legal move is left, so further sampling is pointless.
+                break;
+            };
+            has_samples = true;
+            statistics.add(pp, result, player_id);
+        }
+
+        if !has_samples {
+            // Nothing is known yet, so all legal moves are equally good.
+            let mut placements = Placement::new_from_existing(board, 0.0);
+            for pp in self.referee.get_legal_moves(board) {
+                placements[pp] = 1.0;
+            }
+            return placements;
+        }
+        statistics.into_placement()
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::PointPlacement;
+    use crate::player::guessing::GuessingPlayer;
+    use crate::referee::{MisereReferee, NaiveReferee};
+
+    #[test]
+    fn winning_move_is_found() {
+        let board = GameState::new_with_values([
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ])
+        .unwrap();
+        let mut player0 = GuessingPlayer { id: 0 };
+        let mut player1 = GuessingPlayer { id: 1 };
+        let mut referee = NaiveReferee::new(3);
+        let mut player = TimeBoundMCTSPlayer::new(
+            0,
+            Duration::from_millis(50),
+            &mut player0,
+            &mut player1,
+            &mut referee,
+        );
+
+        let placement = player.do_move(&board);
+        let best = placement
+            .iter_2d()
+            .max_by(|lhs, rhs| lhs.1.total_cmp(rhs.1))
+            .map(|(pp, _)| pp)
+            .unwrap();
+        assert_eq!(best, PointPlacement { row: 0, column: 2 });
+    }
+
+    #[test]
+    fn completing_a_line_is_avoided_in_misere_play() {
+        let board = GameState::new_with_values([
+            [Some(0), Some(0), None],
+            [Some(1), None, Some(1)],
+            [None, Some(1), Some(0)],
+        ])
+        .unwrap();
+        let mut player0 = GuessingPlayer { id: 0 };
+        let mut player1 = GuessingPlayer { id: 1 };
+        let mut referee = MisereReferee::new(Box::new(NaiveReferee::new(3)));
+        let mut player = TimeBoundMCTSPlayer::new(
+            0,
+            Duration::from_millis(50),
+            &mut player0,
+            &mut player1,
+            &mut referee,
+        );
+
+        let placement = player.do_move(&board);
+        let best = placement
+            .iter_2d()
+            .max_by(|lhs, rhs| lhs.1.total_cmp(rhs.1))
+            .map(|(pp, _)| pp)
+            .unwrap();
+        assert_eq!(best, PointPlacement { row: 2, column: 0 });
+    }
+
+    #[test]
+    fn stopped_player_still_returns_free_cells() {
+        let board = GameState::new_with_values([
+            [Some(0), Some(1), None],
+            [None, None, None],
+            [None, None, None],
+        ])
+        .unwrap();
+        let mut player0 = GuessingPlayer { id: 0 };
+        let mut player1 = GuessingPlayer { id: 1 };
+        let mut referee = NaiveReferee::new(3);
+        let mut player = TimeBoundMCTSPlayer::new(
+            0,
+            Duration::from_hours(1),
+            &mut player0,
+            &mut player1,
+            &mut referee,
+        );
+
+        // Keep stopping until the search returns, as a request issued before the
+        // search starts is discarded.
+        let stop_handle = player.get_stop_handle();
+        let is_done = Arc::new(AtomicBool::new(false));
+        let stopper = {
+            let is_done = Arc::clone(&is_done);
+            std::thread::spawn(move || {
+                while !is_done.load(Ordering::Relaxed) {
+                    stop_handle.stop();
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            })
+        };
+        let start = Instant::now();
+        let placement = player.do_move(&board);
+        is_done.store(true, Ordering::Relaxed);
+        stopper.join().unwrap();
+        assert!(start.elapsed() < Duration::from_mins(1));
+        assert!(placement.iter_2d().any(|(_, &weight)| weight > 0.0));
+        for (pp, cell, weight) in board.joint_iter_2d(&placement) {
+            assert!(cell.is_free() || *weight == 0.0, "Wrong weight at {pp}");
+        }
+    }
+
+    #[test]
+    fn stop_requests_between_searches_are_discarded() {
+        let board = GameState::new(3, 3, None);
+        let mut player0 = GuessingPlayer { id: 0 };
+        let mut player1 = GuessingPlayer { id: 1 };
+        let mut referee = NaiveReferee::new(3);
+        let time_budget = Duration::from_millis(20);
+        let mut player = TimeBoundMCTSPlayer::new(
+            0,
+            time_budget,
+            &mut player0,
+            &mut player1,
+            &mut referee,
+        );
+
+        player.get_stop_handle().stop();
+        let start = Instant::now();
+        player.do_move(&board);
+        assert!(start.elapsed() >= time_budget);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::game_state_storage::GameStateStorage;
+use crate::interfaces::{
+    Evaluation, GameState, Placement, Player, PlayerID, SearchPosition,
+    TicTacToeReferee,
+};
+use crate::player::minmax::MinMaxPlayer;
+use std::time::{Duration, Instant};
+
+/// Time-bound counterpart of `MinMaxPlayer` using iterative deepening.
+///
+/// The player searches with a lookahead of 1, 2, 3 and so on, until its time budget
+/// for the current move is used up. It then plays according to the deepest search
+/// that was completed. A search with lookahead 1 is always completed, regardless of
+/// the budget.
+///
+/// All iterations share the storage of the wrapped `MinMaxPlayer`. Apart from
+/// transpositions, this allows to reuse wins and losses found by shallower
+/// iterations.
+pub struct TimeBoundMinMaxPlayer<'player, Storage, Position = GameState>
+where
+    Storage: GameStateStorage<Position, Evaluation>,
+    Position: SearchPosition,
+{
+    completed_depth: u32,
+    player: MinMaxPlayer<'player, Storage, Position>,
+    time_budget: Duration,
+}
+
+impl<'player, Storage, Position> TimeBoundMinMaxPlayer<'player, Storage, Position>
+where
+    Storage: GameStateStorage<Position, Evaluation>,
+    Position: SearchPosition,
+{
+    pub fn new(
+        other_id: PlayerID,
+        game_state_storage: Storage,
+        referee: &'player dyn TicTacToeReferee,
+        self_id: PlayerID,
+        time_budget: Duration,
+    ) -> Self {
+        Self {
+            completed_depth: 0,
+            player: MinMaxPlayer::new(
+                1,
+                other_id,
+                game_state_storage,
+                referee,
+                self_id,
+            ),
+            time_budget,
+        }
+    }
+
+    /// Returns the lookahead of the deepest search completed for the last move.
+    #[must_use]
+    pub fn get_completed_depth(&self) -> u32 {
+        self.completed_depth
+    }
+}
+
+impl<Storage, Position> Player for TimeBoundMinMaxPlayer<'_, Storage, Position>
+where
+    Storage: GameStateStorage<Position, Evaluation>,
+    Position: SearchPosition,
+{
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        let deadline = Instant::now() + self.time_budget;
+        let mut evaluations = self
+            .player
+            .get_evaluations_until(board, 1, None)
+            .expect("Searches without deadline are never aborted.");
+        self.completed_depth = 1;
+
+        // Searching deeper than the number of free cells does not reveal anything new.
+        let nfree_cells = board.iter_2d().filter(|(_, cell)| cell.is_free()).count();
+        let max_depth = u32::try_from(nfree_cells).unwrap_or(u32::MAX);
+        for depth in 2..=max_depth {
+            let Some(deeper_evaluations) =
+                self.player
+                    .get_evaluations_until(board, depth, Some(deadline))
+            else {
+                break;
+            };
+            evaluations = deeper_evaluations;
+            self.completed_depth = depth;
+        }
+        MinMaxPlayer::<Storage, Position>::to_placement(&evaluations)
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.player.get_id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state_storage::ZobristGameStateStorage;
+    use crate::referee::*;
+
+    #[test]
+    fn plays_like_full_depth_minmax_if_there_is_time() {
+        let board = GameState::new_with_values([
+            [Some(0), None, None],
+            [None, Some(1), None],
+            [None, None, None],
+        ])
+        .unwrap();
+        let referee = NaiveReferee::new(3);
+        let mut player = TimeBoundMinMaxPlayer::new(
+            1,
+            ZobristGameStateStorage::new(true),
+            &referee,
+            0,
+            Duration::from_mins(1),
+        );
+        let mut minmax_player =
+            MinMaxPlayer::new(7, 1, ZobristGameStateStorage::new(true), &referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(player.get_completed_depth(), 7);
+        assert_eq!(result, minmax_player.do_move(&board));
+    }
+
+    #[test]
+    fn lookahead_of_one_is_completed_without_time() {
+        let board = GameState::new(5, 5, None);
+        let referee = NaiveReferee::new(4);
+        let mut player = TimeBoundMinMaxPlayer::new(
+            1,
+            ZobristGameStateStorage::new(false),
+            &referee,
+            0,
+            Duration::ZERO,
+        );
+        let mut minmax_player =
+            MinMaxPlayer::new(1, 1, ZobristGameStateStorage::new(false), &referee, 0);
+
+        let result = player.do_move(&board);
+        assert_eq!(player.get_completed_depth(), 1);
+        assert_eq!(result, minmax_player.do_move(&board));
+    }
+
+    #[test]
+    fn searches_are_aborted_once_the_deadline_passed() {
+        let board = GameState::new(7, 7, None);
+        let referee = IncrementalReferee::new(4);
+        let mut player =
+            MinMaxPlayer::new(1, 1, ZobristGameStateStorage::new(false), &referee, 0);
+
+        let expired = Instant::now();
+        assert!(player.get_evaluations_until(&board, 1, None).is_some());
+        assert!(
+            player
+                .get_evaluations_until(&board, 2, Some(expired))
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,705 @@
+use crate::interfaces::{
+    BoardStateEntry, GameResult, GameState, Placement, Player, PlayerID,
+    PointPlacement, TicTacToeReferee,
+};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::collections::VecDeque;
+
+type NodeIndex = usize;
+
+const ROOT: NodeIndex = 0;
+
+/// Default exploration constant of UCB1, i.e. `sqrt(2)`.
+pub const DEFAULT_EXPLORATION_CONSTANT: f32 = std::f32::consts::SQRT_2;
+
+#[derive(Debug, Clone)]
+struct Node {
+    children: Vec<NodeIndex>,
+    /// The cell entry `placement` resulted in, which need not be a plain stone of
+    /// `player`, e.g. in ultimate tic-tac-toe.
+    entry: BoardStateEntry,
+    parent: Option<NodeIndex>,
+    /// The placement that led from the parent to this node.
+    placement: Option<PointPlacement>,
+    /// The player that made `placement`.
+    player: PlayerID,
+    /// The referee's verdict on `placement`.
+    result: GameResult,
+    untried: Vec<PointPlacement>,
+    /// Sum of rewards from the perspective of `player`.
+    value: f32,
+    visits: u32,
+}
+
+impl Node {
+    fn new(
+        board: &GameState,
+        parent: Option<NodeIndex>,
+        placement: Option<PointPlacement>,
+        player: PlayerID,
+        referee: &dyn TicTacToeReferee,
+        result: GameResult,
+    ) -> Self {
+        let untried = if result == GameResult::Undecided {
+            referee.get_legal_moves(board)
+        } else {
+            Vec::new()
+        };
+        Self {
+            children: Vec::new(),
+            entry: placement.map_or(None.into(), |pp| board[pp]),
+            parent,
+            placement,
+            player,
+            result,
+            untried,
+            value: 0.0,
+            visits: 0,
+        }
+    }
+
+    fn new_placeholder() -> Self {
+        Self {
+            children: Vec::new(),
+            entry: None.into(),
+            parent: None,
+            placement: None,
+            player: 0,
+            result: GameResult::Undecided,
+            untried: Vec::new(),
+            value: 0.0,
+            visits: 0,
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.result != GameResult::Undecided
+    }
+
+    fn is_fully_expanded(&self) -> bool {
+        self.untried.is_empty()
+    }
+}
+
+/// Outcome of a finished (simulated) game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Outcome {
+    winner: Option<PlayerID>,
+}
+
+impl Outcome {
+    /// Reward in `[0, 1]` for `player`, counting draws as half a win.
+    fn reward_for(self, player: PlayerID) -> f32 {
+        match self.winner {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+}
+
+/// The searching player and its opponent, who take turns.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Opponents {
+    other_id: PlayerID,
+    self_id: PlayerID,
+}
+
+impl Opponents {
+    pub(super) fn new(other_id: PlayerID, self_id: PlayerID) -> Self {
+        Self { other_id, self_id }
+    }
+
+    fn get_opponent(self, player: PlayerID) -> PlayerID {
+        if player == self.self_id {
+            self.other_id
+        } else {
+            self.self_id
+        }
+    }
+
+    /// Determines the winner of a game in which `player` caused `result`.
+    fn get_outcome(self, result: GameResult, player: PlayerID) -> Outcome {
+        let winner = match result {
+            GameResult::Victory => Some(player),
+            GameResult::Defeat | GameResult::IllegalMove => {
+                Some(self.get_opponent(player))
+            }
+            GameResult::Draw | GameResult::Undecided => None,
+        };
+        Outcome { winner }
+    }
+
+    /// Plays uniformly random moves, starting with `player`, until the game on `board`
+    /// is decided.
+    pub(super) fn rollout(
+        self,
+        board: &mut GameState,
+        mut player: PlayerID,
+        referee: &dyn TicTacToeReferee,
+        rng: &mut StdRng,
+    ) -> Outcome {
+        loop {
+            let legal_moves = referee.get_legal_moves(board);
+            if legal_moves.is_empty() {
+                return Outcome { winner: None };
+            }
+            let pp = legal_moves[rng.random_range(0..legal_moves.len())];
+            let result = referee.receive_move(board, pp, player);
+            if result != GameResult::Undecided {
+                return self.get_outcome(result, player);
+            }
+            if referee.is_turn_over(board) {
+                player = self.get_opponent(player);
+            }
+        }
+    }
+}
+
+/// Search tree of the UCT players, stored as an arena of nodes.
+///
+/// Visits are counted on the way down to a leaf, while the rewards are only added once
+/// the game of the leaf is finished. Thus, a leaf whose rollout is still running counts
+/// as lost for the nodes above it, which steers concurrent searches of the same tree
+/// to different leaves.
+#[derive(Debug, Clone)]
+pub(super) struct SearchTree {
+    nodes: Vec<Node>,
+    opponents: Opponents,
+}
+
+impl SearchTree {
+    /// Creates a tree of the single position `board`, on which the searching player is
+    /// to move.
+    pub(super) fn new(
+        board: &GameState,
+        opponents: Opponents,
+        referee: &dyn TicTacToeReferee,
+    ) -> Self {
+        let root = Node::new(
+            board,
+            None,
+            None,
+            opponents.other_id,
+            referee,
+            GameResult::Undecided,
+        );
+        Self {
+            nodes: vec![root],
+            opponents,
+        }
+    }
+
+    /// Returns the player who places the next stone on `board`, which is the position
+    /// of `node`.
+    pub(super) fn get_player_to_move(
+        &self,
+        node: NodeIndex,
+        board: &GameState,
+        referee: &dyn TicTacToeReferee,
+    ) -> PlayerID {
+        let last_player = self.nodes[node].player;
+        if node == ROOT {
+            self.opponents.self_id
+        } else if referee.is_turn_over(board) {
+            self.opponents.get_opponent(last_player)
+        } else {
+            last_player
+        }
+    }
+
+    /// Descends from the root to a leaf by UCB1 and adds a random untried move of the
+    /// leaf to the tree, playing all moves on `board`, which is the root position.
+    ///
+    /// Returns the node reached, whose visit has been counted.
+    pub(super) fn select_leaf(
+        &mut self,
+        board: &mut GameState,
+        referee: &dyn TicTacToeReferee,
+        exploration_constant: f32,
+        rng: &mut StdRng,
+    ) -> NodeIndex {
+        let mut node = ROOT;
+        self.nodes[node].visits += 1;
+        while !self.nodes[node].is_terminal()
+            && self.nodes[node].is_fully_expanded()
+            && !self.nodes[node].children.is_empty()
+        {
+            node = self.select_child(node, exploration_constant);
+            let cur = &mut self.nodes[node];
+            cur.visits += 1;
+            let pp = cur.placement.expect("Only the root has no placement.");
+            referee.receive_move(board, pp, cur.player);
+        }
+
+        if !self.nodes[node].is_terminal() && !self.nodes[node].is_fully_expanded() {
+            node = self.expand(node, board, referee, rng);
+            self.nodes[node].visits += 1;
+        }
+        node
+    }
+
+    /// Returns the outcome of the game at `leaf` if it is over.
+    pub(super) fn get_terminal_outcome(&self, leaf: NodeIndex) -> Option<Outcome> {
+        let cur = &self.nodes[leaf];
+        cur.is_terminal()
+            .then(|| self.opponents.get_outcome(cur.result, cur.player))
+    }
+
+    /// Adds the rewards of `outcome` to `leaf` and its ancestors.
+    pub(super) fn backpropagate(&mut self, leaf: NodeIndex, outcome: Outcome) {
+        let mut maybe_node = Some(leaf);
+        while let Some(node) = maybe_node {
+            let cur = &mut self.nodes[node];
+            cur.value += outcome.reward_for(cur.player);
+            maybe_node = cur.parent;
+        }
+    }
+
+    /// Returns the visits of the moves at the root.
+    pub(super) fn get_root_visits(&self, board: &GameState) -> Placement {
+        let mut placements = Placement::new_from_existing(board, 0.0);
+        for &child in &self.nodes[ROOT].children {
+            let cur = &self.nodes[child];
+            if let Some(pp) = cur.placement {
+                #[allow(clippy::cast_precision_loss)]
+                let visits = cur.visits as f32;
+                placements[pp] = visits;
+            }
+        }
+        placements
+    }
+
+    /// Returns the child of `node` with the highest UCB1 value.
+    fn select_child(&self, node: NodeIndex, exploration_constant: f32) -> NodeIndex {
+        let parent = &self.nodes[node];
+        #[allow(clippy::cast_precision_loss)]
+        let log_parent_visits = (parent.visits as f32).ln();
+        let ucb1 = |child: &Node| {
+            #[allow(clippy::cast_precision_loss)]
+            let visits = child.visits as f32;
+            child.value / visits
+                + exploration_constant * (log_parent_visits / visits).sqrt()
+        };
+        *parent
+            .children
+            .iter()
+            .max_by(|&&lhs, &&rhs| {
+                ucb1(&self.nodes[lhs]).total_cmp(&ucb1(&self.nodes[rhs]))
+            })
+            .expect("Only nodes with children are selected from.")
+    }
+
+    /// Adds a random untried move of `node` to the tree, playing it on `board`.
+    fn expand(
+        &mut self,
+        node: NodeIndex,
+        board: &mut GameState,
+        referee: &dyn TicTacToeReferee,
+        rng: &mut StdRng,
+    ) -> NodeIndex {
+        let player = self.get_player_to_move(node, board, referee);
+        let untried = &mut self.nodes[node].untried;
+        let idx = rng.random_range(0..untried.len());
+        let pp = untried.swap_remove(idx);
+        let result = referee.receive_move(board, pp, player);
+        let child = Node::new(board, Some(node), Some(pp), player, referee, result);
+        let child_index = self.nodes.len();
+        self.nodes.push(child);
+        self.nodes[node].children.push(child_index);
+        child_index
+    }
+
+    /// Returns the node reached from the root by placing `new_stones`, in any order.
+    fn find_descendant(
+        &self,
+        mut new_stones: Vec<(PointPlacement, BoardStateEntry)>,
+    ) -> Option<NodeIndex> {
+        let mut node = ROOT;
+        while !new_stones.is_empty() {
+            let (child, position) =
+                self.nodes[node].children.iter().find_map(|&child| {
+                    let child_node = &self.nodes[child];
+                    child_node
+                        .placement
+                        .and_then(|pp| {
+                            new_stones
+                                .iter()
+                                .position(|&cur| cur == (pp, child_node.entry))
+                        })
+                        .map(|position| (child, position))
+                })?;
+            new_stones.swap_remove(position);
+            node = child;
+        }
+        Some(node)
+    }
+
+    /// Replaces the tree by the subtree rooted at `new_root`.
+    fn extract_subtree(&mut self, new_root: NodeIndex) {
+        let mut old_nodes = std::mem::take(&mut self.nodes);
+        let mut queue = VecDeque::from([(new_root, None)]);
+        while let Some((old_index, new_parent)) = queue.pop_front() {
+            let new_index = self.nodes.len();
+            let mut node =
+                std::mem::replace(&mut old_nodes[old_index], Node::new_placeholder());
+            for &child in &node.children {
+                queue.push_back((child, Some(new_index)));
+            }
+            node.children.clear();
+            node.parent = new_parent;
+            if let Some(parent) = new_parent {
+                self.nodes[parent].children.push(new_index);
+            }
+            self.nodes.push(node);
+        }
+    }
+}
+
+/// Monte Carlo Tree Search player using the UCT algorithm.
+///
+/// In contrast to `CountBoundMCTSPlayer`, this player builds a search tree. Each
+/// iteration selects a leaf using UCB1, expands it by one node, finishes the game with
+/// uniformly random moves and propagates the result back to the root. The tree is kept
+/// between moves, so that the subtree of the position actually reached is reused.
+pub struct UCTPlayer<'player> {
+    exploration_constant: f32,
+    niterations: u32,
+    opponents: Opponents,
+    referee: &'player dyn TicTacToeReferee,
+    rng: StdRng,
+    root_board: Option<GameState>,
+    tree: Option<SearchTree>,
+}
+
+impl<'player> UCTPlayer<'player> {
+    #[must_use]
+    pub fn new(
+        exploration_constant: f32,
+        niterations: u32,
+        other_id: PlayerID,
+        referee: &'player dyn TicTacToeReferee,
+        self_id: PlayerID,
+    ) -> Self {
+        Self::new_with_rng(
+            exploration_constant,
+            niterations,
+            other_id,
+            referee,
+            StdRng::from_os_rng(),
+            self_id,
+        )
+    }
+
+    /// Creates a player drawing all random moves from `rng`.
+    #[must_use]
+    pub fn new_with_rng(
+        exploration_constant: f32,
+        niterations: u32,
+        other_id: PlayerID,
+        referee: &'player dyn TicTacToeReferee,
+        rng: StdRng,
+        self_id: PlayerID,
+    ) -> Self {
+        Self {
+            exploration_constant,
+            niterations,
+            opponents: Opponents::new(other_id, self_id),
+            referee,
+            rng,
+            root_board: None,
+            tree: None,
+        }
+    }
+
+    /// Makes `board` the new root, reusing the existing tree if possible.
+    fn prepare_root(&mut self, board: &GameState) {
+        if !self.advance_root(board) {
+            self.tree = Some(SearchTree::new(board, self.opponents, self.referee));
+        }
+        self.root_board = Some(board.clone());
+    }
+
+    /// Tries to find `board` in the subtree of the current root.
+    ///
+    /// On success, the subtree of the matching node becomes the new tree and `true` is
+    /// returned.
+    fn advance_root(&mut self, board: &GameState) -> bool {
+        let (Some(root_board), Some(tree)) = (&self.root_board, &mut self.tree) else {
+            return false;
+        };
+        if root_board.get_number_of_rows() != board.get_number_of_rows()
+            || root_board.get_number_of_columns() != board.get_number_of_columns()
+        {
+            return false;
+        }
+
+        let mut new_stones = Vec::new();
+        for (pp, old, new) in root_board.joint_iter_2d(board) {
+            if old == new {
+                continue;
+            }
+            if old.is_taken() {
+                // Stones never vanish in a game, so this is an unrelated position.
+                return false;
+            }
+            new_stones.push((pp, *new));
+        }
+
+        let Some(node) = tree.find_descendant(new_stones) else {
+            return false;
+        };
+        if tree.nodes[node].is_terminal()
+            || (node != ROOT
+                && tree.get_player_to_move(node, board, self.referee)
+                    != self.opponents.self_id)
+        {
+            return false;
+        }
+        tree.extract_subtree(node);
+        true
+    }
+}
+
+impl Player for UCTPlayer<'_> {
+    fn do_move(&mut self, board: &GameState) -> Placement {
+        self.prepare_root(board);
+        let tree = self
+            .tree
+            .as_mut()
+            .expect("The root has just been prepared.");
+        for _ in 0..self.niterations {
+            let mut leaf_board = board.clone();
+            let leaf = tree.select_leaf(
+                &mut leaf_board,
+                self.referee,
+                self.exploration_constant,
+                &mut self.rng,
+            );
+            let outcome = tree.get_terminal_outcome(leaf).unwrap_or_else(|| {
+                let player = tree.get_player_to_move(leaf, &leaf_board, self.referee);
+                self.opponents.rollout(
+                    &mut leaf_board,
+                    player,
+                    self.referee,
+                    &mut self.rng,
+                )
+            });
+            tree.backpropagate(leaf, outcome);
+        }
+        tree.get_root_visits(board)
+    }
+
+    fn get_id(&self) -> PlayerID {
+        self.opponents.self_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::referee::{
+        ConnectReferee, GravityReferee, NaiveReferee, TurnSchedule, UltimateReferee,
+        ultimate_referee,
+    };
+    use rstest::*;
+
+    fn get_tree<'player>(player: &'player UCTPlayer<'_>) -> &'player SearchTree {
+        player.tree.as_ref().unwrap()
+    }
+
+    fn get_most_visited(placement: &Placement) -> PointPlacement {
+        placement
+            .iter_2d()
+            .max_by(|lhs, rhs| lhs.1.total_cmp(rhs.1))
+            .map(|(pp, _)| pp)
+            .unwrap()
+    }
+
+    #[rstest]
+    // direct winning move
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement { row: 0, column: 2 },
+    )]
+    // blocking the opponent
+    #[case(GameState::new_with_values(
+        [
+            [Some(1), None, None],
+            [Some(1), Some(0), None],
+            [None, None, Some(0)],
+        ]
+    ).unwrap(),
+        PointPlacement { row: 2, column: 0 },
+    )]
+    fn correct_moves_are_found(
+        #[case] board: GameState,
+        #[case] expected: PointPlacement,
+    ) {
+        let referee = NaiveReferee::new(3);
+        let mut player =
+            UCTPlayer::new(DEFAULT_EXPLORATION_CONSTANT, 2000, 1, &referee, 0);
+        let placement = player.do_move(&board);
+        assert_eq!(get_most_visited(&placement), expected);
+    }
+
+    #[test]
+    fn visit_counts_sum_up_to_iterations() {
+        let referee = NaiveReferee::new(3);
+        let niterations = 500;
+        let board = GameState::new(3, 3, None);
+        let mut player =
+            UCTPlayer::new(DEFAULT_EXPLORATION_CONSTANT, niterations, 1, &referee, 0);
+        let placement = player.do_move(&board);
+        let total: f32 = placement.iter_2d().map(|(_, val)| val).sum();
+        #[allow(clippy::cast_precision_loss)]
+        let expected = niterations as f32;
+        assert!((total - expected).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn both_stones_of_a_turn_are_searched() {
+        // Player 0 places two stones and can only win in the top row.
+        let board = GameState::new_with_values([
+            [Some(0), Some(0), None, None, None],
+            [None, None, None, None, None],
+            [Some(1), Some(1), None, None, None],
+            [None, None, None, None, None],
+            [Some(1), None, Some(1), None, Some(0)],
+        ])
+        .unwrap();
+        let referee = ConnectReferee::new(
+            Box::new(NaiveReferee::new(4)),
+            TurnSchedule::new(1, 2),
+        );
+        let mut player = UCTPlayer::new_with_rng(
+            DEFAULT_EXPLORATION_CONSTANT,
+            5000,
+            1,
+            &referee,
+            StdRng::seed_from_u64(0),
+            0,
+        );
+        let placement = player.do_move(&board);
+
+        let most_visited = get_most_visited(&placement);
+        assert_eq!(most_visited.row, 0);
+        assert!([2, 3].contains(&most_visited.column));
+    }
+
+    #[test]
+    fn only_legal_moves_are_visited_with_gravity() {
+        let board = GameState::new_with_values([
+            [None, None, None, None],
+            [None, None, None, None],
+            [None, None, None, None],
+            [None, Some(1), Some(0), None],
+        ])
+        .unwrap();
+        let referee = GravityReferee::new(Box::new(NaiveReferee::new(4)));
+        let mut player =
+            UCTPlayer::new(DEFAULT_EXPLORATION_CONSTANT, 200, 1, &referee, 0);
+        let placement = player.do_move(&board);
+
+        let legal_moves = referee.get_legal_moves(&board);
+        for (pp, visits) in placement.iter_2d() {
+            assert_eq!(
+                legal_moves.contains(&pp),
+                *visits > 0.0,
+                "Wrong visits at {pp}"
+            );
+        }
+    }
+
+    #[test]
+    fn searches_are_reproducible_with_the_same_seed() {
+        let referee = NaiveReferee::new(3);
+        let board = GameState::new(3, 3, None);
+        let search = |seed| {
+            let mut player = UCTPlayer::new_with_rng(
+                DEFAULT_EXPLORATION_CONSTANT,
+                100,
+                1,
+                &referee,
+                StdRng::seed_from_u64(seed),
+                0,
+            );
+            player.do_move(&board)
+        };
+        assert_eq!(search(7), search(7));
+    }
+
+    #[test]
+    fn tree_is_reused_between_moves() {
+        let referee = NaiveReferee::new(3);
+        let niterations = 500;
+        let mut board = GameState::new(3, 3, None);
+        let mut player =
+            UCTPlayer::new(DEFAULT_EXPLORATION_CONSTANT, niterations, 1, &referee, 0);
+        let placement = player.do_move(&board);
+        let own_move = get_most_visited(&placement);
+        board[own_move] = Some(0).into();
+        let other_move = referee.get_legal_moves(&board)[0];
+        board[other_move] = Some(1).into();
+
+        player.do_move(&board);
+        assert!(get_tree(&player).nodes[ROOT].visits > niterations);
+    }
+
+    #[test]
+    fn tree_is_reused_in_ultimate_tic_tac_toe() {
+        // Stones of ultimate tic-tac-toe tell their move number as well.
+        let referee = UltimateReferee::new();
+        let niterations = 200;
+        let mut board = GameState::new(
+            ultimate_referee::BOARD_SIZE,
+            ultimate_referee::BOARD_SIZE,
+            None,
+        );
+        let mut player = UCTPlayer::new_with_rng(
+            DEFAULT_EXPLORATION_CONSTANT,
+            niterations,
+            1,
+            &referee,
+            StdRng::seed_from_u64(0),
+            0,
+        );
+        let placement = player.do_move(&board);
+        let own_move = get_most_visited(&placement);
+        referee.receive_move(&mut board, own_move, 0);
+        // Answer with a move the tree already knows.
+        let own_node = get_tree(&player).nodes[ROOT]
+            .children
+            .iter()
+            .find(|&&child| get_tree(&player).nodes[child].placement == Some(own_move))
+            .copied()
+            .unwrap();
+        let other_node = get_tree(&player).nodes[own_node].children[0];
+        let other_move = get_tree(&player).nodes[other_node].placement.unwrap();
+        referee.receive_move(&mut board, other_move, 1);
+
+        player.do_move(&board);
+        assert!(get_tree(&player).nodes[ROOT].visits > niterations);
+    }
+
+    #[test]
+    fn unrelated_position_discards_tree() {
+        let referee = NaiveReferee::new(3);
+        let niterations = 100;
+        let board = GameState::new(3, 3, None);
+        let mut player =
+            UCTPlayer::new(DEFAULT_EXPLORATION_CONSTANT, niterations, 1, &referee, 0);
+        player.do_move(&board);
+
+        let mut other_board = GameState::new(3, 3, None);
+        other_board[PointPlacement { row: 1, column: 1 }] = Some(1).into();
+        player.do_move(&other_board);
+        assert_eq!(get_tree(&player).nodes[ROOT].visits, niterations);
+    }
+}
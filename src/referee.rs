@@ -1,6 +1,28 @@
+pub mod bitboard_referee;
+pub mod connect_referee;
 pub mod faster_referee_v1;
+pub mod gomoku_referee;
+pub mod gravity_referee;
+pub mod grid;
+pub mod hex_referee;
+pub mod incremental_referee;
+pub mod misere_referee;
 pub mod naive_referee;
+pub mod nd_referee;
+pub mod topology;
+pub mod ultimate_referee;
 
+pub use bitboard_referee::BitBoardReferee;
+pub use connect_referee::{ConnectReferee, TurnSchedule};
 #[allow(unused_imports)]
 pub use faster_referee_v1::FasterRefereeV1;
+pub use gomoku_referee::{GomokuReferee, RuleSet};
+pub use gravity_referee::GravityReferee;
+pub use grid::Grid;
+pub use hex_referee::HexReferee;
+pub use incremental_referee::IncrementalReferee;
+pub use misere_referee::MisereReferee;
 pub use naive_referee::NaiveReferee;
+pub use nd_referee::{NdReferee, get_cells_per_dimension, get_embedding_size};
+pub use topology::Topology;
+pub use ultimate_referee::{UltimateReferee, UltimateState};
@@ -0,0 +1,175 @@
+use crate::bitboard::BitBoard;
+use crate::interfaces::{GameResult, PlayerID, PointPlacement, WinLengthT};
+
+/// Referee for games played on a `BitBoard`.
+///
+/// It follows the same rules as `NaiveReferee`, but detects lines by shifting bitsets,
+/// which needs neither allocations nor a scan over all cells.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct BitBoardReferee {
+    winning_length: WinLengthT,
+}
+
+impl BitBoardReferee {
+    #[must_use]
+    pub fn new(winning_length: WinLengthT) -> Self {
+        Self { winning_length }
+    }
+
+    /// Places a stone of `player_id` on `board` and judges the resulting position.
+    pub fn receive_move(
+        &self,
+        board: &mut BitBoard,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        if placement.row >= board.get_number_of_rows()
+            || placement.column >= board.get_number_of_columns()
+        {
+            // Placement is out of bounds.
+            return GameResult::IllegalMove;
+        }
+        if !board.is_free(placement) || !board.get_players().contains(&player_id) {
+            // There is already a player on this cell or the player is unknown.
+            return GameResult::IllegalMove;
+        }
+        board.place(placement, player_id);
+        if board.has_line(player_id, self.winning_length) {
+            return GameResult::Victory;
+        }
+        if board.is_full() {
+            return GameResult::Draw;
+        }
+        GameResult::Undecided
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::GameState;
+    use rstest::*;
+
+    #[rstest]
+    // horizontal
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(0), Some(0), None],
+            [None, Some(1), Some(1), None],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        3,
+        0,
+        GameResult::Victory
+    )]
+    // vertical
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(0), Some(1)],
+            [None, Some(0), Some(1)],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 2},
+        3,
+        1,
+        GameResult::Victory
+    )]
+    // slash diagonal
+    #[case(GameState::new_with_values(
+        [
+            [None, None, Some(1), Some(0)],
+            [None, None, Some(0), Some(1)],
+            [None, Some(0), Some(1), None],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 3, column: 0},
+        4,
+        0,
+        GameResult::Victory
+    )]
+    // backslash diagonal
+    #[case(GameState::new_with_values(
+        [
+            [None,    None,    None,    None,    None,    None],
+            [None,    None,    None,    None,    None,    None],
+            [Some(0), None,    None,    None,    None,    None],
+            [Some(1), Some(0), None,    None,    None,    None],
+            [None,    Some(1), Some(0), None,    None,    None],
+            [None,    None,    Some(1), Some(0), None,    None],
+            [None,    None,    None,    Some(1), Some(0), None],
+            [None,    None,    None,    None,    Some(1), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 7, column: 5},
+        6,
+        0,
+        GameResult::Victory,
+    )]
+    // Illegal move - cell already taken
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), None],
+            [None, None]
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        2,
+        1,
+        GameResult::IllegalMove,
+    )]
+    // Illegal move - placement out of bounds
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(1)],
+            [None, None]
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 0},
+        2,
+        0,
+        GameResult::IllegalMove,
+    )]
+    // undecided
+    #[case(GameState::new_with_values(
+        [
+            [None, None, Some(1), Some(0)],
+            [None, None, Some(0), Some(1)],
+            [None, Some(0), Some(1), None],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 0},
+        4,
+        1,
+        GameResult::Undecided,
+    )]
+    // draw
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(1), Some(0)],
+            [Some(0), Some(1), Some(1)],
+            [Some(1), Some(0), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 2},
+        3,
+        0,
+        GameResult::Draw,
+    )]
+    fn referee_judges_board_correctly(
+        #[case] board: GameState,
+        #[case] next_move: PointPlacement,
+        #[case] winning_length: WinLengthT,
+        #[case] player: PlayerID,
+        #[case] expected: GameResult,
+    ) {
+        let mut board = BitBoard::from_game_state(&board, [0, 1]).unwrap();
+        let referee = BitBoardReferee::new(winning_length);
+        let result = referee.receive_move(&mut board, next_move, player);
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::interfaces::{
+    GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee,
+};
+
+/// Number of stones placed per turn in games of the connect(m,n,k,p,q) family.
+///
+/// The first turn consists of `first_turn_stones` (q) stones, every later turn of
+/// `stones_per_turn` (p) stones. E.g. Connect6 uses one stone on the first turn and two
+/// stones on all others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnSchedule {
+    pub first_turn_stones: u16,
+    pub stones_per_turn: u16,
+}
+
+impl TurnSchedule {
+    #[must_use]
+    pub fn new(first_turn_stones: u16, stones_per_turn: u16) -> Self {
+        Self {
+            first_turn_stones,
+            stones_per_turn,
+        }
+    }
+
+    /// Returns whether a turn ends with the stone with the given number.
+    ///
+    /// Stones are counted from 1, starting with the first stone of the game.
+    #[must_use]
+    pub fn is_last_stone_of_turn(&self, nstones: usize) -> bool {
+        let first_turn_stones = usize::from(self.first_turn_stones);
+        if nstones <= first_turn_stones {
+            return nstones == first_turn_stones;
+        }
+        (nstones - first_turn_stones).is_multiple_of(usize::from(self.stones_per_turn))
+    }
+}
+
+impl Default for TurnSchedule {
+    /// Returns the schedule of games with one stone per turn.
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+/// Referee for games in which a turn consists of several stones.
+///
+/// Placements are judged by the wrapped referee. Which stone of a turn the last one
+/// was is told by the number of stones on the board, so boards are expected to be
+/// reached by playing from an empty board.
+pub struct ConnectReferee {
+    referee: Box<dyn TicTacToeReferee>,
+    turn_schedule: TurnSchedule,
+}
+
+impl ConnectReferee {
+    #[must_use]
+    pub fn new(
+        referee: Box<dyn TicTacToeReferee>,
+        turn_schedule: TurnSchedule,
+    ) -> Self {
+        Self {
+            referee,
+            turn_schedule,
+        }
+    }
+}
+
+impl TicTacToeReferee for ConnectReferee {
+    fn receive_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        self.referee.receive_move(board, placement, player_id)
+    }
+
+    fn receive_counted_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+        nfree_cells: usize,
+    ) -> GameResult {
+        self.referee
+            .receive_counted_move(board, placement, player_id, nfree_cells)
+    }
+
+    fn get_legal_moves(&self, board: &GameState) -> Vec<PointPlacement> {
+        self.referee.get_legal_moves(board)
+    }
+
+    fn get_winner(&self, board: &GameState) -> Option<PlayerID> {
+        self.referee.get_winner(board)
+    }
+
+    fn is_turn_over(&self, board: &GameState) -> bool {
+        let nstones = board.iter_2d().filter(|(_, cell)| cell.has_stone()).count();
+        self.turn_schedule.is_last_stone_of_turn(nstones)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::parse_mask;
+    use crate::referee::NaiveReferee;
+    use rstest::*;
+
+    #[rstest]
+    #[case(TurnSchedule::default(), &[true, true, true, true, true])]
+    #[case(TurnSchedule::new(1, 2), &[true, false, true, false, true])]
+    #[case(TurnSchedule::new(2, 3), &[false, true, false, false, true])]
+    fn turns_end_according_to_the_schedule(
+        #[case] turn_schedule: TurnSchedule,
+        #[case] expected: &[bool],
+    ) {
+        let is_last_stone: Vec<bool> = (1..=expected.len())
+            .map(|nstones| turn_schedule.is_last_stone_of_turn(nstones))
+            .collect();
+        assert_eq!(is_last_stone, expected);
+    }
+
+    #[test]
+    fn turns_are_told_by_the_stones_on_the_board() {
+        let referee = ConnectReferee::new(
+            Box::new(NaiveReferee::new(6)),
+            TurnSchedule::new(1, 2),
+        );
+        let mut board = GameState::new(4, 4, None);
+        let moves = [(0, 0, 0), (1, 1, 1), (1, 2, 1), (2, 2, 0)];
+
+        let mut is_turn_over = Vec::new();
+        for (row, column, player) in moves {
+            let result = referee.receive_move(
+                &mut board,
+                PointPlacement { row, column },
+                player,
+            );
+            assert_eq!(result, GameResult::Undecided);
+            is_turn_over.push(referee.is_turn_over(&board));
+        }
+        assert_eq!(is_turn_over, [true, false, true, false]);
+    }
+
+    #[test]
+    fn blocked_cells_are_no_stones() {
+        let referee = ConnectReferee::new(
+            Box::new(NaiveReferee::new(6)),
+            TurnSchedule::new(1, 2),
+        );
+        let board = parse_mask("0#\n##").unwrap();
+        assert!(referee.is_turn_over(&board));
+    }
+}
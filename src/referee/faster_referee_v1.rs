@@ -1,7 +1,7 @@
 use crate::interfaces::{
-    BoardSizeT, GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee,
-    WinLengthT,
+    GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee, WinLengthT,
 };
+use crate::referee::{Grid, Topology};
 
 const DELTAS: [Direction; 4] = [
     Direction {
@@ -28,6 +28,8 @@ const DELTAS: [Direction; 4] = [
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct FasterRefereeV1 {
+    grid: Grid,
+    topology: Topology,
     winning_length: WinLengthT,
 }
 
@@ -39,10 +41,13 @@ struct Direction {
 
 impl Direction {
     #[inline]
-    fn add(&self, other: PointPlacement) -> (i32, i32) {
-        let row = i32::from(other.row) + self.row_delta;
-        let column = i32::from(other.column) + self.column_delta;
-        (row, column)
+    fn add(
+        &self,
+        other: PointPlacement,
+        board: &GameState,
+        topology: Topology,
+    ) -> Option<PointPlacement> {
+        topology.translate(board, other, self.row_delta, self.column_delta)
     }
 }
 
@@ -50,7 +55,21 @@ impl Direction {
 impl FasterRefereeV1 {
     #[must_use]
     pub fn new(winning_length: u16) -> Self {
-        Self { winning_length }
+        Self::new_with_topology(Topology::Bounded, winning_length)
+    }
+
+    #[must_use]
+    pub fn new_with_topology(topology: Topology, winning_length: u16) -> Self {
+        Self::new_with_grid(Grid::Square, topology, winning_length)
+    }
+
+    #[must_use]
+    pub fn new_with_grid(grid: Grid, topology: Topology, winning_length: u16) -> Self {
+        Self {
+            grid,
+            topology,
+            winning_length,
+        }
     }
 
     fn evaluate_board(&self, board: &GameState, player: PlayerID) -> GameResult {
@@ -58,7 +77,10 @@ impl FasterRefereeV1 {
 
         for (pp, value) in board.iter_2d() {
             has_free_cells |= value.is_free();
-            for cur in &DELTAS {
+            for cur in DELTAS
+                .iter()
+                .filter(|cur| self.grid.has_line(cur.row_delta, cur.column_delta))
+            {
                 if self.has_winning_state_in_direction(cur, pp, board, player) {
                     return GameResult::Victory;
                 }
@@ -80,21 +102,18 @@ impl FasterRefereeV1 {
         if board[start_pp] != Some(player).into() {
             return false;
         }
-        let max_row = i32::from(board.get_number_of_rows());
-        let max_column = i32::from(board.get_number_of_columns());
+        let maximum_length = self.topology.get_maximum_line_length(
+            board,
+            direction.row_delta,
+            direction.column_delta,
+        );
+        if usize::from(self.winning_length) > maximum_length {
+            return false;
+        }
         let mut cur_pp = start_pp;
         for _ in 1..self.winning_length {
-            let (row, column) = direction.add(cur_pp);
-            if row < 0 || column < 0 || row >= max_row || column >= max_column {
+            let Some(new_pp) = direction.add(cur_pp, board, self.topology) else {
                 return false;
-            }
-            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-            let new_pp = PointPlacement {
-                // We know that row and column are positive. We also know that they
-                // are less than max_row and max_column. Thus, we can safely cast
-                // them to BoardSizeT.
-                row: row as BoardSizeT,
-                column: column as BoardSizeT,
             };
             if board[new_pp] != Some(player).into() {
                 return false;
@@ -240,4 +259,125 @@ mod tests {
         let result = referee.receive_move(&mut board, next_move, player);
         assert_eq!(result, expected);
     }
+
+    #[rstest]
+    // horizontal, wrapping around the right edge
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), None, None, Some(0)],
+            [Some(1), Some(1), None, None],
+            [None, None, None, None],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 2},
+        3,
+        0,
+        GameResult::Victory
+    )]
+    // vertical, wrapping around the bottom edge
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(1), None, None],
+            [Some(0), None, None, None],
+            [Some(0), None, None, None],
+            [None, Some(1), None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 1},
+        3,
+        1,
+        GameResult::Victory
+    )]
+    // slash diagonal, wrapping around a corner
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(1), None, None],
+            [None, Some(0), None, None],
+            [None, None, None, Some(1)],
+            [None, None, None, Some(0)],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        3,
+        0,
+        GameResult::Victory
+    )]
+    // backslash diagonal, wrapping around the left edge
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None, None],
+            [None, None, None, Some(1)],
+            [None, None, Some(1), None],
+            [Some(0), Some(0), None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        3,
+        1,
+        GameResult::Victory
+    )]
+    // a line around the whole torus does not count stones twice
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 2},
+        4,
+        0,
+        GameResult::Undecided
+    )]
+    // Illegal move - cell already taken
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), None],
+            [None, None]
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        2,
+        1,
+        GameResult::IllegalMove,
+    )]
+    // Illegal move - placement out of bounds
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(1)],
+            [None, None]
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 0},
+        2,
+        0,
+        GameResult::IllegalMove,
+    )]
+    // draw
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), Some(1), Some(1)],
+            [Some(1), Some(1), Some(0), Some(0)],
+            [Some(0), Some(0), Some(1), Some(1)],
+            [Some(1), Some(1), Some(0), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 3, column: 3},
+        3,
+        0,
+        GameResult::Draw,
+    )]
+    fn torus_referee_judges_board_correctly(
+        #[case] mut board: GameState,
+        #[case] next_move: PointPlacement,
+        #[case] winning_length: WinLengthT,
+        #[case] player: PlayerID,
+        #[case] expected: GameResult,
+    ) {
+        let referee =
+            FasterRefereeV1::new_with_topology(Topology::Torus, winning_length);
+        let result = referee.receive_move(&mut board, next_move, player);
+        assert_eq!(result, expected);
+    }
 }
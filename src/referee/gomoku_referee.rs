@@ -0,0 +1,424 @@
+use crate::interfaces::{
+    BoardSizeT, GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee,
+    WinLengthT,
+};
+
+const DIRECTIONS: [(i32, i32); 4] = [
+    // horizontal
+    (0, 1),
+    // vertical
+    (1, 0),
+    // slash diagonal
+    (1, 1),
+    // backslash diagonal
+    (1, -1),
+];
+
+/// Rules deciding which lines win a game of gomoku.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RuleSet {
+    /// Lines of at least `winning_length` stones win.
+    #[default]
+    Freestyle,
+    /// Only lines of exactly `winning_length` stones win, longer ones do not count.
+    Standard,
+    /// The first player wins with lines of exactly `winning_length` stones only and
+    /// must neither make longer lines nor two fours or two open threes at once.
+    /// Every other player wins with lines of at least `winning_length` stones.
+    Renju,
+}
+
+/// Content of a cell on a line through the last placement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineCell {
+    Own,
+    Free,
+    Blocked,
+}
+
+/// Cells of a line through a placement, with the placement in the middle.
+///
+/// The line reaches `winning_length` cells in both directions. This suffices to tell
+/// whether a run of stones through the placement is longer than `winning_length`.
+struct Line {
+    cells: Vec<LineCell>,
+    center: usize,
+}
+
+impl Line {
+    /// Returns the first and last index of the run of own stones through the center.
+    fn get_run(&self) -> (usize, usize) {
+        let mut start = self.center;
+        while start > 0 && self.cells[start - 1] == LineCell::Own {
+            start -= 1;
+        }
+        let mut end = self.center;
+        while end + 1 < self.cells.len() && self.cells[end + 1] == LineCell::Own {
+            end += 1;
+        }
+        (start, end)
+    }
+
+    fn get_run_length(&self) -> usize {
+        let (start, end) = self.get_run();
+        end - start + 1
+    }
+
+    /// Returns the fours through the center, each with the cell completing it.
+    ///
+    /// A four is given by the first and last index of the stones it consists of. It
+    /// is completed by a free cell turning it into a run of exactly `winning_length`
+    /// stones. An open four, which can be completed on both ends, is listed twice.
+    fn get_fours(&mut self, winning_length: usize) -> Vec<((usize, usize), usize)> {
+        let mut fours = Vec::new();
+        let reach = winning_length - 1;
+        let first = self.center.saturating_sub(reach);
+        let last = (self.center + reach).min(self.cells.len() - 1);
+        for completion in first..=last {
+            if self.cells[completion] != LineCell::Free {
+                continue;
+            }
+            self.cells[completion] = LineCell::Own;
+            let (start, end) = self.get_run();
+            if end - start + 1 == winning_length {
+                let four = if completion == start {
+                    (start + 1, end)
+                } else if completion == end {
+                    (start, end - 1)
+                } else {
+                    (start, end)
+                };
+                fours.push((four, completion));
+            }
+            self.cells[completion] = LineCell::Free;
+        }
+        fours
+    }
+
+    /// Returns the number of distinct fours through the center.
+    fn count_fours(&mut self, winning_length: usize) -> usize {
+        let mut fours: Vec<_> = self
+            .get_fours(winning_length)
+            .into_iter()
+            .map(|(four, _)| four)
+            .collect();
+        fours.sort_unstable();
+        fours.dedup();
+        fours.len()
+    }
+
+    /// Returns whether the line holds an open four through the center.
+    fn has_open_four(&mut self, winning_length: usize) -> bool {
+        let fours = self.get_fours(winning_length);
+        fours.iter().enumerate().any(|(n, (four, _))| {
+            fours[n + 1..]
+                .iter()
+                .any(|(other_four, _)| other_four == four)
+        })
+    }
+
+    /// Returns whether the line holds an open three through the center.
+    ///
+    /// A three is open if a single stone turns it into an open four. Lines holding a
+    /// four already are no threes. Whether the stone making the open four would be
+    /// forbidden itself is not considered.
+    fn has_open_three(&mut self, winning_length: usize) -> bool {
+        if self.count_fours(winning_length) > 0 {
+            return false;
+        }
+        for index in 0..self.cells.len() {
+            if self.cells[index] != LineCell::Free {
+                continue;
+            }
+            self.cells[index] = LineCell::Own;
+            let is_open_four = self.get_run_length() <= winning_length
+                && self.has_open_four(winning_length);
+            self.cells[index] = LineCell::Free;
+            if is_open_four {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Referee for gomoku and renju, which distinguish lines by their exact length.
+///
+/// Like `IncrementalReferee`, only the lines through the last placement are checked.
+/// Forbidden placements of the first player are judged `IllegalMove` and are not
+/// kept on the board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GomokuReferee {
+    first_player: PlayerID,
+    rule_set: RuleSet,
+    winning_length: WinLengthT,
+}
+
+impl GomokuReferee {
+    /// Creates a new referee, with `first_player` being restricted by the renju rules.
+    #[must_use]
+    pub fn new(
+        first_player: PlayerID,
+        rule_set: RuleSet,
+        winning_length: WinLengthT,
+    ) -> Self {
+        Self {
+            first_player,
+            rule_set,
+            winning_length,
+        }
+    }
+
+    fn get_line(
+        &self,
+        board: &GameState,
+        placement: PointPlacement,
+        (row_delta, column_delta): (i32, i32),
+        player: PlayerID,
+    ) -> Line {
+        let reach = i32::from(self.winning_length);
+        let max_row = i32::from(board.get_number_of_rows());
+        let max_column = i32::from(board.get_number_of_columns());
+        let cells = (-reach..=reach)
+            .map(|factor| {
+                let row = i32::from(placement.row) + factor * row_delta;
+                let column = i32::from(placement.column) + factor * column_delta;
+                if row < 0 || column < 0 || row >= max_row || column >= max_column {
+                    return LineCell::Blocked;
+                }
+                #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+                let pp = PointPlacement {
+                    // We know that row and column are positive. We also know that
+                    // they are less than max_row and max_column. Thus, we can safely
+                    // cast them to BoardSizeT.
+                    row: row as BoardSizeT,
+                    column: column as BoardSizeT,
+                };
+                match Option::<PlayerID>::from(board[pp]) {
+                    None => LineCell::Free,
+                    Some(id) if id == player => LineCell::Own,
+                    Some(_) => LineCell::Blocked,
+                }
+            })
+            .collect();
+        Line {
+            cells,
+            center: usize::from(self.winning_length),
+        }
+    }
+
+    fn evaluate_placement(
+        &self,
+        board: &GameState,
+        placement: PointPlacement,
+        player: PlayerID,
+    ) -> GameResult {
+        let winning_length = usize::from(self.winning_length);
+        let mut lines: Vec<Line> = DIRECTIONS
+            .iter()
+            .map(|&direction| self.get_line(board, placement, direction, player))
+            .collect();
+        let is_restricted =
+            self.rule_set == RuleSet::Renju && player == self.first_player;
+        let run_lengths: Vec<usize> = lines.iter().map(Line::get_run_length).collect();
+        let is_victory = match self.rule_set {
+            RuleSet::Standard => run_lengths.contains(&winning_length),
+            RuleSet::Renju if is_restricted => run_lengths.contains(&winning_length),
+            RuleSet::Freestyle | RuleSet::Renju => {
+                run_lengths.iter().any(|&length| length >= winning_length)
+            }
+        };
+        if is_victory {
+            return GameResult::Victory;
+        }
+        if is_restricted {
+            let has_overline =
+                run_lengths.iter().any(|&length| length > winning_length);
+            let nfours: usize = lines
+                .iter_mut()
+                .map(|line| line.count_fours(winning_length))
+                .sum();
+            let nopen_threes = lines
+                .iter_mut()
+                .map(|line| line.has_open_three(winning_length))
+                .filter(|&has_open_three| has_open_three)
+                .count();
+            if has_overline || nfours >= 2 || nopen_threes >= 2 {
+                return GameResult::IllegalMove;
+            }
+        }
+        if board.iter_2d().all(|(_, cell)| cell.is_taken()) {
+            return GameResult::Draw;
+        }
+        GameResult::Undecided
+    }
+}
+
+impl TicTacToeReferee for GomokuReferee {
+    fn receive_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        if placement.row >= board.get_number_of_rows()
+            || placement.column >= board.get_number_of_columns()
+        {
+            // Placement is out of bounds.
+            return GameResult::IllegalMove;
+        }
+        if board[placement].is_taken() {
+            // There is already a player on this cell.
+            return GameResult::IllegalMove;
+        }
+        board[placement] = Some(player_id).into();
+        let result = self.evaluate_placement(board, placement, player_id);
+        if result == GameResult::IllegalMove {
+            // The placement is forbidden.
+            board[placement] = None.into();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    /// Parses a board with `x` for the stones of `player` and `o` for the others.
+    fn parse_board(rows: [&str; 9], player: PlayerID) -> GameState {
+        let values: Vec<Vec<Option<PlayerID>>> = rows
+            .iter()
+            .map(|row| {
+                row.chars()
+                    .map(|cell| match cell {
+                        'x' => Some(player),
+                        'o' => Some(1 - player),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+        GameState::new_with_values(values).unwrap()
+    }
+
+    const OVERLINE: [&str; 9] = [
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+        ".xxx.xx..",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ];
+    const FIVE: [&str; 9] = [
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+        "..xx.xx..",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ];
+    const DOUBLE_FOUR: [&str; 9] = [
+        ".........",
+        "....x....",
+        "....x....",
+        "....x....",
+        ".xxx.....",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ];
+    const DOUBLE_FOUR_IN_A_LINE: [&str; 9] = [
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+        ".x.x.x.x.",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ];
+    const DOUBLE_THREE: [&str; 9] = [
+        ".........",
+        ".........",
+        "....x....",
+        "....x....",
+        "..xx.....",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ];
+    const FOUR_THREE: [&str; 9] = [
+        ".........",
+        ".........",
+        "....x....",
+        "....x....",
+        ".xxx.....",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ];
+    const OPEN_FOUR: [&str; 9] = [
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+        "..xx.x...",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ];
+
+    #[rstest]
+    #[case(OVERLINE, RuleSet::Freestyle, 0, GameResult::Victory)]
+    #[case(OVERLINE, RuleSet::Standard, 0, GameResult::Undecided)]
+    #[case(OVERLINE, RuleSet::Renju, 0, GameResult::IllegalMove)]
+    #[case(OVERLINE, RuleSet::Renju, 1, GameResult::Victory)]
+    #[case(FIVE, RuleSet::Freestyle, 0, GameResult::Victory)]
+    #[case(FIVE, RuleSet::Standard, 0, GameResult::Victory)]
+    #[case(FIVE, RuleSet::Renju, 0, GameResult::Victory)]
+    #[case(DOUBLE_FOUR, RuleSet::Standard, 0, GameResult::Undecided)]
+    #[case(DOUBLE_FOUR, RuleSet::Renju, 0, GameResult::IllegalMove)]
+    #[case(DOUBLE_FOUR, RuleSet::Renju, 1, GameResult::Undecided)]
+    #[case(DOUBLE_FOUR_IN_A_LINE, RuleSet::Renju, 0, GameResult::IllegalMove)]
+    #[case(DOUBLE_THREE, RuleSet::Standard, 0, GameResult::Undecided)]
+    #[case(DOUBLE_THREE, RuleSet::Renju, 0, GameResult::IllegalMove)]
+    #[case(DOUBLE_THREE, RuleSet::Renju, 1, GameResult::Undecided)]
+    #[case(FOUR_THREE, RuleSet::Renju, 0, GameResult::Undecided)]
+    #[case(OPEN_FOUR, RuleSet::Renju, 0, GameResult::Undecided)]
+    fn referee_judges_board_correctly(
+        #[case] rows: [&str; 9],
+        #[case] rule_set: RuleSet,
+        #[case] player: PlayerID,
+        #[case] expected: GameResult,
+    ) {
+        let mut board = parse_board(rows, player);
+        let referee = GomokuReferee::new(0, rule_set, 5);
+        let placement = PointPlacement { row: 4, column: 4 };
+        let result = referee.receive_move(&mut board, placement, player);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn forbidden_placements_are_not_kept() {
+        let mut board = parse_board(DOUBLE_FOUR, 0);
+        let expected = board.clone();
+        let referee = GomokuReferee::new(0, RuleSet::Renju, 5);
+        let placement = PointPlacement { row: 4, column: 4 };
+        let result = referee.receive_move(&mut board, placement, 0);
+        assert_eq!(result, GameResult::IllegalMove);
+        assert_eq!(board, expected);
+    }
+}
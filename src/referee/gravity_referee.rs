@@ -0,0 +1,195 @@
+use crate::interfaces::{
+    BoardSizeT, GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee,
+};
+
+/// Referee for games in which stones fall down, as in Connect Four.
+///
+/// A placement is only legal on the lowest free cell of its column, i.e. on the free
+/// cell with the largest row index whose cell below is taken. Whether a legal
+/// placement wins is judged by the wrapped referee.
+pub struct GravityReferee {
+    referee: Box<dyn TicTacToeReferee>,
+}
+
+impl GravityReferee {
+    #[must_use]
+    pub fn new(referee: Box<dyn TicTacToeReferee>) -> Self {
+        Self { referee }
+    }
+
+    fn get_lowest_free_row(
+        board: &GameState,
+        column: BoardSizeT,
+    ) -> Option<BoardSizeT> {
+        (0..board.get_number_of_rows())
+            .rev()
+            .find(|&row| board[PointPlacement { row, column }].is_free())
+    }
+
+    /// Returns whether a stone dropped into the column of `placement` would come to
+    /// rest on it. Placements out of bounds are left to the wrapped referee.
+    fn comes_to_rest(board: &GameState, placement: PointPlacement) -> bool {
+        placement.column >= board.get_number_of_columns()
+            || Self::get_lowest_free_row(board, placement.column) == Some(placement.row)
+    }
+}
+
+impl TicTacToeReferee for GravityReferee {
+    fn receive_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        if !Self::comes_to_rest(board, placement) {
+            return GameResult::IllegalMove;
+        }
+        self.referee.receive_move(board, placement, player_id)
+    }
+
+    fn receive_counted_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+        nfree_cells: usize,
+    ) -> GameResult {
+        if !Self::comes_to_rest(board, placement) {
+            return GameResult::IllegalMove;
+        }
+        self.referee
+            .receive_counted_move(board, placement, player_id, nfree_cells)
+    }
+
+    fn get_legal_moves(&self, board: &GameState) -> Vec<PointPlacement> {
+        (0..board.get_number_of_columns())
+            .filter_map(|column| {
+                Self::get_lowest_free_row(board, column)
+                    .map(|row| PointPlacement { row, column })
+            })
+            .collect()
+    }
+
+    fn get_winner(&self, board: &GameState) -> Option<PlayerID> {
+        // Taking back stones below the topmost ones is not a legal move, so the
+        // wrapped referee has to judge the board.
+        self.referee.get_winner(board)
+    }
+
+    fn is_turn_over(&self, board: &GameState) -> bool {
+        self.referee.is_turn_over(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::referee::NaiveReferee;
+    use rstest::*;
+
+    #[rstest]
+    // stone on the bottom row
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None],
+            [None, None, None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 1},
+        GameResult::Undecided
+    )]
+    // stone on top of another one
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None],
+            [None, None, None],
+            [None, Some(1), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 1, column: 1},
+        GameResult::Undecided
+    )]
+    // stone floating above a free cell
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None],
+            [None, None, None],
+            [None, Some(1), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 1},
+        GameResult::IllegalMove
+    )]
+    // cell already taken
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None],
+            [None, None, None],
+            [None, Some(1), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 1},
+        GameResult::IllegalMove
+    )]
+    // placement out of bounds
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None],
+            [None, None, None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 3, column: 0},
+        GameResult::IllegalMove
+    )]
+    // vertical line completed on top
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None],
+            [None, Some(0), None],
+            [Some(1), Some(0), Some(1)],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 1},
+        GameResult::Victory
+    )]
+    fn referee_judges_board_correctly(
+        #[case] mut board: GameState,
+        #[case] placement: PointPlacement,
+        #[case] expected: GameResult,
+    ) {
+        let referee = GravityReferee::new(Box::new(NaiveReferee::new(3)));
+        let result = referee.receive_move(&mut board, placement, 0);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn only_lowest_free_cells_are_legal() {
+        let board = GameState::new_with_values([
+            [None, None, Some(0)],
+            [None, None, Some(1)],
+            [None, Some(0), Some(1)],
+        ])
+        .unwrap();
+        let referee = GravityReferee::new(Box::new(NaiveReferee::new(3)));
+
+        let expected = [
+            PointPlacement { row: 2, column: 0 },
+            PointPlacement { row: 1, column: 1 },
+        ];
+        assert_eq!(referee.get_legal_moves(&board), expected);
+    }
+
+    #[test]
+    fn winner_is_found_below_the_top() {
+        let board = GameState::new_with_values([
+            [None, Some(1), None],
+            [Some(0), Some(0), Some(0)],
+            [Some(1), Some(1), Some(0)],
+        ])
+        .unwrap();
+        let referee = GravityReferee::new(Box::new(NaiveReferee::new(3)));
+        assert_eq!(referee.get_winner(&board), Some(0));
+    }
+}
@@ -0,0 +1,49 @@
+/// Arrangement of the cells of the board.
+///
+/// A hex grid is stored in axial coordinates, so that a board of `n` rows and `m`
+/// columns is a rhombus of hexagons. Each row is shifted by half a cell to the right
+/// compared to the one above. Thus, the neighbours of a cell are the ones to its left
+/// and right, above and below as well as above right and below left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Grid {
+    Hex,
+    #[default]
+    Square,
+}
+
+impl Grid {
+    /// Returns whether cells `row_delta` rows and `column_delta` columns apart lie on
+    /// a common line of the grid.
+    ///
+    /// On a square grid, all horizontal, vertical and diagonal lines count. On a hex
+    /// grid, the diagonal from top left to bottom right does not connect neighbours
+    /// and therefore is not a line.
+    pub(crate) fn has_line(self, row_delta: i32, column_delta: i32) -> bool {
+        match self {
+            Grid::Hex => row_delta.signum() != column_delta.signum() || row_delta == 0,
+            Grid::Square => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(Grid::Square, 1, 1, true)]
+    #[case(Grid::Hex, 0, 1, true)]
+    #[case(Grid::Hex, 1, 0, true)]
+    #[case(Grid::Hex, 1, -1, true)]
+    #[case(Grid::Hex, 1, 1, false)]
+    #[case(Grid::Hex, -1, -1, false)]
+    fn lines_are_told_correctly(
+        #[case] grid: Grid,
+        #[case] row_delta: i32,
+        #[case] column_delta: i32,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(grid.has_line(row_delta, column_delta), expected);
+    }
+}
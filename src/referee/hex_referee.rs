@@ -0,0 +1,142 @@
+use crate::interfaces::{
+    GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee,
+};
+use crate::referee::Topology;
+
+/// Offsets of the six neighbours of a cell on a hex grid, see `Grid::Hex`.
+const NEIGHBOURS: [(i32, i32); 6] =
+    [(0, -1), (0, 1), (-1, 0), (1, 0), (-1, 1), (1, -1)];
+
+/// Referee for Hex on a hex grid.
+///
+/// Instead of completing a line, a player wins by connecting their pair of opposite
+/// edges with a chain of neighbouring stones. The first player connects the top and
+/// the bottom edge, all others the left and the right one. On a rhombus, the game
+/// cannot end in a draw.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct HexReferee {
+    first_player: PlayerID,
+}
+
+impl HexReferee {
+    #[must_use]
+    pub fn new(first_player: PlayerID) -> Self {
+        Self { first_player }
+    }
+
+    /// Returns whether the group of stones containing `placement` connects the edges
+    /// of `player`.
+    fn connects_edges(
+        &self,
+        board: &GameState,
+        placement: PointPlacement,
+        player: PlayerID,
+    ) -> bool {
+        let (size, get_index): (_, fn(PointPlacement) -> u16) =
+            if player == self.first_player {
+                (board.get_number_of_rows(), |pp| pp.row)
+            } else {
+                (board.get_number_of_columns(), |pp| pp.column)
+            };
+        let mut visited = GameState::new_from_existing(board, None);
+        let mut stack = vec![placement];
+        let mut touches_start = false;
+        let mut touches_end = false;
+        visited[placement] = Some(player).into();
+        while let Some(pp) = stack.pop() {
+            touches_start |= get_index(pp) == 0;
+            touches_end |= get_index(pp) == size - 1;
+            if touches_start && touches_end {
+                return true;
+            }
+            for (row_delta, column_delta) in NEIGHBOURS {
+                let Some(neighbour) =
+                    Topology::Bounded.translate(board, pp, row_delta, column_delta)
+                else {
+                    continue;
+                };
+                if board[neighbour] == Some(player).into()
+                    && visited[neighbour].is_free()
+                {
+                    visited[neighbour] = Some(player).into();
+                    stack.push(neighbour);
+                }
+            }
+        }
+        false
+    }
+}
+
+impl TicTacToeReferee for HexReferee {
+    fn receive_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        if placement.row >= board.get_number_of_rows()
+            || placement.column >= board.get_number_of_columns()
+        {
+            // Placement is out of bounds.
+            return GameResult::IllegalMove;
+        }
+        if board[placement].is_taken() {
+            // There is already a player on this cell.
+            return GameResult::IllegalMove;
+        }
+        board[placement] = Some(player_id).into();
+        if self.connects_edges(board, placement, player_id) {
+            return GameResult::Victory;
+        }
+        if board.iter_2d().all(|(_, cell)| cell.is_taken()) {
+            return GameResult::Draw;
+        }
+        GameResult::Undecided
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::parse_mask;
+    use rstest::*;
+
+    #[rstest]
+    // chain along the hex diagonal from top right to bottom left
+    #[case("..0\n.0.\n...", PointPlacement { row: 2, column: 0 }, 0, GameResult::Victory)]
+    // cells on the other diagonal are no neighbours
+    #[case("0..\n.0.\n...", PointPlacement { row: 2, column: 2 }, 0, GameResult::Undecided)]
+    // the second player connects left and right
+    #[case("...\n11.\n...", PointPlacement { row: 0, column: 2 }, 1, GameResult::Victory)]
+    #[case("...\n11.\n...", PointPlacement { row: 2, column: 2 }, 1, GameResult::Undecided)]
+    // connecting the edges of the other player does not win
+    #[case("...\n11.\n...", PointPlacement { row: 1, column: 2 }, 0, GameResult::Undecided)]
+    // blocked cells break chains
+    #[case(".#.\n.0.\n...", PointPlacement { row: 2, column: 1 }, 0, GameResult::Undecided)]
+    // joining two groups
+    #[case("...\n1.1\n...", PointPlacement { row: 1, column: 1 }, 1, GameResult::Victory)]
+    #[case("0.\n#1", PointPlacement { row: 0, column: 1 }, 0, GameResult::Draw)]
+    #[case("0.\n.1", PointPlacement { row: 0, column: 0 }, 1, GameResult::IllegalMove)]
+    fn referee_judges_board_correctly(
+        #[case] mask: &str,
+        #[case] placement: PointPlacement,
+        #[case] player: PlayerID,
+        #[case] expected: GameResult,
+    ) {
+        let referee = HexReferee::new(0);
+        let mut board = parse_mask(mask).unwrap();
+        assert_eq!(
+            referee.receive_move(&mut board, placement, player),
+            expected
+        );
+    }
+
+    #[test]
+    fn winner_is_found() {
+        let referee = HexReferee::new(1);
+        let board = parse_mask(".1.\n.1.\n1..").unwrap();
+        assert_eq!(referee.get_winner(&board), Some(1));
+        let board = parse_mask(".0.\n.0.\n0..").unwrap();
+        assert_eq!(referee.get_winner(&board), None);
+    }
+}
@@ -0,0 +1,491 @@
+use crate::interfaces::{
+    GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee, WinLengthT,
+};
+use crate::referee::{Grid, Topology};
+
+const DELTAS: [Direction; 4] = [
+    Direction {
+        // horizontal
+        row_delta: 0,
+        column_delta: 1,
+    },
+    Direction {
+        // vertical
+        row_delta: 1,
+        column_delta: 0,
+    },
+    Direction {
+        // slash diagonal
+        row_delta: 1,
+        column_delta: 1,
+    },
+    Direction {
+        // backslash diagonal
+        row_delta: 1,
+        column_delta: -1,
+    },
+];
+
+/// Referee that only inspects what the last placement can have changed.
+///
+/// A new stone can only complete lines running through it, so only the four lines
+/// through the placement are checked. Draws are detected in constant time if the
+/// caller tells the number of free cells via `receive_counted_move`. Otherwise, the
+/// board is searched for a free cell, which stops at the first one found.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct IncrementalReferee {
+    grid: Grid,
+    topology: Topology,
+    winning_length: WinLengthT,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct Direction {
+    row_delta: i32,
+    column_delta: i32,
+}
+
+impl Direction {
+    #[inline]
+    fn add(
+        &self,
+        other: PointPlacement,
+        factor: i32,
+        board: &GameState,
+        topology: Topology,
+    ) -> Option<PointPlacement> {
+        topology.translate(
+            board,
+            other,
+            factor * self.row_delta,
+            factor * self.column_delta,
+        )
+    }
+}
+
+impl IncrementalReferee {
+    #[must_use]
+    pub fn new(winning_length: WinLengthT) -> Self {
+        Self::new_with_topology(Topology::Bounded, winning_length)
+    }
+
+    #[must_use]
+    pub fn new_with_topology(topology: Topology, winning_length: WinLengthT) -> Self {
+        Self::new_with_grid(Grid::Square, topology, winning_length)
+    }
+
+    #[must_use]
+    pub fn new_with_grid(
+        grid: Grid,
+        topology: Topology,
+        winning_length: WinLengthT,
+    ) -> Self {
+        Self {
+            grid,
+            topology,
+            winning_length,
+        }
+    }
+
+    /// Puts the stone of `player` on `placement` and judges it, telling a draw by
+    /// `is_full`.
+    fn place_stone(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player: PlayerID,
+        is_full: impl FnOnce(&GameState) -> bool,
+    ) -> GameResult {
+        if placement.row >= board.get_number_of_rows()
+            || placement.column >= board.get_number_of_columns()
+        {
+            // Placement is out of bounds.
+            return GameResult::IllegalMove;
+        }
+        if board[placement].is_taken() {
+            // There is already a player on this cell.
+            return GameResult::IllegalMove;
+        }
+        board[placement] = Some(player).into();
+        if self.completes_line(board, placement, player) {
+            GameResult::Victory
+        } else if is_full(board) {
+            GameResult::Draw
+        } else {
+            GameResult::Undecided
+        }
+    }
+
+    fn completes_line(
+        &self,
+        board: &GameState,
+        placement: PointPlacement,
+        player: PlayerID,
+    ) -> bool {
+        for cur in DELTAS
+            .iter()
+            .filter(|cur| self.grid.has_line(cur.row_delta, cur.column_delta))
+        {
+            // On a torus, both counts must stop before reaching a stone twice.
+            let maximum_length = self.topology.get_maximum_line_length(
+                board,
+                cur.row_delta,
+                cur.column_delta,
+            );
+            let forward = self.count_stones_in_direction(
+                cur,
+                1,
+                placement,
+                board,
+                player,
+                maximum_length - 1,
+            );
+            let backward = self.count_stones_in_direction(
+                cur,
+                -1,
+                placement,
+                board,
+                player,
+                maximum_length - 1 - forward,
+            );
+            let length = 1 + forward + backward;
+            if length >= usize::from(self.winning_length) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Counts the stones of `player` adjacent to `start_pp` in the given direction.
+    ///
+    /// Counting stops after `limit` stones.
+    fn count_stones_in_direction(
+        &self,
+        direction: &Direction,
+        sign: i32,
+        start_pp: PointPlacement,
+        board: &GameState,
+        player: PlayerID,
+        limit: usize,
+    ) -> usize {
+        let mut count = 0;
+        let mut cur_pp = start_pp;
+        while count < limit {
+            let Some(new_pp) = direction.add(cur_pp, sign, board, self.topology) else {
+                return count;
+            };
+            if board[new_pp] != Some(player).into() {
+                return count;
+            }
+            count += 1;
+            cur_pp = new_pp;
+        }
+        count
+    }
+}
+
+impl TicTacToeReferee for IncrementalReferee {
+    fn receive_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        self.place_stone(board, placement, player_id, |board| {
+            board.iter_2d().all(|(_, cell)| cell.is_taken())
+        })
+    }
+
+    fn receive_counted_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+        nfree_cells: usize,
+    ) -> GameResult {
+        self.place_stone(board, placement, player_id, |_| nfree_cells <= 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    // horizontal
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(0), Some(0), None],
+            [None, Some(1), Some(1), None],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        3,
+        0,
+        GameResult::Victory
+    )]
+    // vertical
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(0), Some(1)],
+            [None, Some(0), Some(1)],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 2},
+        3,
+        1,
+        GameResult::Victory
+    )]
+    // slash diagonal
+    #[case(GameState::new_with_values(
+        [
+            [None, None, Some(1), Some(0)],
+            [None, None, Some(0), Some(1)],
+            [None, Some(0), Some(1), None],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 3, column: 0},
+        4,
+        0,
+        GameResult::Victory
+    )]
+    // backslash diagonal
+    #[case(GameState::new_with_values(
+        [
+            [None,    None,    None,    None,    None,    None],
+            [None,    None,    None,    None,    None,    None],
+            [Some(0), None,    None,    None,    None,    None],
+            [Some(1), Some(0), None,    None,    None,    None],
+            [None,    Some(1), Some(0), None,    None,    None],
+            [None,    None,    Some(1), Some(0), None,    None],
+            [None,    None,    None,    Some(1), Some(0), None],
+            [None,    None,    None,    None,    Some(1), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 7, column: 5},
+        6,
+        0,
+        GameResult::Victory,
+    )]
+    // line completed in its middle
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None, Some(1)],
+            [None, Some(0), None, None],
+            [Some(0), Some(1), None, None],
+            [Some(1), None, Some(0), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 1, column: 2},
+        4,
+        1,
+        GameResult::Victory,
+    )]
+    // Illegal move - cell already taken
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), None],
+            [None, None]
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        2,
+        1,
+        GameResult::IllegalMove,
+    )]
+    // Illegal move - placement out of bounds
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(1)],
+            [None, None]
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 0},
+        2,
+        0,
+        GameResult::IllegalMove,
+    )]
+    // undecided
+    #[case(GameState::new_with_values(
+        [
+            [None, None, Some(1), Some(0)],
+            [None, None, Some(0), Some(1)],
+            [None, Some(0), Some(1), None],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 0},
+        4,
+        1,
+        GameResult::Undecided,
+    )]
+    // draw
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(1), Some(0)],
+            [Some(0), Some(1), Some(1)],
+            [Some(1), Some(0), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 2},
+        3,
+        0,
+        GameResult::Draw,
+    )]
+    fn referee_judges_board_correctly(
+        #[case] mut board: GameState,
+        #[case] next_move: PointPlacement,
+        #[case] winning_length: WinLengthT,
+        #[case] player: PlayerID,
+        #[case] expected: GameResult,
+    ) {
+        let referee = IncrementalReferee::new(winning_length);
+        let result = referee.receive_move(&mut board, next_move, player);
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    // horizontal, wrapping around the right edge
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), None, None, Some(0)],
+            [Some(1), Some(1), None, None],
+            [None, None, None, None],
+            [None, None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 2},
+        3,
+        0,
+        GameResult::Victory
+    )]
+    // vertical, wrapping around the bottom edge
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(1), None, None],
+            [Some(0), None, None, None],
+            [Some(0), None, None, None],
+            [None, Some(1), None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 1},
+        3,
+        1,
+        GameResult::Victory
+    )]
+    // slash diagonal, wrapping around a corner
+    #[case(GameState::new_with_values(
+        [
+            [None, Some(1), None, None],
+            [None, Some(0), None, None],
+            [None, None, None, Some(1)],
+            [None, None, None, Some(0)],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        3,
+        0,
+        GameResult::Victory
+    )]
+    // backslash diagonal, wrapping around the left edge
+    #[case(GameState::new_with_values(
+        [
+            [None, None, None, None],
+            [None, None, None, Some(1)],
+            [None, None, Some(1), None],
+            [Some(0), Some(0), None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        3,
+        1,
+        GameResult::Victory
+    )]
+    // a line around the whole torus does not count stones twice
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 2},
+        4,
+        0,
+        GameResult::Undecided
+    )]
+    // Illegal move - cell already taken
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), None],
+            [None, None]
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 0},
+        2,
+        1,
+        GameResult::IllegalMove,
+    )]
+    // Illegal move - placement out of bounds
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(1)],
+            [None, None]
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 0},
+        2,
+        0,
+        GameResult::IllegalMove,
+    )]
+    // draw
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), Some(1), Some(1)],
+            [Some(1), Some(1), Some(0), Some(0)],
+            [Some(0), Some(0), Some(1), Some(1)],
+            [Some(1), Some(1), Some(0), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 3, column: 3},
+        3,
+        0,
+        GameResult::Draw,
+    )]
+    fn torus_referee_judges_board_correctly(
+        #[case] mut board: GameState,
+        #[case] next_move: PointPlacement,
+        #[case] winning_length: WinLengthT,
+        #[case] player: PlayerID,
+        #[case] expected: GameResult,
+    ) {
+        let referee =
+            IncrementalReferee::new_with_topology(Topology::Torus, winning_length);
+        let result = referee.receive_move(&mut board, next_move, player);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn counted_moves_detect_draws() {
+        let referee = IncrementalReferee::new(3);
+        let mut board = GameState::new_with_values([
+            [Some(0), Some(1), Some(0)],
+            [Some(0), Some(1), Some(1)],
+            [Some(1), None, None],
+        ])
+        .unwrap();
+        let first = PointPlacement { row: 2, column: 1 };
+        let second = PointPlacement { row: 2, column: 2 };
+
+        let result = referee.receive_counted_move(&mut board, first, 0, 2);
+        assert_eq!(result, GameResult::Undecided);
+        let result = referee.receive_counted_move(&mut board, first, 1, 1);
+        assert_eq!(result, GameResult::IllegalMove);
+        let result = referee.receive_counted_move(&mut board, second, 1, 1);
+        assert_eq!(result, GameResult::Draw);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::interfaces::{
+    GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee,
+};
+
+/// Referee for the misère variant, in which completing a line loses the game.
+///
+/// Every placement is judged by the wrapped referee, except that victories are turned
+/// into defeats of the player completing the line. Consequently, no placement is
+/// ever judged a victory and `get_winner` never reports a player.
+pub struct MisereReferee {
+    referee: Box<dyn TicTacToeReferee>,
+}
+
+impl MisereReferee {
+    #[must_use]
+    pub fn new(referee: Box<dyn TicTacToeReferee>) -> Self {
+        Self { referee }
+    }
+}
+
+impl TicTacToeReferee for MisereReferee {
+    fn receive_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        match self.referee.receive_move(board, placement, player_id) {
+            GameResult::Victory => GameResult::Defeat,
+            result => result,
+        }
+    }
+
+    fn receive_counted_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+        nfree_cells: usize,
+    ) -> GameResult {
+        match self.referee.receive_counted_move(
+            board,
+            placement,
+            player_id,
+            nfree_cells,
+        ) {
+            GameResult::Victory => GameResult::Defeat,
+            result => result,
+        }
+    }
+
+    fn get_legal_moves(&self, board: &GameState) -> Vec<PointPlacement> {
+        self.referee.get_legal_moves(board)
+    }
+
+    fn is_turn_over(&self, board: &GameState) -> bool {
+        self.referee.is_turn_over(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::referee::NaiveReferee;
+    use rstest::*;
+
+    #[rstest]
+    // completing a line
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 0, column: 2},
+        GameResult::Defeat
+    )]
+    // blocking a line
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 1, column: 2},
+        GameResult::Undecided
+    )]
+    // cell already taken
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(0), None],
+            [Some(1), Some(1), None],
+            [None, None, None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 1, column: 1},
+        GameResult::IllegalMove
+    )]
+    // filling the last cell
+    #[case(GameState::new_with_values(
+        [
+            [Some(0), Some(1), Some(0)],
+            [Some(0), Some(1), Some(1)],
+            [Some(1), Some(0), None],
+        ]
+    ).unwrap(),
+        PointPlacement{row: 2, column: 2},
+        GameResult::Draw
+    )]
+    fn referee_judges_board_correctly(
+        #[case] mut board: GameState,
+        #[case] placement: PointPlacement,
+        #[case] expected: GameResult,
+    ) {
+        let referee = MisereReferee::new(Box::new(NaiveReferee::new(3)));
+        let result = referee.receive_move(&mut board, placement, 0);
+        assert_eq!(result, expected);
+    }
+}
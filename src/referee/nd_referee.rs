@@ -0,0 +1,278 @@
+use crate::interfaces::{
+    BoardSizeT, GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee,
+    WinLengthT,
+};
+
+/// Returns the number of rows and columns of the board embedding `dimensions`
+/// dimensions of `size` cells each.
+///
+/// Coordinates of even dimensions (counting from zero) make up the column, those of
+/// odd dimensions the row, with lower dimensions varying fastest. Thus, a 3-D board
+/// shows its layers side by side.
+///
+/// Returns `None` if the board does not fit into `BoardSizeT`.
+#[must_use]
+pub fn get_embedding_size(
+    dimensions: u32,
+    size: BoardSizeT,
+) -> Option<(BoardSizeT, BoardSizeT)> {
+    let nrows = size.checked_pow(dimensions / 2)?;
+    let ncolumns = size.checked_pow(dimensions.div_ceil(2))?;
+    Some((nrows, ncolumns))
+}
+
+/// Returns the number of cells per dimension of `board`, which embeds `dimensions`
+/// dimensions as described at `get_embedding_size`.
+#[must_use]
+pub fn get_cells_per_dimension(dimensions: u32, board: &GameState) -> BoardSizeT {
+    let ncolumns = board.get_number_of_columns();
+    let exponent = dimensions.div_ceil(2);
+    (1..=ncolumns)
+        .find(|size| size.checked_pow(exponent).is_none_or(|n| n >= ncolumns))
+        .unwrap_or(1)
+}
+
+/// Referee for k-in-a-row on boards of `n^d` cells.
+///
+/// The board is embedded into a two-dimensional one as described at
+/// `get_embedding_size`, so that boards, players and arenas need not know about the
+/// number of dimensions. Lines run in all `(3^d - 1) / 2` directions, e.g. 13 in three
+/// dimensions.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct NdReferee {
+    dimensions: u32,
+    /// One direction per line, i.e. all vectors of -1, 0 and 1 whose first non-zero
+    /// entry is positive.
+    directions: Vec<Vec<i32>>,
+    winning_length: WinLengthT,
+}
+
+impl NdReferee {
+    #[must_use]
+    pub fn new(dimensions: u32, winning_length: WinLengthT) -> Self {
+        let mut directions: Vec<Vec<i32>> = vec![vec![]];
+        for _ in 0..dimensions {
+            directions = directions
+                .into_iter()
+                .flat_map(|direction| {
+                    [-1, 0, 1].map(|delta| {
+                        let mut longer = direction.clone();
+                        longer.push(delta);
+                        longer
+                    })
+                })
+                .collect();
+        }
+        directions.retain(|direction| {
+            direction
+                .iter()
+                .find(|&&delta| delta != 0)
+                .is_some_and(|&delta| delta > 0)
+        });
+        Self {
+            dimensions,
+            directions,
+            winning_length,
+        }
+    }
+
+    fn to_coordinates(&self, pp: PointPlacement, size: BoardSizeT) -> Vec<i32> {
+        let mut row = i32::from(pp.row);
+        let mut column = i32::from(pp.column);
+        let size = i32::from(size);
+        (0..self.dimensions)
+            .map(|dimension| {
+                let index = if dimension % 2 == 0 {
+                    &mut column
+                } else {
+                    &mut row
+                };
+                let coordinate = *index % size;
+                *index /= size;
+                coordinate
+            })
+            .collect()
+    }
+
+    fn to_placement(&self, coordinates: &[i32], size: BoardSizeT) -> PointPlacement {
+        let mut row = 0;
+        let mut column = 0;
+        for dimension in (0..self.dimensions).rev() {
+            let index = if dimension % 2 == 0 {
+                &mut column
+            } else {
+                &mut row
+            };
+            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+            {
+                // Callers make sure that coordinates lie on the board, so they are
+                // non-negative and the resulting indices fit into BoardSizeT.
+                *index = *index * size + coordinates[dimension as usize] as BoardSizeT;
+            }
+        }
+        PointPlacement { row, column }
+    }
+
+    /// Counts the stones of `player` following the cell at `cur` in `direction`, which
+    /// is reversed if `sign` is negative, on a board of `size` cells per dimension.
+    ///
+    /// `cur` is moved along the line, which spares an allocation per call.
+    fn count_stones_in_direction(
+        &self,
+        board: &GameState,
+        size: BoardSizeT,
+        cur: &mut [i32],
+        direction: &[i32],
+        sign: i32,
+        player: PlayerID,
+    ) -> usize {
+        let mut count = 0;
+        loop {
+            for (coordinate, delta) in cur.iter_mut().zip(direction) {
+                *coordinate += sign * delta;
+            }
+            if cur
+                .iter()
+                .any(|&coordinate| coordinate < 0 || coordinate >= i32::from(size))
+            {
+                return count;
+            }
+            if board[self.to_placement(cur, size)] != Some(player).into() {
+                return count;
+            }
+            count += 1;
+        }
+    }
+
+    fn evaluate_placement(
+        &self,
+        board: &GameState,
+        size: BoardSizeT,
+        placement: PointPlacement,
+        player: PlayerID,
+    ) -> GameResult {
+        let coordinates = self.to_coordinates(placement, size);
+        let mut cur = coordinates.clone();
+        for direction in &self.directions {
+            let [forward, backward] = [1, -1].map(|sign| {
+                cur.copy_from_slice(&coordinates);
+                self.count_stones_in_direction(
+                    board, size, &mut cur, direction, sign, player,
+                )
+            });
+            if 1 + forward + backward >= usize::from(self.winning_length) {
+                return GameResult::Victory;
+            }
+        }
+        if board.iter_2d().all(|(_, cell)| cell.is_taken()) {
+            return GameResult::Draw;
+        }
+        GameResult::Undecided
+    }
+}
+
+impl TicTacToeReferee for NdReferee {
+    fn receive_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        if placement.row >= board.get_number_of_rows()
+            || placement.column >= board.get_number_of_columns()
+        {
+            // Placement is out of bounds.
+            return GameResult::IllegalMove;
+        }
+        let size = get_cells_per_dimension(self.dimensions, board);
+        if get_embedding_size(self.dimensions, size)
+            != Some((board.get_number_of_rows(), board.get_number_of_columns()))
+        {
+            // Board does not embed a cube of this many dimensions.
+            return GameResult::IllegalMove;
+        }
+        if board[placement].is_taken() {
+            // There is already a player on this cell.
+            return GameResult::IllegalMove;
+        }
+        board[placement] = Some(player_id).into();
+        self.evaluate_placement(board, size, placement, player_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::parse_mask;
+    use rstest::*;
+
+    #[rstest]
+    #[case(1, 1)]
+    #[case(2, 4)]
+    #[case(3, 13)]
+    #[case(4, 40)]
+    fn all_directions_are_found(#[case] dimensions: u32, #[case] expected: usize) {
+        assert_eq!(NdReferee::new(dimensions, 3).directions.len(), expected);
+    }
+
+    #[rstest]
+    #[case(1, 4, Some((1, 4)))]
+    #[case(3, 4, Some((4, 16)))]
+    #[case(4, 3, Some((9, 9)))]
+    #[case(5, 100, None)]
+    fn boards_are_embedded_correctly(
+        #[case] dimensions: u32,
+        #[case] size: BoardSizeT,
+        #[case] expected: Option<(BoardSizeT, BoardSizeT)>,
+    ) {
+        assert_eq!(get_embedding_size(dimensions, size), expected);
+    }
+
+    #[rstest]
+    // line within a layer
+    #[case("00.......", PointPlacement { row: 0, column: 2 }, GameResult::Victory)]
+    // line through the layers
+    #[case("0..0.....", PointPlacement { row: 0, column: 6 }, GameResult::Victory)]
+    // lines do not continue from one layer to the next
+    #[case("..00.....", PointPlacement { row: 0, column: 4 }, GameResult::Undecided)]
+    // space diagonal
+    #[case("0........", PointPlacement { row: 1, column: 4 }, GameResult::Undecided)]
+    fn referee_judges_3d_board_correctly(
+        #[case] first_row: &str,
+        #[case] placement: PointPlacement,
+        #[case] expected: GameResult,
+    ) {
+        // The three layers of the 3×3×3 board are shown side by side.
+        let referee = NdReferee::new(3, 3);
+        let mut board =
+            parse_mask(&format!("{first_row}\n.........\n.........")).unwrap();
+        assert_eq!(referee.receive_move(&mut board, placement, 0), expected);
+    }
+
+    #[test]
+    fn space_diagonals_win() {
+        let referee = NdReferee::new(3, 3);
+        let mut board = parse_mask("0........\n.........\n.........").unwrap();
+        let centre = PointPlacement { row: 1, column: 4 };
+        assert_eq!(
+            referee.receive_move(&mut board, centre, 0),
+            GameResult::Undecided
+        );
+        let corner = PointPlacement { row: 2, column: 8 };
+        assert_eq!(
+            referee.receive_move(&mut board, corner, 0),
+            GameResult::Victory
+        );
+    }
+
+    #[test]
+    fn boards_not_embedding_a_cube_are_rejected() {
+        let referee = NdReferee::new(3, 2);
+        let mut board = parse_mask("..\n..").unwrap();
+        let placement = PointPlacement { row: 1, column: 1 };
+        assert_eq!(
+            referee.receive_move(&mut board, placement, 0),
+            GameResult::IllegalMove
+        );
+    }
+}
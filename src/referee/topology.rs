@@ -0,0 +1,133 @@
+use crate::interfaces::{BoardSizeT, GameState, PointPlacement};
+
+/// Shape of the surface the board is drawn on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// Lines end at the edges of the board.
+    #[default]
+    Bounded,
+    /// Lines leaving the board on one edge continue on the opposite edge.
+    Torus,
+}
+
+impl Topology {
+    /// Returns the cell `row_delta` rows and `column_delta` columns away from `pp`.
+    ///
+    /// Returns `None` if there is no such cell, i.e. if the board is bounded and the
+    /// cell would be outside of it.
+    pub(crate) fn translate(
+        self,
+        board: &GameState,
+        pp: PointPlacement,
+        row_delta: i32,
+        column_delta: i32,
+    ) -> Option<PointPlacement> {
+        let max_row = i32::from(board.get_number_of_rows());
+        let max_column = i32::from(board.get_number_of_columns());
+        let mut row = i32::from(pp.row) + row_delta;
+        let mut column = i32::from(pp.column) + column_delta;
+        match self {
+            Topology::Bounded => {
+                if row < 0 || column < 0 || row >= max_row || column >= max_column {
+                    return None;
+                }
+            }
+            Topology::Torus => {
+                row = row.rem_euclid(max_row);
+                column = column.rem_euclid(max_column);
+            }
+        }
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        Some(PointPlacement {
+            // We know that row and column are positive. We also know that they are
+            // less than max_row and max_column. Thus, we can safely cast them to
+            // BoardSizeT.
+            row: row as BoardSizeT,
+            column: column as BoardSizeT,
+        })
+    }
+
+    /// Returns the maximum number of distinct cells on a line in the given direction.
+    ///
+    /// On a torus, a line returns to its first cell eventually. A line must not count
+    /// a stone twice, so it cannot be longer than this.
+    pub(crate) fn get_maximum_line_length(
+        self,
+        board: &GameState,
+        row_delta: i32,
+        column_delta: i32,
+    ) -> usize {
+        match self {
+            Topology::Bounded => usize::MAX,
+            Topology::Torus => {
+                let row_period = if row_delta == 0 {
+                    1
+                } else {
+                    usize::from(board.get_number_of_rows())
+                };
+                let column_period = if column_delta == 0 {
+                    1
+                } else {
+                    usize::from(board.get_number_of_columns())
+                };
+                row_period / gcd(row_period, column_period) * column_period
+            }
+        }
+    }
+}
+
+fn gcd(mut lhs: usize, mut rhs: usize) -> usize {
+    while rhs != 0 {
+        (lhs, rhs) = (rhs, lhs % rhs);
+    }
+    lhs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(Topology::Bounded, (1, 2), 1, 1, Some((2, 3)))]
+    #[case(Topology::Bounded, (2, 3), 1, 1, None)]
+    #[case(Topology::Bounded, (0, 0), 0, -1, None)]
+    #[case(Topology::Torus, (1, 2), 1, 1, Some((2, 3)))]
+    #[case(Topology::Torus, (2, 3), 1, 1, Some((0, 0)))]
+    #[case(Topology::Torus, (0, 0), 1, -1, Some((1, 3)))]
+    fn cells_are_translated(
+        #[case] topology: Topology,
+        #[case] start: (BoardSizeT, BoardSizeT),
+        #[case] row_delta: i32,
+        #[case] column_delta: i32,
+        #[case] expected: Option<(BoardSizeT, BoardSizeT)>,
+    ) {
+        let board = GameState::new(3, 4, None);
+        let pp = PointPlacement {
+            row: start.0,
+            column: start.1,
+        };
+        let expected = expected.map(|(row, column)| PointPlacement { row, column });
+        assert_eq!(
+            topology.translate(&board, pp, row_delta, column_delta),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(0, 1, 4)]
+    #[case(1, 0, 6)]
+    #[case(1, 1, 12)]
+    #[case(1, -1, 12)]
+    fn torus_lines_have_maximum_length(
+        #[case] row_delta: i32,
+        #[case] column_delta: i32,
+        #[case] expected: usize,
+    ) {
+        let board = GameState::new(6, 4, None);
+        assert_eq!(
+            Topology::Torus.get_maximum_line_length(&board, row_delta, column_delta),
+            expected
+        );
+    }
+}
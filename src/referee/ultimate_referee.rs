@@ -0,0 +1,365 @@
+use crate::interfaces::{
+    BoardSizeT, BoardStateEntry, GameResult, GameState, PlayerID, PointPlacement,
+    TicTacToeReferee,
+};
+
+/// Number of rows and columns of each small board as well as of the meta-board.
+pub const SMALL_BOARD_SIZE: BoardSizeT = 3;
+/// Number of rows and columns of the whole board of ultimate tic-tac-toe.
+pub const BOARD_SIZE: BoardSizeT = SMALL_BOARD_SIZE * SMALL_BOARD_SIZE;
+/// Upper bound of the player IDs, which makes room for the move numbers in a cell.
+const MAX_PLAYERS: u16 = 4;
+
+const LINES: [[(BoardSizeT, BoardSizeT); 3]; 8] = [
+    [(0, 0), (0, 1), (0, 2)],
+    [(1, 0), (1, 1), (1, 2)],
+    [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 0), (2, 0)],
+    [(0, 1), (1, 1), (2, 1)],
+    [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)],
+    [(0, 2), (1, 1), (2, 0)],
+];
+
+/// Position of ultimate tic-tac-toe, as stored in a `GameState` of 9×9 cells.
+///
+/// The board consists of 3×3 small boards of 3×3 cells each. The cell a player places
+/// a stone on within its small board determines the small board the next stone must
+/// be placed on. Therefore, every stone records its move number besides its player.
+/// The position, including the last move, can thus be told from the board alone, and
+/// taking back a move only needs to free its cell.
+pub struct UltimateState<'board> {
+    board: &'board GameState,
+}
+
+impl<'board> UltimateState<'board> {
+    #[must_use]
+    pub fn new(board: &'board GameState) -> Self {
+        Self { board }
+    }
+
+    /// Returns the entry of a stone placed by `player` as the stone with the given
+    /// number, counting from 0.
+    #[must_use]
+    pub fn encode_stone(player: PlayerID, nstone: u16) -> BoardStateEntry {
+        Some(nstone * MAX_PLAYERS + player).into()
+    }
+
+    /// Returns the player owning the cell `pp`, if any.
+    #[must_use]
+    pub fn get_owner(&self, pp: PointPlacement) -> Option<PlayerID> {
+        self.decode(pp).map(|(player, _)| player)
+    }
+
+    /// Returns the board with plain stones, as understood by other referees.
+    #[must_use]
+    pub fn to_plain_board(&self) -> GameState {
+        let mut plain_board = self.board.clone();
+        for (pp, cell) in self.board.iter_2d() {
+            if !cell.is_blocked() {
+                plain_board[pp] = self.get_owner(pp).into();
+            }
+        }
+        plain_board
+    }
+
+    /// Returns the number of stones on the board.
+    #[must_use]
+    pub fn get_number_of_stones(&self) -> usize {
+        self.board
+            .iter_2d()
+            .filter(|&(pp, _)| self.decode(pp).is_some())
+            .count()
+    }
+
+    /// Returns the cell of the last stone placed, if any.
+    #[must_use]
+    pub fn get_last_move(&self) -> Option<PointPlacement> {
+        self.board
+            .iter_2d()
+            .filter_map(|(pp, _)| self.decode(pp).map(|(_, nstone)| (pp, nstone)))
+            .max_by_key(|&(_, nstone)| nstone)
+            .map(|(pp, _)| pp)
+    }
+
+    /// Returns the player who has won the small board with the given index, if any.
+    ///
+    /// Small boards are indexed by the row and column of their top left cell divided
+    /// by `SMALL_BOARD_SIZE`.
+    #[must_use]
+    pub fn get_small_board_winner(
+        &self,
+        small_board: PointPlacement,
+    ) -> Option<PlayerID> {
+        Self::get_line_winner(|(row, column)| {
+            self.get_owner(PointPlacement {
+                row: small_board.row * SMALL_BOARD_SIZE + row,
+                column: small_board.column * SMALL_BOARD_SIZE + column,
+            })
+        })
+    }
+
+    /// Returns the player who has won three small boards in a row, if any.
+    #[must_use]
+    pub fn get_winner(&self) -> Option<PlayerID> {
+        Self::get_line_winner(|(row, column)| {
+            self.get_small_board_winner(PointPlacement { row, column })
+        })
+    }
+
+    /// Returns whether no more stones can be placed on the given small board, since it
+    /// has been won or is full.
+    #[must_use]
+    pub fn is_closed(&self, small_board: PointPlacement) -> bool {
+        self.get_small_board_winner(small_board).is_some()
+            || Self::get_cells(small_board).all(|pp| self.board[pp].is_taken())
+    }
+
+    /// Returns the small board the next stone must be placed on, or `None` if it may
+    /// be placed on any small board that is not closed.
+    #[must_use]
+    pub fn get_forced_small_board(&self) -> Option<PointPlacement> {
+        let last_move = self.get_last_move()?;
+        let small_board = PointPlacement {
+            row: last_move.row % SMALL_BOARD_SIZE,
+            column: last_move.column % SMALL_BOARD_SIZE,
+        };
+        (!self.is_closed(small_board)).then_some(small_board)
+    }
+
+    /// Returns all cells the next stone may be placed on.
+    #[must_use]
+    pub fn get_legal_moves(&self) -> Vec<PointPlacement> {
+        if self.get_winner().is_some() {
+            return Vec::new();
+        }
+        let forced_small_board = self.get_forced_small_board();
+        let is_allowed = |small_board: PointPlacement| match forced_small_board {
+            Some(forced) => small_board == forced,
+            None => !self.is_closed(small_board),
+        };
+        self.board
+            .iter_2d()
+            .filter(|(pp, cell)| {
+                cell.is_free() && is_allowed(Self::get_small_board(*pp))
+            })
+            .map(|(pp, _)| pp)
+            .collect()
+    }
+
+    fn decode(&self, pp: PointPlacement) -> Option<(PlayerID, u16)> {
+        let cell = self.board[pp];
+        if !cell.has_stone() {
+            return None;
+        }
+        Option::<PlayerID>::from(cell).map(|id| (id % MAX_PLAYERS, id / MAX_PLAYERS))
+    }
+
+    fn get_small_board(pp: PointPlacement) -> PointPlacement {
+        PointPlacement {
+            row: pp.row / SMALL_BOARD_SIZE,
+            column: pp.column / SMALL_BOARD_SIZE,
+        }
+    }
+
+    fn get_cells(small_board: PointPlacement) -> impl Iterator<Item = PointPlacement> {
+        (0..SMALL_BOARD_SIZE).flat_map(move |row| {
+            (0..SMALL_BOARD_SIZE).map(move |column| PointPlacement {
+                row: small_board.row * SMALL_BOARD_SIZE + row,
+                column: small_board.column * SMALL_BOARD_SIZE + column,
+            })
+        })
+    }
+
+    /// Returns the player owning all three cells of a line of a 3×3 grid, if any.
+    fn get_line_winner(
+        get_owner: impl Fn((BoardSizeT, BoardSizeT)) -> Option<PlayerID>,
+    ) -> Option<PlayerID> {
+        LINES.iter().find_map(|line| {
+            let owner = get_owner(line[0])?;
+            line[1..]
+                .iter()
+                .all(|&cell| get_owner(cell) == Some(owner))
+                .then_some(owner)
+        })
+    }
+}
+
+/// Referee for ultimate tic-tac-toe on a board of 9×9 cells, see `UltimateState`.
+///
+/// Winning a small board requires three stones in a row within it, winning the game
+/// three small boards in a row. Player IDs must be smaller than 4.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct UltimateReferee {}
+
+impl UltimateReferee {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl TicTacToeReferee for UltimateReferee {
+    fn receive_move(
+        &self,
+        board: &mut GameState,
+        placement: PointPlacement,
+        player_id: PlayerID,
+    ) -> GameResult {
+        let state = UltimateState::new(board);
+        if !state.get_legal_moves().contains(&placement) {
+            return GameResult::IllegalMove;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        // There are at most 81 stones on the board.
+        let nstone = state.get_number_of_stones() as u16;
+        board[placement] = UltimateState::encode_stone(player_id, nstone);
+
+        let state = UltimateState::new(board);
+        if state.get_winner().is_some() {
+            return GameResult::Victory;
+        }
+        if state.get_legal_moves().is_empty() {
+            return GameResult::Draw;
+        }
+        GameResult::Undecided
+    }
+
+    fn get_legal_moves(&self, board: &GameState) -> Vec<PointPlacement> {
+        UltimateState::new(board).get_legal_moves()
+    }
+
+    fn get_winner(&self, board: &GameState) -> Option<PlayerID> {
+        UltimateState::new(board).get_winner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays the given moves, alternating between players 0 and 1.
+    fn play(moves: &[(BoardSizeT, BoardSizeT)]) -> (GameState, GameResult) {
+        let referee = UltimateReferee::new();
+        let mut board = GameState::new(BOARD_SIZE, BOARD_SIZE, None);
+        let mut result = GameResult::Undecided;
+        for (idx, &(row, column)) in moves.iter().enumerate() {
+            let player = PlayerID::from(idx % 2 == 1);
+            result = referee.receive_move(
+                &mut board,
+                PointPlacement { row, column },
+                player,
+            );
+        }
+        (board, result)
+    }
+
+    #[test]
+    fn first_stone_may_be_placed_anywhere() {
+        let board = GameState::new(BOARD_SIZE, BOARD_SIZE, None);
+        assert_eq!(UltimateReferee::new().get_legal_moves(&board).len(), 81);
+    }
+
+    #[test]
+    fn cell_decides_on_the_next_small_board() {
+        // The bottom right cell of the centre board sends to the bottom right board.
+        let (board, result) = play(&[(5, 5)]);
+        assert_eq!(result, GameResult::Undecided);
+        let legal_moves = UltimateReferee::new().get_legal_moves(&board);
+        assert_eq!(legal_moves.len(), 9);
+        assert!(legal_moves.iter().all(|pp| pp.row >= 6 && pp.column >= 6));
+        let (_, result) = play(&[(5, 5), (0, 0)]);
+        assert_eq!(result, GameResult::IllegalMove);
+    }
+
+    /// Places the given stones without checking the rules, alternating between players
+    /// 0 and 1.
+    fn place(stones: &[(BoardSizeT, BoardSizeT)]) -> GameState {
+        let mut board = GameState::new(BOARD_SIZE, BOARD_SIZE, None);
+        for (nstone, &(row, column)) in (0..).zip(stones) {
+            board[PointPlacement { row, column }] =
+                UltimateState::encode_stone(nstone % 2, nstone);
+        }
+        board
+    }
+
+    #[test]
+    fn closed_small_boards_free_the_choice() {
+        // Player 0 has won the top left board, which player 1 sends them to.
+        let board = place(&[(0, 0), (3, 3), (0, 1), (4, 4), (0, 2), (3, 0)]);
+        let state = UltimateState::new(&board);
+        let top_left = PointPlacement { row: 0, column: 0 };
+        assert_eq!(state.get_small_board_winner(top_left), Some(0));
+        assert!(state.is_closed(top_left));
+        assert_eq!(state.get_forced_small_board(), None);
+        // All cells but those of the top left board and the three other stones
+        assert_eq!(UltimateReferee::new().get_legal_moves(&board).len(), 69);
+    }
+
+    #[test]
+    fn three_small_boards_in_a_row_win() {
+        // Player 0 has won the two top left boards and is sent to the top right one.
+        let first_player = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (0, 5),
+            (0, 6),
+            (0, 7),
+        ];
+        let second_player = [
+            (3, 0),
+            (4, 2),
+            (5, 1),
+            (6, 1),
+            (7, 0),
+            (8, 2),
+            (4, 4),
+            (3, 5),
+        ];
+        let stones: Vec<_> = first_player
+            .into_iter()
+            .zip(second_player)
+            .flat_map(|(first, second)| [first, second])
+            .collect();
+        let referee = UltimateReferee::new();
+        let mut board = place(&stones);
+        assert_eq!(referee.get_winner(&board), None);
+        assert_eq!(referee.get_legal_moves(&board).len(), 7);
+        let placement = PointPlacement { row: 0, column: 8 };
+        assert_eq!(
+            referee.receive_move(&mut board, placement, 0),
+            GameResult::Victory
+        );
+        assert_eq!(referee.get_winner(&board), Some(0));
+        assert!(referee.get_legal_moves(&board).is_empty());
+    }
+
+    #[test]
+    fn taking_back_a_stone_restores_the_position() {
+        let (before, _) = play(&[(4, 4), (3, 3)]);
+        let (mut after, _) = play(&[(4, 4), (3, 3), (0, 1)]);
+        after[PointPlacement { row: 0, column: 1 }] = None.into();
+        assert_eq!(after, before);
+        assert_eq!(
+            UltimateReferee::new().get_legal_moves(&after),
+            UltimateReferee::new().get_legal_moves(&before)
+        );
+    }
+
+    #[test]
+    fn plain_board_shows_players() {
+        let (board, _) = play(&[(4, 4), (3, 3)]);
+        let plain_board = UltimateState::new(&board).to_plain_board();
+        assert_eq!(
+            plain_board[PointPlacement { row: 4, column: 4 }],
+            Some(0).into()
+        );
+        assert_eq!(
+            plain_board[PointPlacement { row: 3, column: 3 }],
+            Some(1).into()
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use crate::interfaces::{
+    GameResult, GameState, PlayerID, PointPlacement, TicTacToeReferee,
+};
+
+type NodeIndex = usize;
+
+const ROOT: NodeIndex = 0;
+const INFINITY: u32 = u32::MAX;
+
+/// Outcome the proof-number search tries to prove for the player to move at the root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Goal {
+    Win,
+    NotLose,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    children: Vec<NodeIndex>,
+    /// Number of moves until the game ends once the node is solved, assuming that the
+    /// winning side hurries and the losing side delays.
+    length: u32,
+    /// Number of leaves that must be proven at least to prove the goal.
+    proof: u32,
+    /// Number of leaves that must be disproven at least to disprove the goal.
+    disproof: u32,
+    /// The placement that led from the parent to this node.
+    placement: Option<PointPlacement>,
+    player_to_move: PlayerID,
+}
+
+impl Node {
+    fn new_leaf(placement: Option<PointPlacement>, player_to_move: PlayerID) -> Self {
+        Self {
+            children: Vec::new(),
+            length: 0,
+            proof: 1,
+            disproof: 1,
+            placement,
+            player_to_move,
+        }
+    }
+
+    fn new_terminal(placement: PointPlacement, is_proven: bool) -> Self {
+        let (proof, disproof) = if is_proven {
+            (0, INFINITY)
+        } else {
+            (INFINITY, 0)
+        };
+        Self {
+            children: Vec::new(),
+            length: 0,
+            proof,
+            disproof,
+            placement: Some(placement),
+            player_to_move: 0,
+        }
+    }
+
+    fn is_solved(&self) -> bool {
+        self.proof == 0 || self.disproof == 0
+    }
+}
+
+/// Solved position, see `ProofNumberSearch::solve`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// Game-theoretic value of the position for the player to move, i.e. `Victory`,
+    /// `Draw` or `Defeat`.
+    pub result: GameResult,
+    /// A line of moves proving `result`. Since the search stops at the first proof, the
+    /// winning side need not win as fast as possible, nor the losing side resist as
+    /// long as possible.
+    pub moves: Vec<PointPlacement>,
+}
+
+/// Solver telling whether a position is won, drawn or lost with perfect play.
+///
+/// Proof-number search grows a game tree towards the leaves that are cheapest to prove
+/// or disprove the goal, i.e. the positions in which the fewest moves need to be
+/// looked at. It does so until the goal is settled or the tree has reached its maximum
+/// number of nodes. Telling a draw from a defeat takes a second search, which proves
+/// that the player to move does not lose.
+///
+/// The search is meant for two players, who may place several stones per turn.
+pub struct ProofNumberSearch<'solver> {
+    max_nodes: usize,
+    nodes: Vec<Node>,
+    other_id: PlayerID,
+    referee: &'solver dyn TicTacToeReferee,
+    self_id: PlayerID,
+}
+
+impl<'solver> ProofNumberSearch<'solver> {
+    /// Creates a solver for positions in which `self_id` is to move against `other_id`.
+    ///
+    /// Each search gives up once its tree has grown to `max_nodes` nodes.
+    #[must_use]
+    pub fn new(
+        max_nodes: usize,
+        other_id: PlayerID,
+        referee: &'solver dyn TicTacToeReferee,
+        self_id: PlayerID,
+    ) -> Self {
+        Self {
+            max_nodes,
+            nodes: Vec::new(),
+            other_id,
+            referee,
+            self_id,
+        }
+    }
+
+    /// Solves `board` with the player given as `self_id` to move.
+    ///
+    /// Returns `None` if a search ran out of nodes before the position was solved.
+    pub fn solve(&mut self, board: &GameState) -> Option<Solution> {
+        let result = if self.search(board, Goal::Win)? {
+            GameResult::Victory
+        } else if self.search(board, Goal::NotLose)? {
+            GameResult::Draw
+        } else {
+            GameResult::Defeat
+        };
+        Some(Solution {
+            result,
+            moves: self.get_principal_variation(),
+        })
+    }
+
+    /// Returns whether `goal` holds, or `None` if the search ran out of nodes.
+    fn search(&mut self, board: &GameState, goal: Goal) -> Option<bool> {
+        self.nodes.clear();
+        self.nodes.push(Node::new_leaf(None, self.self_id));
+        if self.referee.get_legal_moves(board).is_empty() {
+            // Nobody can move, so the game is drawn.
+            let is_proven = goal == Goal::NotLose;
+            self.nodes[ROOT].proof = if is_proven { 0 } else { INFINITY };
+            self.nodes[ROOT].disproof = if is_proven { INFINITY } else { 0 };
+        }
+        while !self.nodes[ROOT].is_solved() {
+            if self.nodes.len() >= self.max_nodes {
+                return None;
+            }
+            let (path, mut leaf_board) = self.select_most_proving_node(board);
+            self.expand(
+                *path.last().expect("Path starts at the root."),
+                &mut leaf_board,
+                goal,
+            );
+            for &node in path.iter().rev() {
+                self.update(node);
+            }
+        }
+        Some(self.nodes[ROOT].proof == 0)
+    }
+
+    fn is_or_node(&self, node: NodeIndex) -> bool {
+        self.nodes[node].player_to_move == self.self_id
+    }
+
+    /// Descends to the leaf that helps most with settling the goal.
+    ///
+    /// Returns the path from the root to the leaf together with the board of the leaf.
+    fn select_most_proving_node(
+        &self,
+        board: &GameState,
+    ) -> (Vec<NodeIndex>, GameState) {
+        let mut board = board.clone();
+        let mut path = vec![ROOT];
+        let mut node = ROOT;
+        while !self.nodes[node].children.is_empty() {
+            let children = &self.nodes[node].children;
+            let next = if self.is_or_node(node) {
+                children
+                    .iter()
+                    .min_by_key(|&&child| self.nodes[child].proof)
+            } else {
+                children
+                    .iter()
+                    .min_by_key(|&&child| self.nodes[child].disproof)
+            };
+            let next = *next.expect("Children are not empty.");
+            let placement = self.nodes[next]
+                .placement
+                .expect("Only the root has no placement.");
+            self.referee.receive_move(
+                &mut board,
+                placement,
+                self.nodes[node].player_to_move,
+            );
+            path.push(next);
+            node = next;
+        }
+        (path, board)
+    }
+
+    fn expand(&mut self, node: NodeIndex, board: &mut GameState, goal: Goal) {
+        let player = self.nodes[node].player_to_move;
+        let opponent = if player == self.self_id {
+            self.other_id
+        } else {
+            self.self_id
+        };
+        let mut children = Vec::new();
+        for pp in self.referee.get_legal_moves(board) {
+            let old_entry = board[pp];
+            let winner = match self.referee.receive_move(board, pp, player) {
+                GameResult::Victory => Some(Some(player)),
+                GameResult::Defeat | GameResult::IllegalMove => Some(Some(opponent)),
+                GameResult::Draw => Some(None),
+                GameResult::Undecided
+                    if self.referee.get_legal_moves(board).is_empty() =>
+                {
+                    Some(None)
+                }
+                GameResult::Undecided => None,
+            };
+            let child = match winner {
+                Some(winner) => {
+                    let is_proven = match goal {
+                        Goal::Win => winner == Some(self.self_id),
+                        Goal::NotLose => winner != Some(self.other_id),
+                    };
+                    Node::new_terminal(pp, is_proven)
+                }
+                None if self.referee.is_turn_over(board) => {
+                    Node::new_leaf(Some(pp), opponent)
+                }
+                None => Node::new_leaf(Some(pp), player),
+            };
+            board[pp] = old_entry;
+            children.push(self.nodes.len());
+            self.nodes.push(child);
+        }
+        self.nodes[node].children = children;
+    }
+
+    /// Recomputes the proof and disproof number of `node` from its children.
+    fn update(&mut self, node: NodeIndex) {
+        let children = &self.nodes[node].children;
+        if children.is_empty() {
+            return;
+        }
+        let proofs = children.iter().map(|&child| self.nodes[child].proof);
+        let disproofs = children.iter().map(|&child| self.nodes[child].disproof);
+        // Sums saturate, so that any infinite number keeps the sum infinite.
+        let (proof, disproof) = if self.is_or_node(node) {
+            let proof = proofs.min().unwrap_or(INFINITY);
+            (proof, disproofs.fold(0, u32::saturating_add))
+        } else {
+            let disproof = disproofs.min().unwrap_or(INFINITY);
+            (proofs.fold(0, u32::saturating_add), disproof)
+        };
+        self.nodes[node].proof = proof;
+        self.nodes[node].disproof = disproof;
+        if let Some(child) = self.get_best_child(node) {
+            self.nodes[node].length = 1 + self.nodes[child].length;
+        }
+    }
+
+    /// Returns the child a player following the proof of a solved `node` moves to.
+    ///
+    /// Among the children solved so far, the side reaching its goal picks the shortest
+    /// way to get there, the other side the longest one.
+    fn get_best_child(&self, node: NodeIndex) -> Option<NodeIndex> {
+        let current = &self.nodes[node];
+        if !current.is_solved() {
+            return None;
+        }
+        let is_proven = current.proof == 0;
+        let solved_children = current
+            .children
+            .iter()
+            .copied()
+            .filter(|&child| (self.nodes[child].proof == 0) == is_proven);
+        if is_proven == self.is_or_node(node) {
+            solved_children.min_by_key(|&child| self.nodes[child].length)
+        } else {
+            solved_children.max_by_key(|&child| self.nodes[child].length)
+        }
+    }
+
+    fn get_principal_variation(&self) -> Vec<PointPlacement> {
+        let mut moves = Vec::new();
+        let mut node = ROOT;
+        while let Some(child) = self.get_best_child(node) {
+            moves.push(
+                self.nodes[child]
+                    .placement
+                    .expect("Only the root has no placement."),
+            );
+            node = child;
+        }
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::parse_mask;
+    use crate::referee::{ConnectReferee, MisereReferee, NaiveReferee, TurnSchedule};
+    use rstest::*;
+
+    #[rstest]
+    // immediate win
+    #[case("00.\n11.\n...", GameResult::Victory)]
+    // winning beats blocking
+    #[case("0..\n11.\n0.0", GameResult::Victory)]
+    // fork of the opponent
+    #[case("00.\n1..\n1.0", GameResult::Defeat)]
+    // own fork two moves ahead
+    #[case("0..\n...\n..1", GameResult::Victory)]
+    // defending opposite corners
+    #[case("0..\n.1.\n..0", GameResult::Draw)]
+    #[case("...\n...\n...", GameResult::Draw)]
+    fn positions_are_solved(#[case] mask: &str, #[case] expected: GameResult) {
+        let referee = NaiveReferee::new(3);
+        let board = parse_mask(mask).unwrap();
+        let mut solver = ProofNumberSearch::new(1_000_000, 0, &referee, 1);
+        let solution = solver.solve(&board).unwrap();
+        assert_eq!(solution.result, expected);
+        // The proving line ends the game: wins and defeats on the move of the winner,
+        // draws on a full board.
+        let nmoves = solution.moves.len();
+        let nfree = board.iter_2d().filter(|(_, cell)| cell.is_free()).count();
+        match expected {
+            GameResult::Victory => assert!(!nmoves.is_multiple_of(2)),
+            GameResult::Defeat => assert!(nmoves > 0 && nmoves.is_multiple_of(2)),
+            _ => assert_eq!(nmoves, nfree),
+        }
+    }
+
+    #[test]
+    fn proving_moves_are_legal_and_end_the_game() {
+        let referee = NaiveReferee::new(3);
+        let mut board = parse_mask("0..\n...\n..1").unwrap();
+        let mut solver = ProofNumberSearch::new(1_000_000, 0, &referee, 1);
+        let solution = solver.solve(&board).unwrap();
+        let mut result = GameResult::Undecided;
+        for (player, pp) in [1, 0].into_iter().cycle().zip(&solution.moves) {
+            assert_eq!(result, GameResult::Undecided);
+            result = referee.receive_move(&mut board, *pp, player);
+        }
+        assert_eq!(result, GameResult::Victory);
+        assert_eq!(referee.get_winner(&board), Some(1));
+    }
+
+    #[test]
+    fn search_gives_up_without_enough_nodes() {
+        let referee = NaiveReferee::new(3);
+        let board = GameState::new(3, 3, None);
+        let mut solver = ProofNumberSearch::new(100, 0, &referee, 1);
+        assert_eq!(solver.solve(&board), None);
+    }
+
+    #[test]
+    fn misere_is_solved() {
+        // Each move completes a line but the one in the bottom right corner.
+        let referee = MisereReferee::new(Box::new(NaiveReferee::new(3)));
+        let board = parse_mask("00.\n11.\n10.").unwrap();
+        let mut solver = ProofNumberSearch::new(1_000, 1, &referee, 0);
+        let solution = solver.solve(&board).unwrap();
+        assert_eq!(solution.result, GameResult::Victory);
+        assert_eq!(solution.moves[0], PointPlacement { row: 2, column: 2 });
+    }
+
+    #[test]
+    fn both_stones_of_a_turn_are_played() {
+        // Neither player can complete a line with a single stone, but with two.
+        let referee = ConnectReferee::new(
+            Box::new(NaiveReferee::new(3)),
+            TurnSchedule::new(1, 2),
+        );
+        let board = parse_mask("0..\n...\n11.").unwrap();
+        let mut solver = ProofNumberSearch::new(100_000, 1, &referee, 0);
+        let solution = solver.solve(&board).unwrap();
+        assert_eq!(solution.result, GameResult::Victory);
+        assert_eq!(solution.moves.len(), 2);
+    }
+}
@@ -0,0 +1,616 @@
+use crate::book::OpeningBook;
+use crate::config::{ArenaSpec, PlayerResources, PlayerSpec, RefereeSpec, RuleSpec};
+use crate::interfaces::{
+    BoardSizeT, GameResult, GameState, Player, PlayerID, TicTacToeArena,
+    TicTacToeReferee,
+};
+use crate::player::book::BookPlayer;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::fmt;
+
+/// Elo points per natural-log unit of the odds of winning.
+const ELO_PER_LOG_ODDS: f64 = 400.0 / std::f64::consts::LN_10;
+/// Quantile of the standard normal distribution for 95% confidence intervals.
+const Z_95: f64 = 1.96;
+const MAX_RATING_ITERATIONS: usize = 10_000;
+const RATING_TOLERANCE: f64 = 1e-9;
+
+/// Everything about a tournament except for its participants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TournamentConfig {
+    pub arena_spec: ArenaSpec,
+    pub ncolumns: BoardSizeT,
+    pub ngames_per_pairing: u32,
+    pub nrows: BoardSizeT,
+    pub referee_spec: RefereeSpec,
+    pub rules: RuleSpec,
+    /// Seed from which the seeds of all games are derived.
+    pub seed: u64,
+}
+
+/// Result of one game between two participants, identified by their index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameRecord {
+    pub first: usize,
+    pub second: usize,
+    pub starting: usize,
+    pub winner: Option<usize>,
+    /// Seed of all random decisions in the game, see `Tournament::run`.
+    pub seed: u64,
+}
+
+/// Wins, draws and losses of one participant against another.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PairResult {
+    pub draws: u32,
+    pub losses: u32,
+    pub wins: u32,
+}
+
+impl PairResult {
+    #[must_use]
+    pub fn get_number_of_games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Points scored, counting a draw as half a win.
+    #[must_use]
+    pub fn get_points(&self) -> f64 {
+        f64::from(self.wins) + 0.5 * f64::from(self.draws)
+    }
+}
+
+/// Elo rating with the half-width of its 95% confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub elo: f64,
+    pub error_margin: f64,
+}
+
+/// Round-robin tournament in which every participant meets every other one.
+///
+/// Each pairing plays `ngames_per_pairing` games, alternating who makes the first
+/// move. Players are built afresh for every game, so no game influences another.
+pub struct Tournament {
+    book: Option<OpeningBook>,
+    config: TournamentConfig,
+    participants: Vec<PlayerSpec>,
+}
+
+impl Tournament {
+    /// Creates a new tournament.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are less than two participants, if the rules cannot
+    /// be combined or if a participant cannot play by them.
+    pub fn new(
+        config: TournamentConfig,
+        participants: Vec<PlayerSpec>,
+    ) -> anyhow::Result<Self> {
+        Self::new_with_book(None, config, participants)
+    }
+
+    /// Creates a new tournament whose participants consult `book` before moving.
+    ///
+    /// # Errors
+    ///
+    /// See `Tournament::new`.
+    pub fn new_with_book(
+        book: Option<OpeningBook>,
+        config: TournamentConfig,
+        participants: Vec<PlayerSpec>,
+    ) -> anyhow::Result<Self> {
+        if participants.len() < 2 {
+            anyhow::bail!("A tournament needs at least two participants!");
+        }
+        config.rules.check()?;
+        if let Some(spec) = participants
+            .iter()
+            .find(|spec| !spec.supports_rules(config.rules))
+        {
+            anyhow::bail!("Player '{spec}' does not support the rules of the game!");
+        }
+        let board = GameState::new(config.nrows, config.ncolumns, None);
+        if let Some(spec) = participants
+            .iter()
+            .find(|spec| !spec.supports_board(&board))
+        {
+            anyhow::bail!("Player '{spec}' does not support the board!");
+        }
+        Ok(Self {
+            book,
+            config,
+            participants,
+        })
+    }
+
+    /// Plays all games, calling `on_game_finished` after each of them.
+    ///
+    /// The games are numbered consecutively, starting with zero, and each one is
+    /// seeded with the seed of the tournament plus its number. A game can be replayed
+    /// outside of the tournament by giving its seed to `PlayerResources::new_with_seed`
+    /// and the first generator split off a generator seeded with it to
+    /// `ArenaSpec::build`, letting player 0 be the first participant.
+    pub fn run(
+        &self,
+        mut on_game_finished: impl FnMut(&GameRecord),
+    ) -> TournamentResults {
+        let mut results = TournamentResults::new(
+            self.participants.iter().map(ToString::to_string).collect(),
+        );
+        let mut seed = self.config.seed;
+        for first in 0..self.participants.len() {
+            for second in (first + 1)..self.participants.len() {
+                for game in 0..self.config.ngames_per_pairing {
+                    let starting = if game % 2 == 0 { first } else { second };
+                    let winner = self.play_game(first, second, starting, seed);
+                    let record = GameRecord {
+                        first,
+                        second,
+                        starting,
+                        winner,
+                        seed,
+                    };
+                    seed = seed.wrapping_add(1);
+                    results.record(&record);
+                    on_game_finished(&record);
+                }
+            }
+        }
+        results
+    }
+
+    /// Plays one game and returns the index of the winner, if there is one.
+    fn play_game(
+        &self,
+        first: usize,
+        second: usize,
+        starting: usize,
+        seed: u64,
+    ) -> Option<usize> {
+        const FIRST_ID: PlayerID = 0;
+        const SECOND_ID: PlayerID = 1;
+        let config = &self.config;
+        let starting_id = if starting == first {
+            FIRST_ID
+        } else {
+            SECOND_ID
+        };
+        let rules = RuleSpec {
+            first_player: starting_id,
+            ..config.rules
+        };
+        let book_referee = config.referee_spec.build(rules);
+        let mut first_resources = PlayerResources::new_with_seed(
+            &[FIRST_ID, SECOND_ID],
+            config.referee_spec,
+            rules,
+            seed,
+            FIRST_ID,
+        );
+        let mut second_resources = PlayerResources::new_with_seed(
+            &[FIRST_ID, SECOND_ID],
+            config.referee_spec,
+            rules,
+            seed,
+            SECOND_ID,
+        );
+        let first_book_rng = first_resources.split_rng();
+        let mut first_player = self.consult_book(
+            first_resources.build_player(self.participants[first]),
+            &*book_referee,
+            first_book_rng,
+            self.participants[first],
+        );
+        let second_book_rng = second_resources.split_rng();
+        let mut second_player = self.consult_book(
+            second_resources.build_player(self.participants[second]),
+            &*book_referee,
+            second_book_rng,
+            self.participants[second],
+        );
+        let mut referee = config.referee_spec.build(rules);
+        let mut arena = config.arena_spec.build(
+            GameState::new(config.nrows, config.ncolumns, None),
+            vec![&mut *first_player, &mut *second_player],
+            &mut *referee,
+            StdRng::from_rng(&mut StdRng::seed_from_u64(seed)),
+            starting_id,
+        );
+
+        let (result, player_id) = play_until_decided(&mut *arena);
+        let winner_id = match result {
+            GameResult::Victory => Some(player_id),
+            GameResult::Defeat | GameResult::IllegalMove => {
+                Some(if player_id == FIRST_ID {
+                    SECOND_ID
+                } else {
+                    FIRST_ID
+                })
+            }
+            GameResult::Draw | GameResult::Undecided => None,
+        };
+        winner_id.map(|id| if id == FIRST_ID { first } else { second })
+    }
+
+    /// Lets `player` consult the opening book of the tournament, if there is one.
+    ///
+    /// Human players always make their own moves.
+    fn consult_book<'player>(
+        &'player self,
+        player: Box<dyn Player + 'player>,
+        referee: &'player dyn TicTacToeReferee,
+        rng: StdRng,
+        spec: PlayerSpec,
+    ) -> Box<dyn Player + 'player> {
+        match &self.book {
+            Some(book) if spec != PlayerSpec::Human => {
+                Box::new(BookPlayer::new_with_rng(book, player, referee, rng))
+            }
+            _ => player,
+        }
+    }
+}
+
+/// Lets the arena's players move until the game is decided.
+///
+/// Returns the final result together with the player that caused it.
+pub fn play_until_decided(arena: &mut dyn TicTacToeArena) -> (GameResult, PlayerID) {
+    loop {
+        let (result, player_id, _) = arena.do_next_move();
+        if result != GameResult::Undecided {
+            return (result, player_id);
+        }
+    }
+}
+
+/// Cross table of all games played in a tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentResults {
+    names: Vec<String>,
+    /// `table[i][j]` holds the results of participant `i` against participant `j`.
+    table: Vec<Vec<PairResult>>,
+}
+
+impl TournamentResults {
+    #[must_use]
+    pub fn new(names: Vec<String>) -> Self {
+        let nparticipants = names.len();
+        Self {
+            names,
+            table: vec![vec![PairResult::default(); nparticipants]; nparticipants],
+        }
+    }
+
+    pub fn record(&mut self, record: &GameRecord) {
+        let (first, second) = (record.first, record.second);
+        match record.winner {
+            Some(winner) if winner == first => {
+                self.table[first][second].wins += 1;
+                self.table[second][first].losses += 1;
+            }
+            Some(_) => {
+                self.table[first][second].losses += 1;
+                self.table[second][first].wins += 1;
+            }
+            None => {
+                self.table[first][second].draws += 1;
+                self.table[second][first].draws += 1;
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn get_pair_result(&self, participant: usize, opponent: usize) -> PairResult {
+        self.table[participant][opponent]
+    }
+
+    #[must_use]
+    pub fn get_total_result(&self, participant: usize) -> PairResult {
+        self.table[participant]
+            .iter()
+            .fold(PairResult::default(), |acc, cur| PairResult {
+                draws: acc.draws + cur.draws,
+                losses: acc.losses + cur.losses,
+                wins: acc.wins + cur.wins,
+            })
+    }
+
+    /// Computes Elo ratings from all games, with an average rating of 0.
+    ///
+    /// The ratings maximise the likelihood of the observed results under the Elo
+    /// model, counting draws as half a win. To keep ratings finite for participants
+    /// that won or lost every game, each pairing that was played is credited with one
+    /// additional virtual draw. The confidence intervals are derived from the Fisher
+    /// information of the fitted model.
+    #[must_use]
+    pub fn compute_ratings(&self) -> Vec<Rating> {
+        let nparticipants = self.names.len();
+        let games = |i: usize, j: usize| {
+            let ngames = self.table[i][j].get_number_of_games();
+            if ngames == 0 {
+                0.0
+            } else {
+                f64::from(ngames) + 1.0
+            }
+        };
+        let points = |i: usize, j: usize| {
+            let ngames = self.table[i][j].get_number_of_games();
+            if ngames == 0 {
+                0.0
+            } else {
+                self.table[i][j].get_points() + 0.5
+            }
+        };
+
+        // Minorisation-maximisation algorithm for the Bradley-Terry model.
+        let mut strengths = vec![1.0; nparticipants];
+        for _ in 0..MAX_RATING_ITERATIONS {
+            let mut max_change: f64 = 0.0;
+            for i in 0..nparticipants {
+                let total_points: f64 = (0..nparticipants).map(|j| points(i, j)).sum();
+                let denominator: f64 = (0..nparticipants)
+                    .map(|j| games(i, j) / (strengths[i] + strengths[j]))
+                    .sum();
+                if denominator == 0.0 {
+                    continue;
+                }
+                let new_strength = total_points / denominator;
+                max_change = max_change.max((new_strength / strengths[i]).ln().abs());
+                strengths[i] = new_strength;
+            }
+            if max_change < RATING_TOLERANCE {
+                break;
+            }
+        }
+
+        let log_strengths: Vec<f64> = strengths.iter().map(|s| s.ln()).collect();
+        #[allow(clippy::cast_precision_loss)]
+        let mean = log_strengths.iter().sum::<f64>() / nparticipants as f64;
+        (0..nparticipants)
+            .map(|i| {
+                let information: f64 = (0..nparticipants)
+                    .map(|j| {
+                        let expected = strengths[i] / (strengths[i] + strengths[j]);
+                        games(i, j) * expected * (1.0 - expected)
+                    })
+                    .sum();
+                let error_margin = if information > 0.0 {
+                    Z_95 * ELO_PER_LOG_ODDS / information.sqrt()
+                } else {
+                    f64::INFINITY
+                };
+                Rating {
+                    elo: ELO_PER_LOG_ODDS * (log_strengths[i] - mean),
+                    error_margin,
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for TournamentResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self.names.iter().map(String::len).max().unwrap_or(0);
+        let ratings = self.compute_ratings();
+
+        write!(f, "{:name_width$}", "")?;
+        for idx in 0..self.names.len() {
+            write!(f, " | {:^11}", format!("#{idx}"))?;
+        }
+        writeln!(f, " | {:^11} | {:^14}", "total", "Elo")?;
+
+        for (i, name) in self.names.iter().enumerate() {
+            write!(f, "{name:name_width$}")?;
+            for j in 0..self.names.len() {
+                if i == j {
+                    write!(f, " | {:^11}", "-")?;
+                } else {
+                    write!(f, " | {:^11}", format_pair_result(self.table[i][j]))?;
+                }
+            }
+            let rating = ratings[i];
+            writeln!(
+                f,
+                " | {:^11} | {:>6.0} ± {:<5.0}",
+                format_pair_result(self.get_total_result(i)),
+                rating.elo,
+                rating.error_margin
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn format_pair_result(result: PairResult) -> String {
+    format!("{}/{}/{}", result.wins, result.draws, result.losses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::PointPlacement;
+    use rstest::*;
+
+    fn results_from_scores(wins: u32, draws: u32, losses: u32) -> TournamentResults {
+        let mut results = TournamentResults::new(vec!["a".into(), "b".into()]);
+        let outcomes = [(wins, Some(0)), (draws, None), (losses, Some(1))];
+        for (count, winner) in outcomes {
+            for _ in 0..count {
+                results.record(&GameRecord {
+                    first: 0,
+                    second: 1,
+                    starting: 0,
+                    winner,
+                    seed: 0,
+                });
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn equal_results_give_equal_ratings() {
+        let ratings = results_from_scores(10, 5, 10).compute_ratings();
+        assert!(ratings[0].elo.abs() < 1e-6);
+        assert!(ratings[1].elo.abs() < 1e-6);
+        assert!(ratings[0].error_margin > 0.0);
+    }
+
+    #[rstest]
+    #[case(300, 0, 100)]
+    #[case(700, 100, 200)]
+    fn rating_difference_matches_score(
+        #[case] wins: u32,
+        #[case] draws: u32,
+        #[case] losses: u32,
+    ) {
+        let ratings = results_from_scores(wins, draws, losses).compute_ratings();
+        // Both cases score 75% (ignoring the virtual draw), i.e. about 191 Elo.
+        let difference = ratings[0].elo - ratings[1].elo;
+        assert!((difference - 190.8).abs() < 2.0, "Got {difference}");
+    }
+
+    #[test]
+    fn perfect_scores_give_finite_ratings() {
+        let ratings = results_from_scores(10, 0, 0).compute_ratings();
+        assert!(ratings[0].elo.is_finite());
+        assert!(ratings[0].elo > ratings[1].elo);
+    }
+
+    #[test]
+    fn more_games_narrow_the_confidence_interval() {
+        let few = results_from_scores(4, 2, 4).compute_ratings();
+        let many = results_from_scores(40, 20, 40).compute_ratings();
+        assert!(many[0].error_margin < few[0].error_margin);
+    }
+
+    #[test]
+    fn tournament_plays_every_pairing() {
+        let config = TournamentConfig {
+            arena_spec: ArenaSpec::Exploiting,
+            ncolumns: 3,
+            ngames_per_pairing: 4,
+            nrows: 3,
+            referee_spec: RefereeSpec::Naive,
+            rules: RuleSpec::new(3),
+            seed: 0,
+        };
+        let participants = vec![
+            PlayerSpec::Guessing,
+            PlayerSpec::OneLookahead,
+            PlayerSpec::MinMax { depth: 2 },
+        ];
+        let tournament = Tournament::new(config, participants).unwrap();
+        let mut records = Vec::new();
+        let results = tournament.run(|record| records.push(*record));
+
+        assert_eq!(records.len(), 12);
+        for i in 0..3 {
+            assert_eq!(results.get_total_result(i).get_number_of_games(), 8);
+        }
+        let starting_counts = records.iter().filter(|r| r.starting == r.first).count();
+        assert_eq!(starting_counts, 6);
+    }
+
+    #[test]
+    fn tournament_players_follow_the_book() {
+        let config = TournamentConfig {
+            arena_spec: ArenaSpec::Exploiting,
+            ncolumns: 3,
+            ngames_per_pairing: 2,
+            nrows: 3,
+            referee_spec: RefereeSpec::Naive,
+            rules: RuleSpec::new(3),
+            seed: 0,
+        };
+        // Whoever starts completes the top row, as the book tells the other player to
+        // play in the bottom row.
+        let mut book = OpeningBook::new();
+        for starting_id in [0, 1] {
+            let mut board = GameState::new(3, 3, None);
+            for (idx, column) in [0, 2, 1, 0, 2].into_iter().enumerate() {
+                let (row, player_id) = if idx % 2 == 0 {
+                    (0, starting_id)
+                } else {
+                    (2, 1 - starting_id)
+                };
+                let pp = PointPlacement { row, column };
+                book.add_move(&board, pp, 1.0);
+                board[pp] = Some(player_id).into();
+            }
+        }
+        let participants = vec![PlayerSpec::Guessing, PlayerSpec::Guessing];
+        let tournament =
+            Tournament::new_with_book(Some(book), config, participants).unwrap();
+        let mut records = Vec::new();
+        tournament.run(|record| records.push(*record));
+        assert_eq!(records.len(), 2);
+        for record in records {
+            assert_eq!(record.winner, Some(record.starting));
+        }
+    }
+
+    #[test]
+    fn tournaments_are_reproducible() {
+        let config = TournamentConfig {
+            arena_spec: ArenaSpec::Exploring,
+            ncolumns: 4,
+            ngames_per_pairing: 4,
+            nrows: 4,
+            referee_spec: RefereeSpec::Naive,
+            rules: RuleSpec::new(3),
+            seed: 42,
+        };
+        let participants = vec![
+            PlayerSpec::UCT { niterations: 50 },
+            PlayerSpec::CountBoundMCTS { nsamples: 5 },
+        ];
+        let run = || {
+            let tournament = Tournament::new(config, participants.clone()).unwrap();
+            let mut records = Vec::new();
+            tournament.run(|record| records.push(*record));
+            records
+        };
+        let records = run();
+        assert_eq!(
+            records.iter().map(|record| record.seed).collect::<Vec<_>>(),
+            [42, 43, 44, 45]
+        );
+        assert_eq!(run(), records);
+    }
+
+    #[test]
+    fn tournament_needs_two_participants() {
+        let config = TournamentConfig {
+            arena_spec: ArenaSpec::Exploiting,
+            ncolumns: 3,
+            ngames_per_pairing: 1,
+            nrows: 3,
+            referee_spec: RefereeSpec::Naive,
+            rules: RuleSpec::new(3),
+            seed: 0,
+        };
+        assert!(Tournament::new(config, vec![PlayerSpec::Guessing]).is_err());
+    }
+
+    #[test]
+    fn tournament_players_must_support_the_board() {
+        let config = TournamentConfig {
+            arena_spec: ArenaSpec::Exploiting,
+            ncolumns: 12,
+            ngames_per_pairing: 1,
+            nrows: 12,
+            referee_spec: RefereeSpec::Naive,
+            rules: RuleSpec::new(3),
+            seed: 0,
+        };
+        let participants = vec![
+            PlayerSpec::Guessing,
+            PlayerSpec::BitBoardMinMax { depth: 1 },
+        ];
+        assert!(Tournament::new(config, participants).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::interfaces::{
+    BoardStateEntry, GameResult, GameState, PlayerID, PointPlacement, SearchPosition,
+    TicTacToeReferee,
+};
+use std::hash::{Hash, Hasher};
+
+const SEED: u64 = 0x5851_f42d_4c95_7f2d;
+
+/// Returns the Zobrist key of `player` occupying the cell with the given index.
+///
+/// Instead of a table of random numbers, the keys are derived by a `SplitMix64` step.
+/// This gives equally well distributed keys for any board size and player ID.
+fn get_key(cell_index: usize, player: PlayerID) -> u64 {
+    let mut z = SEED
+        .wrapping_add((cell_index as u64) << 16 | u64::from(player))
+        .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn get_entry_key(board: &GameState, pp: PointPlacement, entry: BoardStateEntry) -> u64 {
+    match Option::<PlayerID>::from(entry) {
+        Some(player) => {
+            let cell_index = usize::from(pp.row)
+                * usize::from(board.get_number_of_columns())
+                + usize::from(pp.column);
+            get_key(cell_index, player)
+        }
+        None => 0,
+    }
+}
+
+/// `GameState` together with its Zobrist hash and its number of free cells.
+///
+/// The hash is the XOR of the keys of all stones on the board. It is updated in
+/// constant time whenever a stone is placed or removed, so that hashing the position
+/// does not need to look at the whole board. Likewise, the number of free cells is
+/// passed to `TicTacToeReferee::receive_counted_move`, so that referees need not count
+/// them.
+#[derive(Debug, Clone)]
+pub struct ZobristGameState {
+    board: GameState,
+    hash: u64,
+    nfree_cells: usize,
+}
+
+impl ZobristGameState {
+    #[must_use]
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Accounts for `placement` having changed from `old_entry` to `new_entry`.
+    fn update(
+        &mut self,
+        placement: PointPlacement,
+        old_entry: BoardStateEntry,
+        new_entry: BoardStateEntry,
+    ) {
+        self.hash ^= get_entry_key(&self.board, placement, old_entry)
+            ^ get_entry_key(&self.board, placement, new_entry);
+        self.nfree_cells += usize::from(new_entry.is_free());
+        self.nfree_cells -= usize::from(old_entry.is_free());
+    }
+
+    fn compute_hash(board: &GameState) -> u64 {
+        board
+            .iter_2d()
+            .map(|(pp, entry)| get_entry_key(board, pp, *entry))
+            .fold(0, |hash, key| hash ^ key)
+    }
+}
+
+impl SearchPosition for ZobristGameState {
+    fn from_game_state(board: &GameState) -> Self {
+        Self {
+            board: board.clone(),
+            hash: Self::compute_hash(board),
+            nfree_cells: board.iter_2d().filter(|(_, cell)| cell.is_free()).count(),
+        }
+    }
+
+    fn get_game_state(&self) -> &GameState {
+        &self.board
+    }
+
+    fn receive_move(
+        &mut self,
+        referee: &dyn TicTacToeReferee,
+        placement: PointPlacement,
+        player: PlayerID,
+    ) -> GameResult {
+        let in_bounds = placement.row < self.board.get_number_of_rows()
+            && placement.column < self.board.get_number_of_columns();
+        if !in_bounds {
+            return referee.receive_move(&mut self.board, placement, player);
+        }
+        let old_entry = self.board[placement];
+        let result = referee.receive_counted_move(
+            &mut self.board,
+            placement,
+            player,
+            self.nfree_cells,
+        );
+        let new_entry = self.board[placement];
+        self.update(placement, old_entry, new_entry);
+        result
+    }
+
+    fn set_entry(&mut self, placement: PointPlacement, entry: BoardStateEntry) {
+        let old_entry = self.board[placement];
+        self.board[placement] = entry;
+        self.update(placement, old_entry, entry);
+    }
+}
+
+impl PartialEq for ZobristGameState {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.board == other.board
+    }
+}
+
+impl Eq for ZobristGameState {}
+
+impl Hash for ZobristGameState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::referee::{IncrementalReferee, NaiveReferee};
+
+    #[test]
+    fn hash_is_updated_incrementally() {
+        let referee = NaiveReferee::new(3);
+        let empty_board = GameState::new(4, 5, None);
+        let mut position = ZobristGameState::from_game_state(&empty_board);
+        let moves = [(0, 0, 0), (1, 3, 1), (2, 4, 0), (3, 1, 1)];
+
+        for (row, column, player) in moves {
+            let pp = PointPlacement { row, column };
+            position.receive_move(&referee, pp, player);
+            let recomputed =
+                ZobristGameState::from_game_state(position.get_game_state());
+            assert_eq!(position.get_hash(), recomputed.get_hash());
+        }
+        for (row, column, _) in moves {
+            position.set_entry(PointPlacement { row, column }, None.into());
+        }
+        assert_eq!(position, ZobristGameState::from_game_state(&empty_board));
+        assert_eq!(position.get_hash(), 0);
+    }
+
+    #[test]
+    fn illegal_moves_keep_the_hash() {
+        let referee = NaiveReferee::new(3);
+        let board =
+            GameState::new_with_values([[Some(0), None], [None, None]]).unwrap();
+        let mut position = ZobristGameState::from_game_state(&board);
+        let hash = position.get_hash();
+
+        let pp = PointPlacement { row: 0, column: 0 };
+        let result = position.receive_move(&referee, pp, 1);
+        assert_eq!(result, GameResult::IllegalMove);
+        assert_eq!(position.get_hash(), hash);
+    }
+
+    #[test]
+    fn transpositions_have_the_same_hash() {
+        let board = GameState::new(3, 3, None);
+        let first = PointPlacement { row: 0, column: 1 };
+        let second = PointPlacement { row: 2, column: 2 };
+        let mut position = ZobristGameState::from_game_state(&board);
+        let mut other_position = position.clone();
+
+        position.set_entry(first, Some(0).into());
+        position.set_entry(second, Some(1).into());
+        other_position.set_entry(second, Some(1).into());
+        other_position.set_entry(first, Some(0).into());
+        assert_eq!(position.get_hash(), other_position.get_hash());
+
+        let mut swapped_position = ZobristGameState::from_game_state(&board);
+        swapped_position.set_entry(first, Some(1).into());
+        swapped_position.set_entry(second, Some(0).into());
+        assert_ne!(position.get_hash(), swapped_position.get_hash());
+    }
+
+    #[test]
+    fn free_cells_are_counted_for_the_referee() {
+        let referee = IncrementalReferee::new(3);
+        let board =
+            GameState::new_with_values([[Some(0), None], [None, None]]).unwrap();
+        let mut position = ZobristGameState::from_game_state(&board);
+        let last = PointPlacement { row: 1, column: 1 };
+
+        position.receive_move(&referee, PointPlacement { row: 0, column: 1 }, 1);
+        position.receive_move(&referee, PointPlacement { row: 1, column: 0 }, 0);
+        assert_eq!(position.receive_move(&referee, last, 1), GameResult::Draw);
+        position.set_entry(last, None.into());
+        assert_eq!(position.nfree_cells, 1);
+        assert_eq!(position.receive_move(&referee, last, 1), GameResult::Draw);
+    }
+}